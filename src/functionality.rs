@@ -18,6 +18,29 @@
  * along with this program.  If not, see <https://www.gnu.org/licenses/>.
  */
 
+/// Writes files and copies directories atomically.
+///
+/// This submodule writes to a temporary path next to the destination and renames it into place,
+/// so an interrupted run leaves either the old or the new file. The previous contents are backed
+/// up in the run journal first so that the change can be undone.
+///
+/// # See Also
+/// - `journal`: Records the changed files.
+pub mod atomic;
+
+/// Installs packages from the Arch User Repository.
+///
+/// This submodule bootstraps an AUR helper (paru or yay) by building it with `makepkg`, and
+/// installs the AUR packages a profile lists with it, one at a time and always as the
+/// unprivileged user, reporting the status of each package.
+///
+/// # Example
+/// ```
+/// use glwiz::functionality::aur::AurHelper;
+/// assert_eq!(AurHelper::Paru.package(), "paru-bin");
+/// ```
+pub mod aur;
+
 /// Provides utilities for executing system commands, including `sudo` and user-level operations.
 ///
 /// This submodule contains functions to run commands with elevated privileges or as the current user,
@@ -32,6 +55,20 @@
 /// ```
 pub mod commands;
 
+/// Defines the package bundles that can be combined at install time.
+///
+/// This submodule lists named sets of logical package names (base, developer, multimedia,
+/// security and server-minimal), parses the bundles given with `--bundles` or at the prompt,
+/// and shows them with their contents.
+///
+/// # Example
+/// ```
+/// use glwiz::functionality::bundle::{bundle_packages, parse_bundles};
+/// let bundles = parse_bundles("dev,security").unwrap();
+/// assert!(bundle_packages(&bundles).contains(&"clang".to_string()));
+/// ```
+pub mod bundle;
+
 /// Manages user and root configuration file setups, such as `.zshrc` and `.vimrc`.
 ///
 /// This submodule handles copying configuration files to user and root directories, ensuring
@@ -42,6 +79,21 @@ pub mod commands;
 /// - `user_cfg`: For managing user-specific configuration data.
 pub mod configs;
 
+/// Removes unwanted preinstalled packages.
+///
+/// This submodule removes the games, office suites and crash-report tools a distribution
+/// preinstalls, from a per-distribution default list and a profile list. The packages depending
+/// on them are listed for confirmation before removal, and orphaned dependencies are cleaned up
+/// afterwards.
+pub mod debloat;
+
+/// Identifies the distribution being configured from its os-release file.
+///
+/// This submodule provides the `Distro` struct, which records the specific distribution and
+/// version (e.g., Linux Mint 22) together with the supported family whose package manager it
+/// uses (e.g., Debian), so that derivatives are handled like their base distribution.
+pub mod distro;
+
 /// Retrieves environment variables for system configuration.
 ///
 /// This submodule provides functions to access environment variables like `USER` and `HOME`,
@@ -49,6 +101,35 @@ pub mod configs;
 /// robust error handling.
 pub mod env;
 
+/// Exports the setup plan into formats that can be reviewed and run without glwiz.
+///
+/// This submodule renders the tasks described by `plan` as a standalone POSIX shell script with
+/// the bundled configuration files embedded, keeping the overwrite checks of a regular run, or
+/// as an Ansible playbook with the configuration files saved alongside it.
+///
+/// # See Also
+/// - `plan`: Describes the setup tasks without performing them.
+pub mod export;
+
+/// Installs Flatpak applications from Flathub.
+///
+/// This submodule installs `flatpak` itself, adds the Flathub remote to the system or user
+/// installation, and installs the apps a profile lists, given by app ID or by a package name
+/// the package name table maps to one, reporting the status of each app.
+///
+/// # Example
+/// ```
+/// use glwiz::functionality::flatpak::is_app_id;
+/// assert!(is_app_id("org.mozilla.firefox"));
+/// ```
+pub mod flatpak;
+
+/// Handles Ctrl-C and `SIGTERM` so that the current step can finish.
+///
+/// This submodule installs signal handlers that record the interrupt instead of terminating
+/// glwiz, letting the task in progress complete before the run stops. A second Ctrl-C aborts.
+pub mod interrupt;
+
 /// Configures iptables firewall rules for network security.
 ///
 /// This submodule sets up and applies iptables rules by copying configuration files and
@@ -56,6 +137,98 @@ pub mod env;
 /// user confirmation.
 pub mod iptables;
 
+/// Records setup runs so that they can be resumed or undone.
+///
+/// This submodule saves the tasks a run completed and the files it changed, with backups of
+/// their previous contents, under `~/.local/state/glwiz`. An interrupted run can be continued
+/// with `glwiz run --resume`, and `glwiz undo` restores the changed files.
+pub mod journal;
+
+/// Prevents concurrent glwiz runs.
+///
+/// This submodule takes an exclusive lock on `/run/glwiz.lock` and a per-user lock file for the
+/// duration of a run, reporting the PID and start time of a running instance, or waiting for it
+/// to finish.
+pub mod lock;
+
+/// Generates NixOS and home-manager modules instead of changing a NixOS system.
+///
+/// This submodule renders the setup as a module covering the packages, the default shell, Oh My
+/// Zsh with its plugins, the firewall and zram swap, ready to be imported and applied with
+/// `nixos-rebuild`.
+///
+/// # Example
+/// ```
+/// use glwiz::functionality::nixos::{nixos_module, NixosSettings};
+/// assert!(nixos_module(&NixosSettings::default()).contains("programs.zsh"));
+/// ```
+pub mod nixos;
+
+/// Maps package names between distributions.
+///
+/// This submodule loads the bundled table of per-family package names, extended by the user’s
+/// own, and resolves package lists through it, reporting the mapped and unknown names.
+///
+/// # Example
+/// ```
+/// use glwiz::functionality::package_map::PackageMap;
+/// let map = PackageMap::parse("[fd]\ndebian = \"fd-find\"\n").unwrap();
+/// assert_eq!(map.resolve(&["fd"], "debian").packages, vec!["fd-find"]);
+/// ```
+pub mod package_map;
+
+/// Queries and changes installed packages through the distribution’s package manager.
+///
+/// This submodule defines the `PackageManager` trait with implementations for `pacman`, `apt`,
+/// `dnf`, `zypper`, `apk`, `xbps` and Portage, which refresh the package index, install, remove and search packages, and parse
+/// the installed packages into typed results.
+///
+/// # Example
+/// ```
+/// use glwiz::functionality::package_manager::pacman::parse_query;
+/// assert_eq!(parse_query("zsh 5.9-5\n")[0].name, "zsh");
+/// ```
+pub mod package_manager;
+
+/// Tunes the package manager before software is installed.
+///
+/// This submodule applies settings such as parallel downloads and colored output to
+/// `/etc/pacman.conf` and `/etc/dnf/dnf.conf` through line-by-line edits that keep the rest of
+/// each file intact, and writes the APT recommends policy to a configuration snippet.
+pub mod package_tuning;
+
+/// Describes the default setup as a list of tasks without performing them.
+///
+/// This submodule defines the `PlanTask` and `PlanStep` types and builds the plan of the default
+/// setup, in the same order and with the same task names as a regular run. It is used by the
+/// exporters to render the setup into other formats.
+///
+/// # Example
+/// ```
+/// use glwiz::functionality::plan::default_plan;
+/// let plan = default_plan(&["zsh"]);
+/// assert_eq!(plan.last().unwrap().name, "ZRAM swap setup");
+/// ```
+pub mod plan;
+
+/// Describes a machine’s configuration as a portable profile.
+///
+/// This submodule defines the `Profile` struct, stored as TOML, which records the packages,
+/// login shell, Oh My Zsh settings and configuration file contents of a machine. A profile is
+/// captured with `snapshot` and applied by the setup in place of the interactive prompts and the
+/// bundled configuration files.
+///
+/// # See Also
+/// - `snapshot`: Captures a profile from the running system.
+pub mod profile;
+
+/// Checks that the setup can run before anything is changed.
+///
+/// This submodule verifies the required commands, `sudo` rights, free disk space, package
+/// manager state, writable destinations, network access and a supported distribution, and
+/// reports every problem at once.
+pub mod preflight;
+
 /// Contains utility functions for common setup tasks, such as user input handling and license display.
 ///
 /// This submodule provides functions for tasks like prompting users for software lists, validating
@@ -69,6 +242,44 @@ pub mod iptables;
 /// ```
 pub mod prog_fun;
 
+/// Applies the setup to remote hosts over SSH.
+///
+/// This submodule copies the glwiz binary and the bundled configuration assets to a remote host,
+/// runs the setup there, and streams the output prefixed with the host name. It also parses
+/// inventory files listing several hosts.
+///
+/// # Example
+/// ```
+/// use glwiz::functionality::remote::parse_inventory;
+/// assert_eq!(parse_inventory("admin@lab1\n"), vec!["admin@lab1"]);
+/// ```
+pub mod remote;
+
+/// Enables optional and third-party package repositories.
+///
+/// This submodule enables the repositories a profile opts into: `multilib` in `pacman.conf` on
+/// Arch, the `contrib`, `non-free` and `non-free-firmware` components in deb822 APT sources on
+/// Debian, and RPM Fusion free and nonfree on Fedora through their release packages and signing
+/// keys. The package index is refreshed afterwards.
+///
+/// # Example
+/// ```
+/// use glwiz::functionality::repositories::enable_pacman_repo;
+/// let conf = "#[multilib]\n#Include = /etc/pacman.d/mirrorlist\n";
+/// assert_eq!(
+///     enable_pacman_repo(conf, "multilib").unwrap(),
+///     "[multilib]\nInclude = /etc/pacman.d/mirrorlist\n"
+/// );
+/// ```
+pub mod repositories;
+
+/// Detects the init system and enables services with it.
+///
+/// This submodule provides the `InitSystem` enum, which tells systemd, OpenRC and runit systems
+/// apart so that services such as `iptables` or `zram-init` are enabled with `systemctl`,
+/// `rc-update` or a service symlink as appropriate.
+pub mod service;
+
 /// Manages shell configurations, including Zsh setup and plugin installations.
 ///
 /// This submodule handles changing the default shell to Zsh, installing Oh My Zsh, and adding
@@ -79,6 +290,13 @@ pub mod prog_fun;
 /// - `configs`: For related configuration file management.
 pub mod shell;
 
+/// Captures the configuration of the running system as a profile.
+///
+/// This submodule queries the package manager for explicitly installed packages and reads the
+/// login shell, Oh My Zsh settings, dotfiles, iptables rules and zram configuration, producing a
+/// `profile::Profile` that can be applied to another machine.
+pub mod snapshot;
+
 /// Handles software package installation across supported Linux distributions.
 ///
 /// This submodule detects the Linux distribution and uses the appropriate package manager
@@ -86,6 +304,20 @@ pub mod shell;
 /// package lists.
 pub mod software;
 
+/// Describes the system being configured: the running host or a mounted root filesystem.
+///
+/// This submodule provides the `Target` struct, which resolves system paths such as
+/// `/etc/iptables` or `/root` below an alternate root (e.g., `/mnt` after `pacstrap`) and runs
+/// commands inside it through the distribution’s chroot tool.
+///
+/// # Example
+/// ```
+/// use glwiz::functionality::target::Target;
+/// let target = Target::host();
+/// assert_eq!(target.path("/etc/passwd"), "/etc/passwd");
+/// ```
+pub mod target;
+
 /// Tracks and validates the results of configuration tasks.
 ///
 /// This submodule defines the `TaskResult` struct and functions to validate task outcomes,
@@ -93,6 +325,17 @@ pub mod software;
 /// summarize task statuses.
 pub mod task;
 
+/// Renders templated configuration files with per-host variables.
+///
+/// This submodule provides a small template language with `{{ name }}` substitutions and
+/// `{{ if }}`/`{{ else }}`/`{{ end }}` conditionals. Configuration sources ending in `.tmpl` are
+/// rendered with variables such as the user, hostname, distribution, memory size and CPU count,
+/// plus any values defined in a profile, before they are installed.
+///
+/// # See Also
+/// - `profile`: Defines additional template variables under `[vars]`.
+pub mod template;
+
 /// Stores and manages user configuration data, such as username and home directory.
 ///
 /// This submodule provides the `UserCfg` struct to hold user-specific data, with methods to
@@ -113,8 +356,8 @@ pub mod user_cfg;
 /// ```should_panic
 /// // Requires ../configs/zram-generator.conf and sudo privileges.
 /// // Use integration tests for actual validation.
-/// use glwiz::functionality::zram::zram_swap_setup;
-/// let result = zram_swap_setup();
+/// use glwiz::functionality::{target::Target, template::TemplateVars, zram::{zram_swap_setup, ZRAM_CONFIG_SOURCE}};
+/// let result = zram_swap_setup(ZRAM_CONFIG_SOURCE, &TemplateVars::new(), &Target::host(), None);
 /// assert_eq!(result, 0); // Success if config exists and no overwrite
 /// ```
 pub mod zram;
@@ -0,0 +1,189 @@
+/*
+ * GLWiz - The ultimate post-installation setup assistant for GNU/Linux popular distros,
+ * streamlining your configuration process with ease and precision.
+ * 
+ * Copyright (C) 2025  Andrew Kushyk
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published
+ * by the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use super::{
+    commands::{command_exists, run_sudo_command, run_sudo_command_with_stdin, run_user_command},
+    journal::record_change,
+};
+use std::{fs, io::Write, path::Path};
+
+// Suffixes of the temporary copy being written and of the replaced directory being removed.
+const TMP_SUFFIX: &str = ".glwiz-tmp";
+const OLD_SUFFIX: &str = ".glwiz-old";
+
+/// Writes a file owned by the current user atomically.
+///
+/// The contents are written to a temporary file next to `path`, flushed to disk, and renamed
+/// over `path`, so an interruption leaves either the old or the new file, never a partial one.
+/// The previous contents are backed up by `journal::record_change` first.
+///
+/// # Arguments
+/// * `path` - The file to write (e.g., `"/home/user/.zshrc"`).
+/// * `contents` - The new contents.
+///
+/// # Returns
+/// * `Ok(())` - The file was replaced.
+/// * `Err(String)` - An error message if the backup, the write or the rename failed; the
+///   temporary file is removed.
+///
+/// # Example
+/// ```
+/// use glwiz::functionality::atomic::write_file;
+/// let path = std::env::temp_dir().join("glwiz-atomic-example");
+/// write_file(path.to_str().unwrap(), "set number\n").unwrap();
+/// assert_eq!(std::fs::read_to_string(&path).unwrap(), "set number\n");
+/// std::fs::remove_file(path).unwrap();
+/// ```
+pub fn write_file(path: &str, contents: &str) -> Result<(), String> {
+    record_change(path, false)?;
+    let tmp = format!("{}{}", path, TMP_SUFFIX);
+    let result = fs::File::create(&tmp)
+        .and_then(|mut file| {
+            file.write_all(contents.as_bytes())?;
+            file.sync_all()
+        })
+        .and_then(|()| fs::rename(&tmp, path));
+    if let Err(e) = result {
+        let _ = fs::remove_file(&tmp);
+        return Err(format!("Failed to write {}: {}", path, e));
+    }
+    Ok(())
+}
+
+/// Writes a root-owned file atomically with `sudo`.
+///
+/// The contents are written with `sudo tee` to a temporary file next to `path`, which is then
+/// renamed over `path` with `sudo mv`. The previous contents are backed up first. When `path`
+/// exists, the temporary file starts as a `cp -a` copy of it, so the replacement keeps its
+/// owner, mode and SELinux label (e.g., `passwd_file_t` on `/etc/passwd`). A new file is
+/// labelled with `restorecon` when it is available.
+///
+/// # Arguments
+/// * `path` - The file to write (e.g., `"/etc/iptables/iptables.rules"`).
+/// * `contents` - The new contents.
+///
+/// # Returns
+/// * `Ok(())` - The file was replaced.
+/// * `Err(String)` - An error message if the backup, the write or the rename failed; the
+///   temporary file is removed.
+///
+/// # Example
+/// ```no_run
+/// // Requires sudo privileges.
+/// use glwiz::functionality::atomic::write_file_as_root;
+/// write_file_as_root("/etc/systemd/zram-generator.conf", "[zram0]\n").unwrap();
+/// ```
+pub fn write_file_as_root(path: &str, contents: &str) -> Result<(), String> {
+    record_change(path, true)?;
+    let tmp = format!("{}{}", path, TMP_SUFFIX);
+    let exists = run_sudo_command("test", &["-e", path]).is_ok();
+    // `tee` truncates the copy in place, keeping the attributes `cp -a` preserved.
+    let copied = if exists {
+        run_sudo_command("cp", &["-a", path, &tmp])
+    } else {
+        Ok(())
+    };
+    let result = copied
+        .and_then(|()| run_sudo_command_with_stdin("tee", &[&tmp], contents.to_string()))
+        .and_then(|()| run_sudo_command("mv", &["-f", &tmp, path]));
+    if result.is_err() {
+        let _ = run_sudo_command("rm", &["-f", &tmp]);
+    } else if !exists && command_exists("restorecon") {
+        let _ = run_sudo_command("restorecon", &[path]);
+    }
+    result
+}
+
+/// Copies a file or directory owned by the current user, replacing `dest` atomically.
+///
+/// # Arguments
+/// * `src` - The file or directory to copy.
+/// * `dest` - The path to replace.
+///
+/// # Returns
+/// * `Ok(())` - `dest` now holds a copy of `src`.
+/// * `Err(String)` - An error message if the copy failed; `dest` is left unchanged.
+pub fn copy_path(src: &str, dest: &str) -> Result<(), String> {
+    record_change(dest, false)?;
+    let is_dir = Path::new(dest).is_dir();
+    replace_with_copy(src, dest, is_dir, run_user_command)
+}
+
+/// Copies a file or directory with `sudo`, replacing `dest` atomically.
+///
+/// The copy is made next to `dest` and renamed into place. An existing directory is moved
+/// aside first and removed once the copy is in place, since a directory cannot be renamed over
+/// a non-empty one. The previous contents are backed up first.
+///
+/// # Arguments
+/// * `src` - The file or directory to copy (e.g., `"/home/user/.oh-my-zsh"`).
+/// * `dest` - The path to replace (e.g., `"/root/.oh-my-zsh"`).
+///
+/// # Returns
+/// * `Ok(())` - `dest` now holds a copy of `src`.
+/// * `Err(String)` - An error message if the copy failed; `dest` is left unchanged.
+///
+/// # Example
+/// ```no_run
+/// // Requires sudo privileges.
+/// use glwiz::functionality::atomic::copy_path_as_root;
+/// copy_path_as_root("/home/user/.vimrc", "/root/.vimrc").unwrap();
+/// ```
+pub fn copy_path_as_root(src: &str, dest: &str) -> Result<(), String> {
+    record_change(dest, true)?;
+    let is_dir = run_sudo_command("test", &["-d", dest]).is_ok();
+    replace_with_copy(src, dest, is_dir, run_sudo_command)
+}
+
+// Copies `src` next to `dest` and renames it into place with the given command runner.
+fn replace_with_copy(
+    src: &str,
+    dest: &str,
+    dest_is_dir: bool,
+    run: fn(&str, &[&str]) -> Result<(), String>,
+) -> Result<(), String> {
+    let tmp = format!("{}{}", dest, TMP_SUFFIX);
+    let old = format!("{}{}", dest, OLD_SUFFIX);
+
+    let _ = run("rm", &["-rf", &tmp]);
+    if let Err(e) = run("cp", &["-a", src, &tmp]) {
+        let _ = run("rm", &["-rf", &tmp]);
+        return Err(e);
+    }
+
+    if dest_is_dir {
+        let _ = run("rm", &["-rf", &old]);
+        if let Err(e) = run("mv", &["-T", dest, &old]) {
+            let _ = run("rm", &["-rf", &tmp]);
+            return Err(e);
+        }
+        if let Err(e) = run("mv", &["-T", &tmp, dest]) {
+            let _ = run("mv", &["-T", &old, dest]);
+            let _ = run("rm", &["-rf", &tmp]);
+            return Err(e);
+        }
+        let _ = run("rm", &["-rf", &old]);
+        Ok(())
+    } else {
+        run("mv", &["-fT", &tmp, dest]).inspect_err(|_| {
+            let _ = run("rm", &["-rf", &tmp]);
+        })
+    }
+}
@@ -0,0 +1,275 @@
+/*
+ * GLWiz - The ultimate post-installation setup assistant for GNU/Linux popular distros,
+ * streamlining your configuration process with ease and precision.
+ * 
+ * Copyright (C) 2025  Andrew Kushyk
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published
+ * by the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+use super::{
+    commands::{capture_user_command, command_exists, run_user_command, stream_user_command_in},
+    distro::DistroFamily,
+    env::get_env_var,
+    package_manager::{
+        PackageManager,
+        pacman::{self, Pacman},
+    },
+    software::{PackageStatus, SoftwareReport, suggest_packages},
+    target::Target,
+};
+use colored::Colorize;
+use serde::{Deserialize, Serialize};
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+/// The Arch User Repository, which serves one Git repository per package.
+pub const AUR_URL: &str = "https://aur.archlinux.org";
+
+/// The packages needed to build AUR packages with `makepkg`.
+pub const AUR_BUILD_DEPENDENCIES: [&str; 2] = ["base-devel", "git"];
+
+/// The AUR helper that builds and installs AUR packages.
+///
+/// # Example
+/// ```
+/// use glwiz::functionality::aur::AurHelper;
+/// assert_eq!(AurHelper::default().command(), "paru");
+/// assert_eq!(AurHelper::Yay.package(), "yay-bin");
+/// ```
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AurHelper {
+    /// paru (https://github.com/Morganamilo/paru).
+    #[default]
+    Paru,
+    /// yay (https://github.com/Jguer/yay).
+    Yay,
+}
+
+impl AurHelper {
+    /// Returns the helper’s executable.
+    pub fn command(self) -> &'static str {
+        match self {
+            Self::Paru => "paru",
+            Self::Yay => "yay",
+        }
+    }
+
+    /// Returns the AUR package the helper is bootstrapped from. The prebuilt `-bin` packages
+    /// avoid compiling the helper itself.
+    pub fn package(self) -> &'static str {
+        match self {
+            Self::Paru => "paru-bin",
+            Self::Yay => "yay-bin",
+        }
+    }
+
+    /// Returns the arguments that install AUR packages without prompting or showing the
+    /// build files for review.
+    pub fn install_args(self) -> &'static [&'static str] {
+        match self {
+            Self::Paru => &["-S", "--aur", "--needed", "--noconfirm", "--skipreview"],
+            Self::Yay => &[
+                "-S",
+                "--aur",
+                "--needed",
+                "--noconfirm",
+                "--answerdiff",
+                "None",
+                "--answerclean",
+                "None",
+            ],
+        }
+    }
+}
+
+/// Returns the directory AUR packages are cloned and built in.
+///
+/// This is `$XDG_CACHE_HOME/glwiz/aur`, or `~/.cache/glwiz/aur` if `XDG_CACHE_HOME` is unset.
+///
+/// # Returns
+/// * `Ok(PathBuf)` - The build directory.
+/// * `Err(String)` - An error message if neither `XDG_CACHE_HOME` nor `HOME` is set.
+pub fn aur_build_dir() -> Result<PathBuf, String> {
+    match get_env_var("XDG_CACHE_HOME") {
+        Ok(dir) if !dir.is_empty() => Ok(Path::new(&dir).join("glwiz/aur")),
+        _ => get_env_var("HOME").map(|home| Path::new(&home).join(".cache/glwiz/aur")),
+    }
+}
+
+// Installs the AUR helper unless it is already available: the build dependencies come from
+// the repositories, then the helper is cloned from the AUR and built with `makepkg` as the
+// current user, which installs the result through `sudo pacman`.
+fn bootstrap_helper(helper: AurHelper, target: &Target) -> Result<(), String> {
+    if command_exists(helper.command()) {
+        return Ok(());
+    }
+    println!("Bootstrapping {} from the AUR...", helper.command());
+    Pacman::new(target.clone()).install(&AUR_BUILD_DEPENDENCIES)?;
+
+    let build_dir = aur_build_dir()?;
+    fs::create_dir_all(&build_dir)
+        .map_err(|e| format!("Failed to create {}: {}", build_dir.display(), e))?;
+    let package_dir = build_dir.join(helper.package());
+    if package_dir.exists() {
+        fs::remove_dir_all(&package_dir)
+            .map_err(|e| format!("Failed to remove {}: {}", package_dir.display(), e))?;
+    }
+    let package_dir = package_dir.to_string_lossy();
+    let url = format!("{}/{}.git", AUR_URL, helper.package());
+    run_user_command("git", &["clone", "--depth", "1", &url, &package_dir])?;
+    stream_user_command_in(
+        &package_dir,
+        "makepkg",
+        &["--syncdeps", "--install", "--noconfirm"],
+    )
+}
+
+// Suggests AUR packages close to a name that the AUR does not have.
+fn suggest_aur_packages(helper: AurHelper, name: &str) -> Vec<String> {
+    let candidates: Vec<String> = capture_user_command(helper.command(), &["-Ss", "--aur", name])
+        .map(|output| pacman::parse_search(&output))
+        .unwrap_or_default()
+        .into_iter()
+        .map(|package| package.name)
+        .collect();
+    suggest_packages(name, &candidates)
+}
+
+/// Installs packages from the Arch User Repository with an AUR helper.
+///
+/// This function bootstraps the chosen helper (paru or yay) when it is missing, by installing
+/// `base-devel` and `git`, cloning the helper’s prebuilt package from the AUR and building it
+/// with `makepkg`. It then looks each package up in the AUR and installs it with the helper on
+/// its own, so that one failing build does not stop the others; packages that are already
+/// installed are skipped. The status of every package is printed and returned for the task
+/// result.
+///
+/// Packages are always built as the current user: the function refuses to run as root, and
+/// `makepkg` and the helper only gain root privileges through `sudo pacman` to install what
+/// they built. Other distributions have no AUR, so their AUR packages are skipped with a
+/// warning.
+///
+/// # Arguments
+/// * `packages` - The AUR package names (e.g., `&["visual-studio-code-bin"]`).
+/// * `helper` - The AUR helper to use.
+/// * `distro` - The Linux distribution identifier (e.g., `"arch"`).
+/// * `target` - The system the packages are installed into.
+///
+/// # Returns
+/// A `SoftwareReport` with the status of each package, whose `status` is `0` if all of them
+/// are installed.
+///
+/// # Errors
+/// The report’s status is `1` if:
+/// - glwiz runs as root, or the target is a mounted root.
+/// - The helper cannot be bootstrapped (e.g., a network error or a failed build).
+/// - A package is not in the AUR or fails to build.
+///
+/// # Example
+/// ```no_run
+/// // Requires an Arch Linux system, a regular user with sudo privileges and network access.
+/// use glwiz::functionality::{
+///     aur::{aur_setup, AurHelper},
+///     target::Target,
+/// };
+/// let report = aur_setup(&["visual-studio-code-bin"], AurHelper::Paru, "arch", &Target::host());
+/// assert_eq!(report.status, 0);
+/// ```
+///
+/// # See Also
+/// - `software::software_setup`: Installs the packages from the repositories.
+/// - `profile::ProfilePackages`: Lists the AUR packages.
+pub fn aur_setup(
+    packages: &[&str],
+    helper: AurHelper,
+    distro: &str,
+    target: &Target,
+) -> SoftwareReport {
+    if DistroFamily::from_id(distro) != Some(DistroFamily::Arch) {
+        eprintln!(
+            "{} AUR packages are only installed on Arch Linux, skipping: {}",
+            "warning:".yellow(),
+            packages.join(" ")
+        );
+        return SoftwareReport::new(Vec::new());
+    }
+    if target.is_chroot() {
+        eprintln!(
+            "{} AUR packages can only be built on the running host, not in a mounted root.",
+            "error:".red()
+        );
+        return SoftwareReport::failed();
+    }
+    if unsafe { libc::getuid() } == 0 {
+        eprintln!(
+            "{} AUR packages are never built as root; run glwiz as a regular user.",
+            "error:".red()
+        );
+        return SoftwareReport::failed();
+    }
+    if let Err(e) = bootstrap_helper(helper, target) {
+        eprintln!(
+            "{} Cannot bootstrap {}: {}",
+            "error:".red(),
+            helper.command(),
+            e
+        );
+        return SoftwareReport::failed();
+    }
+    let build_dir = match aur_build_dir() {
+        Ok(dir) => dir.to_string_lossy().into_owned(),
+        Err(e) => {
+            eprintln!("{} {}", "error:".red(), e);
+            return SoftwareReport::failed();
+        }
+    };
+    if let Err(e) = fs::create_dir_all(&build_dir) {
+        eprintln!("{} Failed to create {}: {}", "error:".red(), build_dir, e);
+        return SoftwareReport::failed();
+    }
+
+    let pacman = Pacman::new(target.clone());
+    let mut statuses = Vec::new();
+    for package in packages {
+        let status = if pacman.is_installed(package).unwrap_or(false) {
+            PackageStatus::AlreadyPresent
+        } else if capture_user_command(helper.command(), &["-Si", "--aur", package]).is_err() {
+            PackageStatus::NotFound(suggest_aur_packages(helper, package))
+        } else {
+            println!("Building {} from the AUR...", package.green());
+            let mut args = helper.install_args().to_vec();
+            args.push(package);
+            match stream_user_command_in(&build_dir, helper.command(), &args) {
+                Ok(()) => PackageStatus::Installed,
+                Err(e) => {
+                    eprintln!("{} Cannot build {}: {}", "error:".red(), package, e);
+                    PackageStatus::Failed
+                }
+            }
+        };
+        statuses.push((package.to_string(), status));
+    }
+
+    let report = SoftwareReport::new(statuses);
+    report.print();
+    if report.status == 0 {
+        println!("AUR packages {}.", "installed".green());
+    } else {
+        eprintln!("{} Some AUR packages were not installed.", "error:".red());
+    }
+    report
+}
@@ -0,0 +1,186 @@
+/*
+ * GLWiz - The ultimate post-installation setup assistant for GNU/Linux popular distros,
+ * streamlining your configuration process with ease and precision.
+ * 
+ * Copyright (C) 2025  Andrew Kushyk
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published
+ * by the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+use colored::Colorize;
+
+/// A named set of packages that can be combined with others at install time.
+///
+/// Packages are given by their logical names, which the package name table translates for
+/// each distribution (see `package_map::PackageMap`).
+///
+/// # Fields
+/// * `name` - The bundle name (e.g., `"developer"`).
+/// * `aliases` - Shorter names accepted for the bundle (e.g., `"dev"`).
+/// * `description` - A one-line summary shown when choosing bundles.
+/// * `packages` - The logical package names.
+#[derive(Debug, PartialEq, Eq)]
+pub struct Bundle {
+    pub name: &'static str,
+    pub aliases: &'static [&'static str],
+    pub description: &'static str,
+    pub packages: &'static [&'static str],
+}
+
+/// The bundles that can be selected interactively or with `--bundles`.
+pub const BUNDLES: [Bundle; 5] = [
+    Bundle {
+        name: "base",
+        aliases: &[],
+        description: "Shell, editor and everyday command-line tools",
+        packages: &["zsh", "git", "curl", "vim", "htop", "tmux"],
+    },
+    Bundle {
+        name: "developer",
+        aliases: &["dev"],
+        description: "Compilers, build tools and language runtimes",
+        packages: &[
+            "build-essential",
+            "clang",
+            "git",
+            "python",
+            "pip",
+            "nodejs",
+            "ripgrep",
+            "fd",
+        ],
+    },
+    Bundle {
+        name: "multimedia",
+        aliases: &[],
+        description: "Web browser, image editor, media player and codecs",
+        packages: &["firefox", "gimp", "mpv", "ffmpeg"],
+    },
+    Bundle {
+        name: "security",
+        aliases: &[],
+        description: "Rootkit and malware scanners, auditing and intrusion prevention",
+        packages: &["rkhunter", "lynis", "clamav", "fail2ban"],
+    },
+    Bundle {
+        name: "server-minimal",
+        aliases: &["server"],
+        description: "Remote access and administration essentials",
+        packages: &["openssh", "curl", "vim", "htop", "tmux", "rsync"],
+    },
+];
+
+/// Finds a bundle by its name or one of its aliases, ignoring case.
+///
+/// # Example
+/// ```
+/// use glwiz::functionality::bundle::find_bundle;
+/// assert_eq!(find_bundle("dev").unwrap().name, "developer");
+/// assert_eq!(find_bundle("Security").unwrap().name, "security");
+/// assert!(find_bundle("games").is_none());
+/// ```
+pub fn find_bundle(name: &str) -> Option<&'static Bundle> {
+    let name = name.trim().to_lowercase();
+    BUNDLES
+        .iter()
+        .find(|bundle| bundle.name == name || bundle.aliases.contains(&name.as_str()))
+}
+
+/// Parses a list of bundle names, as given to `--bundles` or typed at the prompt.
+///
+/// Names may be separated by commas or whitespace; a bundle named twice is selected once.
+///
+/// # Arguments
+/// * `names` - The bundle names (e.g., `"dev,security"`).
+///
+/// # Returns
+/// * `Ok(Vec<&Bundle>)` - The selected bundles, in the given order.
+/// * `Err(String)` - An error message naming the unknown bundles and listing the available ones.
+///
+/// # Example
+/// ```
+/// use glwiz::functionality::bundle::parse_bundles;
+/// let bundles = parse_bundles("dev, security dev").unwrap();
+/// let names: Vec<&str> = bundles.iter().map(|bundle| bundle.name).collect();
+/// assert_eq!(names, ["developer", "security"]);
+/// assert!(parse_bundles("dev,games").is_err());
+/// ```
+pub fn parse_bundles(names: &str) -> Result<Vec<&'static Bundle>, String> {
+    let mut bundles: Vec<&'static Bundle> = Vec::new();
+    let mut unknown = Vec::new();
+    for name in names
+        .split(|c: char| c == ',' || c.is_whitespace())
+        .filter(|name| !name.is_empty())
+    {
+        match find_bundle(name) {
+            Some(bundle) if !bundles.contains(&bundle) => bundles.push(bundle),
+            Some(_) => {}
+            None => unknown.push(name),
+        }
+    }
+    if unknown.is_empty() {
+        Ok(bundles)
+    } else {
+        let available: Vec<&str> = BUNDLES.iter().map(|bundle| bundle.name).collect();
+        Err(format!(
+            "Unknown bundle: {} (available: {})",
+            unknown.join(", "),
+            available.join(", ")
+        ))
+    }
+}
+
+/// Returns the packages of the selected bundles, each listed once, in bundle order.
+///
+/// # Example
+/// ```
+/// use glwiz::functionality::bundle::{bundle_packages, find_bundle};
+/// let bundles = [find_bundle("base").unwrap(), find_bundle("server").unwrap()];
+/// assert_eq!(
+///     bundle_packages(&bundles),
+///     ["zsh", "git", "curl", "vim", "htop", "tmux", "openssh", "rsync"]
+/// );
+/// ```
+pub fn bundle_packages(bundles: &[&Bundle]) -> Vec<String> {
+    let mut packages: Vec<String> = Vec::new();
+    for package in bundles.iter().flat_map(|bundle| bundle.packages) {
+        if !packages.iter().any(|listed| listed == package) {
+            packages.push(package.to_string());
+        }
+    }
+    packages
+}
+
+/// Prints bundles with their descriptions and contents.
+///
+/// # Example
+/// ```
+/// use glwiz::functionality::bundle::{print_bundles, BUNDLES};
+/// print_bundles(&BUNDLES.iter().collect::<Vec<_>>());
+/// ```
+pub fn print_bundles(bundles: &[&Bundle]) {
+    for bundle in bundles {
+        let aliases = if bundle.aliases.is_empty() {
+            String::new()
+        } else {
+            format!(" ({})", bundle.aliases.join(", "))
+        };
+        println!(
+            "{}{} - {}",
+            bundle.name.green(),
+            aliases,
+            bundle.description
+        );
+        println!("    {}", bundle.packages.join(" "));
+    }
+}
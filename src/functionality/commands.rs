@@ -21,15 +21,42 @@
 use colored::Colorize;
 use std::{
     io::Write,
+    os::unix::fs::PermissionsExt,
     process::{Command, Stdio},
+    sync::OnceLock,
 };
 
+/// The commands glwiz can gain root privileges with, in order of preference.
+pub const PRIVILEGE_COMMANDS: [&str; 2] = ["sudo", "doas"];
+
+/// Returns the command used to run commands with root privileges.
+///
+/// `sudo` is used when it is installed; otherwise `doas`, the default on Alpine Linux. If
+/// neither is found, `sudo` is returned so that errors name the expected tool. The choice is
+/// made once per run.
+///
+/// # Example
+/// ```
+/// use glwiz::functionality::commands::{privilege_command, PRIVILEGE_COMMANDS};
+/// assert!(PRIVILEGE_COMMANDS.contains(&privilege_command()));
+/// ```
+pub fn privilege_command() -> &'static str {
+    static COMMAND: OnceLock<&'static str> = OnceLock::new();
+    COMMAND.get_or_init(|| {
+        PRIVILEGE_COMMANDS
+            .into_iter()
+            .find(|command| command_exists(command))
+            .unwrap_or(PRIVILEGE_COMMANDS[0])
+    })
+}
+
 /// Executes a system command with `sudo` privileges, requiring elevated permissions.
 ///
 /// This function runs a specified command with `sudo`, passing the provided arguments, and captures
 /// its output. It is used in the "glwiz" project for tasks requiring root access, such as copying
 /// configuration files to system directories or applying iptables rules. The function ensures robust
-/// error handling by reporting command failures with detailed stdout and stderr messages.
+/// error handling by reporting command failures with detailed stdout and stderr messages. On
+/// systems without `sudo`, `doas` is used instead (see `privilege_command`).
 ///
 /// # Arguments
 /// * `command` - The command to execute (e.g., `"cp"`, `"iptables-restore"`).
@@ -59,7 +86,7 @@ use std::{
 /// Use caution when running commands with `sudo`, as they execute with root privileges and can modify
 /// critical system files. Ensure `command` and `args` are validated to prevent unintended consequences.
 pub fn run_sudo_command(command: &str, args: &[&str]) -> Result<(), String> {
-    let output = Command::new("sudo")
+    let output = Command::new(privilege_command())
         .arg(command)
         .args(args)
         .output()
@@ -165,7 +192,7 @@ pub fn run_sudo_command_with_stdin(
     args: &[&str],
     stdin_content: String,
 ) -> Result<(), String> {
-    let mut cmd = Command::new("sudo")
+    let mut cmd = Command::new(privilege_command())
         .arg(command)
         .args(args)
         .stdin(Stdio::piped())
@@ -191,3 +218,330 @@ pub fn run_sudo_command_with_stdin(
         ))
     }
 }
+
+/// Executes a system command with root privileges inside a mounted root filesystem.
+///
+/// This function runs a command inside a system mounted at `root` (e.g., `/mnt` after `pacstrap`
+/// or `debootstrap`). It prefers `arch-chroot`, which prepares the API filesystems itself, and
+/// otherwise falls back to plain `chroot` after bind-mounting `/proc`, `/sys`, `/dev`, `/dev/pts`
+/// and `/etc/resolv.conf` into the root. The bind mounts are released again once the command
+/// finishes, whether it succeeded or not.
+///
+/// # Arguments
+/// * `root` - The directory the target system is mounted at (e.g., `"/mnt"`).
+/// * `command` - The command to execute inside the root (e.g., `"pacman"`).
+/// * `args` - A slice of arguments to pass to the command.
+///
+/// # Returns
+/// * `Ok(())` - The command executed successfully.
+/// * `Err(String)` - An error message describing the failure, including stdout and stderr if applicable.
+///
+/// # Errors
+/// Returns an error if:
+/// - A bind mount cannot be created.
+/// - The chroot tool fails to spawn or the command exits with a non-zero status.
+///
+/// # Example
+/// ```no_run
+/// // Requires a system mounted at /mnt and sudo privileges.
+/// use glwiz::functionality::commands::run_chroot_command;
+/// let result = run_chroot_command("/mnt", "pacman", &["-S", "--noconfirm", "zsh"]);
+/// assert!(result.is_ok());
+/// ```
+///
+/// # See Also
+/// - `target::Target::run_command`: Chooses between the host and a mounted root.
+pub fn run_chroot_command(root: &str, command: &str, args: &[&str]) -> Result<(), String> {
+    chroot_with(root, command, args, run_sudo_command)
+}
+
+/// Executes a system command with root privileges, streaming its output to the terminal.
+///
+/// Unlike `run_sudo_command`, the command’s stdout and stderr are not captured but shown as it
+/// runs, which suits long operations such as compiling packages. The command also reads from
+/// the terminal, so it receives Ctrl-C together with glwiz.
+///
+/// # Arguments
+/// * `command` - The command to execute (e.g., `"emerge"`).
+/// * `args` - A slice of arguments to pass to the command.
+///
+/// # Returns
+/// * `Ok(())` - The command executed successfully.
+/// * `Err(String)` - An error message with the command’s exit status.
+///
+/// # Example
+/// ```no_run
+/// // Requires sudo privileges.
+/// use glwiz::functionality::commands::stream_sudo_command;
+/// stream_sudo_command("emerge", &["--ask=n", "app-shells/zsh"]).unwrap();
+/// ```
+///
+/// # See Also
+/// - `stream_chroot_command`: The same inside a mounted root.
+pub fn stream_sudo_command(command: &str, args: &[&str]) -> Result<(), String> {
+    let status = Command::new(privilege_command())
+        .arg(command)
+        .args(args)
+        .status()
+        .map_err(|e| format!("{} {}: {}", "Failed to execute:".red(), command, e))?;
+    if status.success() {
+        Ok(())
+    } else {
+        Err(format!("Command `{}` failed: {}", command.red(), status))
+    }
+}
+
+/// Executes a system command inside a mounted root, streaming its output to the terminal.
+///
+/// The root is prepared as for `run_chroot_command`.
+///
+/// # Arguments
+/// * `root` - The directory the target system is mounted at (e.g., `"/mnt/gentoo"`).
+/// * `command` - The command to execute inside the root (e.g., `"emerge"`).
+/// * `args` - A slice of arguments to pass to the command.
+///
+/// # Returns
+/// * `Ok(())` - The command executed successfully.
+/// * `Err(String)` - An error message describing the failure.
+///
+/// # Example
+/// ```no_run
+/// // Requires a system mounted at /mnt/gentoo and sudo privileges.
+/// use glwiz::functionality::commands::stream_chroot_command;
+/// stream_chroot_command("/mnt/gentoo", "emerge", &["--ask=n", "app-shells/zsh"]).unwrap();
+/// ```
+pub fn stream_chroot_command(root: &str, command: &str, args: &[&str]) -> Result<(), String> {
+    chroot_with(root, command, args, stream_sudo_command)
+}
+
+/// Executes a command as the current user in a working directory, streaming its output to the
+/// terminal.
+///
+/// The command inherits the terminal, so tools it runs can prompt (e.g., `makepkg` asking for
+/// the `sudo` password to install what it built).
+///
+/// # Arguments
+/// * `dir` - The working directory (e.g., a cloned AUR package).
+/// * `command` - The command to execute (e.g., `"makepkg"`).
+/// * `args` - A slice of arguments to pass to the command.
+///
+/// # Returns
+/// * `Ok(())` - The command executed successfully.
+/// * `Err(String)` - An error message with the command’s exit status.
+///
+/// # Example
+/// ```
+/// use glwiz::functionality::commands::stream_user_command_in;
+/// assert!(stream_user_command_in("/", "true", &[]).is_ok());
+/// assert!(stream_user_command_in("/", "false", &[]).is_err());
+/// ```
+pub fn stream_user_command_in(dir: &str, command: &str, args: &[&str]) -> Result<(), String> {
+    let status = Command::new(command)
+        .args(args)
+        .current_dir(dir)
+        .status()
+        .map_err(|e| format!("{} {}: {}", "Failed to execute:".red(), command, e))?;
+    if status.success() {
+        Ok(())
+    } else {
+        Err(format!("Command `{}` failed: {}", command.red(), status))
+    }
+}
+
+// Runs a command inside a mounted root with `run`, preparing the API filesystems first.
+fn chroot_with(
+    root: &str,
+    command: &str,
+    args: &[&str],
+    run: fn(&str, &[&str]) -> Result<(), String>,
+) -> Result<(), String> {
+    if command_exists("arch-chroot") {
+        let mut chroot_args = vec![root, command];
+        chroot_args.extend_from_slice(args);
+        return run("arch-chroot", &chroot_args);
+    }
+
+    let mut mounted = Vec::new();
+    let mut result = Ok(());
+    for dir in ["/proc", "/sys", "/dev", "/dev/pts", "/etc/resolv.conf"] {
+        let dest = format!("{}{}", root, dir);
+        if !std::path::Path::new(&dest).exists() {
+            continue;
+        }
+        if let Err(e) = run_sudo_command("mount", &["--bind", dir, &dest]) {
+            result = Err(e);
+            break;
+        }
+        mounted.push(dest);
+    }
+
+    if result.is_ok() {
+        let mut chroot_args = vec![root, command];
+        chroot_args.extend_from_slice(args);
+        result = run("chroot", &chroot_args);
+    }
+
+    for dest in mounted.iter().rev() {
+        if let Err(e) = run_sudo_command("umount", &[dest]) {
+            eprintln!("{} Failed to unmount {}: {}", "warning:".yellow(), dest, e);
+        }
+    }
+
+    result
+}
+
+/// Checks whether an executable is available in one of the directories listed in `PATH`.
+///
+/// # Arguments
+/// * `name` - The executable name to look up (e.g., `"arch-chroot"`).
+///
+/// # Returns
+/// * `true` - An executable file with that name exists in `PATH`.
+/// * `false` - The executable could not be found.
+///
+/// # Example
+/// ```
+/// use glwiz::functionality::commands::command_exists;
+/// assert!(command_exists("sh"));
+/// assert!(!command_exists("glwiz-no-such-command"));
+/// ```
+pub fn command_exists(name: &str) -> bool {
+    std::env::var_os("PATH")
+        .map(|paths| {
+            std::env::split_paths(&paths).any(|dir| {
+                dir.join(name)
+                    .metadata()
+                    .map(|m| m.is_file() && m.permissions().mode() & 0o111 != 0)
+                    .unwrap_or(false)
+            })
+        })
+        .unwrap_or(false)
+}
+
+/// Executes a system command as the current user and returns its standard output.
+///
+/// This function is the capturing counterpart of `run_user_command`, used where glwiz needs to
+/// inspect the system rather than change it, for example to list explicitly installed packages.
+///
+/// # Arguments
+/// * `command` - The command to execute (e.g., `"pacman"`).
+/// * `args` - A slice of arguments to pass to the command (e.g., `&["-Qqe"]`).
+///
+/// # Returns
+/// * `Ok(String)` - The command’s stdout, decoded lossily as UTF-8.
+/// * `Err(String)` - An error message describing the failure, including stderr if applicable.
+///
+/// # Example
+/// ```
+/// use glwiz::functionality::commands::capture_user_command;
+/// let output = capture_user_command("echo", &["hello"]).unwrap();
+/// assert_eq!(output.trim(), "hello");
+/// ```
+///
+/// # See Also
+/// - `capture_sudo_command`: The same with `sudo` privileges.
+pub fn capture_user_command(command: &str, args: &[&str]) -> Result<String, String> {
+    capture_output(Command::new(command).args(args), command)
+}
+
+/// Executes a system command with `sudo` privileges and returns its standard output.
+///
+/// # Arguments
+/// * `command` - The command to execute (e.g., `"iptables-save"`).
+/// * `args` - A slice of arguments to pass to the command.
+///
+/// # Returns
+/// * `Ok(String)` - The command’s stdout, decoded lossily as UTF-8.
+/// * `Err(String)` - An error message describing the failure, including stderr if applicable.
+///
+/// # Example
+/// ```no_run
+/// // Requires sudo privileges.
+/// use glwiz::functionality::commands::capture_sudo_command;
+/// let rules = capture_sudo_command("iptables-save", &[]).unwrap();
+/// println!("{}", rules);
+/// ```
+pub fn capture_sudo_command(command: &str, args: &[&str]) -> Result<String, String> {
+    capture_output(
+        Command::new(privilege_command()).arg(command).args(args),
+        command,
+    )
+}
+
+/// Executes a system command with `sudo` privileges and returns its standard output, whatever
+/// its exit status.
+///
+/// Package managers preview a transaction by printing it and then declining it (e.g., `dnf
+/// remove --assumeno`), which ends with a failure status, so only a command that cannot be run
+/// is an error here.
+///
+/// # Arguments
+/// * `command` - The command to execute (e.g., `"dnf"`).
+/// * `args` - A slice of arguments to pass to the command (e.g., `&["autoremove", "--assumeno"]`).
+///
+/// # Returns
+/// * `Ok(String)` - The command’s stdout, decoded lossily as UTF-8.
+/// * `Err(String)` - An error message if the command could not be executed.
+///
+/// # Example
+/// ```no_run
+/// // Requires a Fedora system and sudo privileges.
+/// use glwiz::functionality::commands::capture_sudo_output;
+/// let preview = capture_sudo_output("dnf", &["autoremove", "--assumeno"]).unwrap();
+/// println!("{}", preview);
+/// ```
+pub fn capture_sudo_output(command: &str, args: &[&str]) -> Result<String, String> {
+    Command::new(privilege_command())
+        .arg(command)
+        .args(args)
+        .output()
+        .map(|output| String::from_utf8_lossy(&output.stdout).into_owned())
+        .map_err(|e| format!("{} {}: {}", "Failed to execute:".red(), command, e))
+}
+
+/// Quotes a word for a POSIX shell, leaving plain words untouched.
+///
+/// Words made only of characters the shell does not interpret are returned as they are; any
+/// other word is wrapped in single quotes, with embedded single quotes escaped as `'\''`.
+///
+/// # Arguments
+/// * `word` - The word to quote (e.g., a command-line argument or a file name).
+///
+/// # Returns
+/// A `String` the shell reads back as `word`.
+///
+/// # Example
+/// ```
+/// use glwiz::functionality::commands::sh_quote;
+/// assert_eq!(sh_quote("dev,security"), "dev,security");
+/// assert_eq!(sh_quote("Shell change"), "'Shell change'");
+/// assert_eq!(sh_quote("it's"), "'it'\\''s'");
+/// assert_eq!(sh_quote(""), "''");
+/// ```
+pub fn sh_quote(word: &str) -> String {
+    let plain = !word.is_empty()
+        && word
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || "_./:@+=-,".contains(c));
+    if plain {
+        word.to_string()
+    } else {
+        format!("'{}'", word.replace('\'', "'\\''"))
+    }
+}
+
+// Runs a prepared command and returns its stdout, or an error with its stderr.
+fn capture_output(cmd: &mut Command, command: &str) -> Result<String, String> {
+    let output = cmd
+        .output()
+        .map_err(|e| format!("{} {}: {}", "Failed to execute:".red(), command, e))?;
+    if output.status.success() {
+        Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+    } else {
+        Err(format!(
+            "Command `{}` failed:\nstderr: {}",
+            command.red(),
+            String::from_utf8_lossy(&output.stderr).trim()
+        ))
+    }
+}
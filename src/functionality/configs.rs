@@ -18,22 +18,77 @@
  * along with this program.  If not, see <https://www.gnu.org/licenses/>.
  */
 
-use super::commands::run_sudo_command;
+use super::atomic::{copy_path_as_root, write_file};
+use super::target::Target;
+use super::template::{TEMPLATE_EXTENSION, TemplateVars, prefer_template, read_source};
 use colored::Colorize;
 use std::path::Path;
 
+/// The bundled Zsh configuration, relative to the glwiz binary.
+pub const ZSHRC_SOURCE: &str = "../configs/.zshrc";
+
+/// The bundled Vim configuration, relative to the glwiz binary.
+pub const VIMRC_SOURCE: &str = "../configs/.vimrc";
+
+/// The source files of the configurations glwiz installs.
+///
+/// By default these are the files bundled in `../configs`, or their `.tmpl` variants if present.
+/// When a profile is applied, the files it carries are staged in a temporary directory and used
+/// instead. Sources ending in `.tmpl` are rendered as templates when installed.
+///
+/// # Fields
+/// * `zshrc` - The Zsh configuration installed as `~/.zshrc`.
+/// * `vimrc` - The Vim configuration installed as `~/.vimrc`.
+/// * `iptables` - The iptables rules installed by `iptables::iptables_file_setup`.
+/// * `zram` - The zram-generator configuration installed by `zram::zram_swap_setup`.
+///
+/// # Example
+/// ```
+/// use glwiz::functionality::configs::ConfigSources;
+/// let sources = ConfigSources::default();
+/// assert_eq!(sources.vimrc, "../configs/.vimrc");
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct ConfigSources {
+    pub zshrc: String,
+    pub vimrc: String,
+    pub iptables: String,
+    pub zram: String,
+}
+
+impl Default for ConfigSources {
+    fn default() -> Self {
+        Self {
+            zshrc: prefer_template(ZSHRC_SOURCE),
+            vimrc: prefer_template(VIMRC_SOURCE),
+            iptables: prefer_template(super::iptables::IPTABLES_RULES_SOURCE),
+            zram: prefer_template(super::zram::ZRAM_CONFIG_SOURCE),
+        }
+    }
+}
+
+/// The items copied from the user’s home directory to root’s by `setup_root_config`, with the
+/// description used in logs.
+pub const ROOT_CONFIG_ITEMS: [(&str, &str); 3] = [
+    (".oh-my-zsh", "Root Oh My Zsh"),
+    (".zshrc", "Root Zsh config"),
+    (".vimrc", "Root Vim config"),
+];
+
 /// Copies a configuration file to the user's home directory, ensuring idempotent operation.
 ///
 /// This function copies a specified configuration file (e.g., `.zshrc`, `.vimrc`) to the user’s
 /// home directory, creating a consistent user environment in the "glwiz" project. It checks
 /// for existing files at the destination and prompts the user to overwrite them, preventing
 /// unintended modifications. The function is used for setting up user-specific configurations
-/// like Zsh and Vim settings during post-installation setup.
+/// like Zsh and Vim settings during post-installation setup. A source ending in `.tmpl` is
+/// rendered with `vars` and installed without the extension.
 ///
 /// # Arguments
 /// * `config_path` - The path to the source configuration file (e.g., `"../configs/.zshrc"`).
 /// * `home_dir` - The user’s home directory where the file will be copied (e.g., `"/home/user"`).
 /// * `cfg_name` - A descriptive name for the configuration (e.g., `"zsh"`, `"vim"`) used in logs.
+/// * `vars` - The variables available if the source is a template.
 ///
 /// # Returns
 /// * `0` - The configuration was successfully copied or skipped (user chose not to overwrite).
@@ -42,24 +97,31 @@ use std::path::Path;
 /// # Errors
 /// Returns `1` if:
 /// - The source path is invalid or does not contain a file name.
+/// - The source is a template that fails to render (e.g., an unknown variable).
 /// - The file copy operation fails due to permissions or other I/O errors.
 ///
 /// # Example
 /// ```should_panic
 /// // This example requires a valid ../configs/.zshrc file and write permissions.
 /// // For actual testing, use integration tests with a mocked file system.
-/// use glwiz::functionality::configs::user_config_setup;
-/// let result = user_config_setup("../configs/.zshrc", "/home/user", "zsh");
+/// use glwiz::functionality::{configs::user_config_setup, template::TemplateVars};
+/// let result = user_config_setup("../configs/.zshrc", "/home/user", "zsh", &TemplateVars::new());
 /// assert_eq!(result, 0); // Success if files exist and no overwrite prompt
 /// ```
 ///
 /// # See Also
 /// - `prog_fun::read_input`: Used to prompt the user for overwrite confirmation.
 /// - `setup_root_config`: For configuring the root user’s environment.
-pub fn user_config_setup(config_path: &str, home_dir: &str, cfg_name: &str) -> i8 {
+/// - `template::render`: The template syntax.
+pub fn user_config_setup(
+    config_path: &str,
+    home_dir: &str,
+    cfg_name: &str,
+    vars: &TemplateVars,
+) -> i8 {
     let source = Path::new(config_path);
-    let filename = match source.file_name() {
-        Some(name) => name,
+    let filename = match source.file_name().and_then(|name| name.to_str()) {
+        Some(name) => name.strip_suffix(TEMPLATE_EXTENSION).unwrap_or(name),
         None => {
             eprintln!("{} Invalid path: {}", "error:".red(), config_path);
             return 1;
@@ -80,7 +142,15 @@ pub fn user_config_setup(config_path: &str, home_dir: &str, cfg_name: &str) -> i
         }
     }
 
-    match std::fs::copy(config_path, &dest_path) {
+    let contents = match read_source(config_path, vars) {
+        Ok(contents) => contents,
+        Err(e) => {
+            eprintln!("{} Failed to install {}: {}", "error:".red(), cfg_name, e);
+            return 1;
+        }
+    };
+
+    match write_file(&dest_path.to_string_lossy(), &contents) {
         Ok(_) => {
             println!("{} {}.", cfg_name, "installed".green());
             0
@@ -94,8 +164,9 @@ pub fn user_config_setup(config_path: &str, home_dir: &str, cfg_name: &str) -> i
 
 // Copies a file or directory to a system location using root privileges.
 //
-// This private helper function executes a `cp` command with `sudo` to copy a file or directory
-// from a source to a destination, typically for root-owned locations like `/root`. It is used
+// This private helper function copies a file or directory with `sudo` to a destination, typically
+// for root-owned locations like `/root`. The copy replaces the destination atomically, so an
+// interrupted run never leaves a partially copied directory behind. It is used
 // by `setup_root_config` to set up root user configurations. The function logs success or failure
 // with descriptive messages.
 //
@@ -108,7 +179,7 @@ pub fn user_config_setup(config_path: &str, home_dir: &str, cfg_name: &str) -> i
 // * `0` - The copy operation succeeded.
 // * `1` - The copy operation failed, with an error logged to stderr.
 fn copy_item_as_root(src: &str, dest: &str, description: &str) -> i8 {
-    match run_sudo_command("cp", &["-r", src, dest]) {
+    match copy_path_as_root(src, dest) {
         Ok(_) => {
             println!("{} {}.", description, "created".green());
             0
@@ -129,6 +200,7 @@ fn copy_item_as_root(src: &str, dest: &str, description: &str) -> i8 {
 ///
 /// # Arguments
 /// * `home_dir` - The user’s home directory containing the source configurations (e.g., `"/home/user"`).
+/// * `target` - The system whose root home directory receives the copies.
 ///
 /// # Returns
 /// * `0` - All configurations were successfully copied.
@@ -141,35 +213,19 @@ fn copy_item_as_root(src: &str, dest: &str, description: &str) -> i8 {
 ///
 /// # Example
 /// ```
-/// use glwiz::functionality::configs::setup_root_config;
-/// let result = setup_root_config("/home/user");
+/// use glwiz::functionality::{configs::setup_root_config, target::Target};
+/// let result = setup_root_config("/home/user", &Target::host());
 /// assert_eq!(result, 0); // Root configurations copied successfully
 /// ```
 ///
 /// # See Also
 /// - `commands::run_sudo_command`: Used for executing copy operations with root privileges.
 /// - `user_config_setup`: For setting up user-specific configurations.
-pub fn setup_root_config(home_dir: &str) -> i8 {
-    let items = [
-        (
-            format!("{}/.oh-my-zsh", home_dir),
-            "/root/.oh-my-zsh",
-            "Root Oh My Zsh",
-        ),
-        (
-            format!("{}/.zshrc", home_dir),
-            "/root/.zshrc",
-            "Root Zsh config",
-        ),
-        (
-            format!("{}/.vimrc", home_dir),
-            "/root/.vimrc",
-            "Root Vim config",
-        ),
-    ];
-
-    for (src, dest, desc) in items.iter() {
-        if copy_item_as_root(src, dest, desc) != 0 {
+pub fn setup_root_config(home_dir: &str, target: &Target) -> i8 {
+    for (item, desc) in ROOT_CONFIG_ITEMS {
+        let src = format!("{}/{}", home_dir, item);
+        let dest = target.path(&format!("/root/{}", item));
+        if copy_item_as_root(&src, &dest, desc) != 0 {
             return 1;
         }
     }
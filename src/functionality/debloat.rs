@@ -0,0 +1,259 @@
+/*
+ * GLWiz - The ultimate post-installation setup assistant for GNU/Linux popular distros,
+ * streamlining your configuration process with ease and precision.
+ * 
+ * Copyright (C) 2025  Andrew Kushyk
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published
+ * by the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+use super::{
+    distro::Distro, package_manager::for_family, package_map::PackageMap, prog_fun::read_input,
+    target::Target,
+};
+use colored::Colorize;
+
+/// The packages removed by default, per distribution ID.
+///
+/// They are games, office suites and crash-report or telemetry tools that the distributions
+/// preinstall; only the installed ones are removed. Derivatives use the list of the first
+/// distribution in their `ID_LIKE` with one (e.g., Linux Mint uses Ubuntu’s).
+pub const DEFAULT_DEBLOAT: [(&str, &[&str]); 4] = [
+    (
+        "ubuntu",
+        &[
+            "apport",
+            "whoopsie",
+            "ubuntu-report",
+            "popularity-contest",
+            "kerneloops",
+            "aisleriot",
+            "gnome-mahjongg",
+            "gnome-mines",
+            "gnome-sudoku",
+            "libreoffice-core",
+        ],
+    ),
+    (
+        "debian",
+        &[
+            "popularity-contest",
+            "reportbug",
+            "gnome-games",
+            "libreoffice-core",
+        ],
+    ),
+    (
+        "fedora",
+        &["abrt", "gnome-mahjongg", "gnome-mines", "libreoffice-core"],
+    ),
+    (
+        "opensuse",
+        &[
+            "gnome-mahjongg",
+            "gnome-mines",
+            "iagno",
+            "quadrapassel",
+            "libreoffice",
+        ],
+    ),
+];
+
+/// Returns the default debloat list of a distribution.
+///
+/// # Arguments
+/// * `distro` - The distribution being configured.
+///
+/// # Returns
+/// The packages in `DEFAULT_DEBLOAT` for its ID, or for the first of its `ID_LIKE` entries
+/// with a list; an empty slice if there is none (e.g., on Arch, which preinstalls nothing).
+///
+/// # Example
+/// ```
+/// use glwiz::functionality::{debloat::default_debloat, distro::Distro};
+/// let mint = Distro::from_os_release("ID=linuxmint\nID_LIKE=\"ubuntu debian\"\n").unwrap();
+/// assert!(default_debloat(&mint).contains(&"whoopsie"));
+/// let arch = Distro::from_os_release("ID=arch\n").unwrap();
+/// assert!(default_debloat(&arch).is_empty());
+/// ```
+pub fn default_debloat(distro: &Distro) -> &'static [&'static str] {
+    let id = distro.id.split('-').next().unwrap_or(&distro.id);
+    std::iter::once(id)
+        .chain(distro.id_like.iter().map(String::as_str))
+        .find_map(|id| {
+            DEFAULT_DEBLOAT
+                .iter()
+                .find(|(distro_id, _)| *distro_id == id)
+                .map(|(_, packages)| *packages)
+        })
+        .unwrap_or_default()
+}
+
+/// Returns the packages removed along with the listed ones because they depend on them.
+///
+/// # Arguments
+/// * `listed` - The packages to remove.
+/// * `removal_set` - Every package the removal would remove (see
+///   `PackageManager::removal_set`).
+///
+/// # Returns
+/// The packages of `removal_set` that are not listed, in their order.
+///
+/// # Example
+/// ```
+/// use glwiz::functionality::debloat::reverse_dependencies;
+/// let removed = vec!["gnome-games".to_string(), "aisleriot".to_string()];
+/// assert_eq!(reverse_dependencies(&["aisleriot"], &removed), vec!["gnome-games"]);
+/// ```
+pub fn reverse_dependencies(listed: &[&str], removal_set: &[String]) -> Vec<String> {
+    removal_set
+        .iter()
+        .filter(|package| !listed.contains(&package.as_str()))
+        .cloned()
+        .collect()
+}
+
+/// Removes unwanted preinstalled packages.
+///
+/// This function removes the installed packages among `packages`, which are typically the
+/// distribution’s `default_debloat` list and the `debloat` list of a profile. Listed names are
+/// resolved through the package name table (see `package_map::PackageMap`); names that are not
+/// installed are ignored. Before anything is removed, the packages are listed together with the
+/// installed packages that depend on them and would be removed as well, and the removal must
+/// be confirmed. Afterwards the dependencies that are no longer needed are listed (e.g., with
+/// `pacman -Qdtq`, `apt-get -s autoremove` or `dnf autoremove --assumeno`) and removed once
+/// that is confirmed too.
+///
+/// # Arguments
+/// * `packages` - The packages to remove.
+/// * `distro` - The distribution being configured.
+/// * `target` - The system the packages are removed from.
+///
+/// # Returns
+/// * `0` - The packages were removed, none was installed, or a removal was declined.
+/// * `1` - The distribution is unsupported, or the removal or orphan cleanup failed.
+///
+/// # Example
+/// ```no_run
+/// // Requires an Ubuntu system and sudo privileges; prompts for confirmation.
+/// use glwiz::functionality::{
+///     debloat::{debloat_setup, default_debloat},
+///     distro::Distro,
+///     target::Target,
+/// };
+/// let target = Target::host();
+/// let distro = Distro::detect(&target).unwrap();
+/// let result = debloat_setup(default_debloat(&distro), &distro, &target);
+/// assert_eq!(result, 0);
+/// ```
+///
+/// # See Also
+/// - `PackageManager::removal_set`: Lists the packages a removal would take along.
+/// - `PackageManager::orphans`: Lists the dependencies no longer needed.
+/// - `PackageManager::remove_orphans`: Removes the dependencies no longer needed.
+pub fn debloat_setup(packages: &[&str], distro: &Distro, target: &Target) -> i8 {
+    let Some(family) = distro.family else {
+        eprintln!("{} Unsupported distribution: {}", "error:".red(), distro);
+        return 1;
+    };
+    let manager = for_family(family, target);
+
+    let resolution = PackageMap::load().resolve(packages, family.as_str());
+    let mut installed: Vec<String> = Vec::new();
+    for package in &resolution.packages {
+        let name = manager.package_name(package);
+        if installed.contains(&name) {
+            continue;
+        }
+        match manager.is_installed(&name) {
+            Ok(true) => installed.push(name),
+            Ok(false) => {}
+            Err(e) => {
+                eprintln!("{} Cannot query {}: {}", "error:".red(), name, e);
+                return 1;
+            }
+        }
+    }
+    if installed.is_empty() {
+        println!("None of the packages to remove is installed.");
+        return 0;
+    }
+
+    let listed: Vec<&str> = installed.iter().map(String::as_str).collect();
+    let dependents = match manager.removal_set(&listed) {
+        Ok(removed) => reverse_dependencies(&listed, &removed),
+        Err(e) => {
+            eprintln!(
+                "{} Cannot determine the packages depending on them: {}",
+                "error:".red(),
+                e
+            );
+            return 1;
+        }
+    };
+    println!("The following packages will be removed:");
+    println!("  {}", listed.join(" "));
+    if !dependents.is_empty() {
+        println!(
+            "{} These packages depend on them and will be removed as well:",
+            "warning:".yellow()
+        );
+        println!("  {}", dependents.join(" "));
+    }
+    println!("Remove them? (y/n)");
+    if read_input().trim().to_lowercase() != "y" {
+        println!("Package removal {}.", "skipped".green());
+        return 0;
+    }
+
+    if let Err(e) = manager.remove_with_dependents(&listed) {
+        eprintln!("{} Cannot remove the packages: {}", "error:".red(), e);
+        return 1;
+    }
+    println!(
+        "{} packages {}.",
+        listed.len() + dependents.len(),
+        "removed".green()
+    );
+
+    let orphans = match manager.orphans() {
+        Ok(orphans) => orphans,
+        Err(e) => {
+            eprintln!(
+                "{} Cannot determine the dependencies no longer needed: {}",
+                "error:".red(),
+                e
+            );
+            return 1;
+        }
+    };
+    if orphans.is_empty() {
+        return 0;
+    }
+    println!("These dependencies are no longer needed and will be removed:");
+    println!("  {}", orphans.join(" "));
+    println!("Remove them? (y/n)");
+    if read_input().trim().to_lowercase() != "y" {
+        println!("Dependency removal {}.", "skipped".green());
+        return 0;
+    }
+
+    println!("Removing dependencies that are no longer needed...");
+    match manager.remove_orphans() {
+        Ok(()) => 0,
+        Err(e) => {
+            eprintln!("{} Cannot remove orphaned packages: {}", "error:".red(), e);
+            1
+        }
+    }
+}
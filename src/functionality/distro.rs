@@ -0,0 +1,409 @@
+/*
+ * GLWiz - The ultimate post-installation setup assistant for GNU/Linux popular distros,
+ * streamlining your configuration process with ease and precision.
+ * 
+ * Copyright (C) 2025  Andrew Kushyk
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published
+ * by the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use super::{nixos::NIXOS_ID, service::InitSystem, software::DISTRO_RELEASE_FILES, target::Target};
+use std::{fmt, fs};
+
+/// The locations of the os-release file, in order of precedence.
+pub const OS_RELEASE_PATHS: [&str; 2] = ["/etc/os-release", "/usr/lib/os-release"];
+
+/// The distribution families glwiz supports, named after their base distribution.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DistroFamily {
+    /// Arch Linux and derivatives such as Manjaro and EndeavourOS (pacman).
+    Arch,
+    /// Debian and derivatives such as Ubuntu, Linux Mint and Pop!_OS (apt).
+    Debian,
+    /// Fedora and derivatives such as Nobara (dnf).
+    Fedora,
+    /// openSUSE Tumbleweed, Leap and SUSE Linux Enterprise (zypper).
+    Suse,
+    /// Alpine Linux and postmarketOS (apk, OpenRC).
+    Alpine,
+    /// Void Linux (xbps, runit).
+    Void,
+    /// Gentoo Linux and derivatives such as Funtoo and Calculate (Portage, OpenRC or systemd).
+    Gentoo,
+}
+
+impl DistroFamily {
+    /// Returns the family identifier (e.g., `"arch"`, `"suse"` or `"alpine"`).
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Arch => "arch",
+            Self::Debian => "debian",
+            Self::Fedora => "fedora",
+            Self::Suse => "suse",
+            Self::Alpine => "alpine",
+            Self::Void => "void",
+            Self::Gentoo => "gentoo",
+        }
+    }
+
+    /// Every supported family, in the order release files are checked (see
+    /// `software::DISTRO_RELEASE_FILES`).
+    pub const ALL: [Self; 7] = [
+        Self::Arch,
+        Self::Debian,
+        Self::Fedora,
+        Self::Suse,
+        Self::Alpine,
+        Self::Void,
+        Self::Gentoo,
+    ];
+
+    /// Returns the os-release IDs of the distributions in the family.
+    ///
+    /// # Example
+    /// ```
+    /// use glwiz::functionality::distro::DistroFamily;
+    /// assert!(DistroFamily::Debian.ids().contains(&"ubuntu"));
+    /// assert_eq!(DistroFamily::Void.ids(), ["void"]);
+    /// ```
+    pub fn ids(&self) -> &'static [&'static str] {
+        match self {
+            Self::Arch => &[
+                "arch",
+                "archarm",
+                "manjaro",
+                "manjaro-arm",
+                "endeavouros",
+                "garuda",
+                "arcolinux",
+                "cachyos",
+                "artix",
+            ],
+            Self::Debian => &[
+                "debian",
+                "ubuntu",
+                "linuxmint",
+                "pop",
+                "elementary",
+                "zorin",
+                "raspbian",
+                "kali",
+                "neon",
+                "mx",
+            ],
+            Self::Fedora => &["fedora", "nobara", "ultramarine"],
+            Self::Suse => &[
+                "suse",
+                "opensuse",
+                "opensuse-tumbleweed",
+                "opensuse-leap",
+                "opensuse-slowroll",
+                "opensuse-microos",
+                "sles",
+                "sled",
+            ],
+            Self::Alpine => &["alpine", "postmarketos"],
+            Self::Void => &["void"],
+            Self::Gentoo => &["gentoo", "funtoo", "calculate", "pentoo"],
+        }
+    }
+
+    /// Returns the family a distribution ID belongs to, if it is known.
+    ///
+    /// # Arguments
+    /// * `id` - An os-release `ID` or `ID_LIKE` entry (e.g., `"manjaro"`, `"ubuntu"`).
+    ///
+    /// # Example
+    /// ```
+    /// use glwiz::functionality::distro::DistroFamily;
+    /// assert_eq!(DistroFamily::from_id("pop"), Some(DistroFamily::Debian));
+    /// assert_eq!(DistroFamily::from_id("nobara"), Some(DistroFamily::Fedora));
+    /// assert_eq!(DistroFamily::from_id("funtoo"), Some(DistroFamily::Gentoo));
+    /// assert_eq!(DistroFamily::from_id("rocky"), None);
+    /// assert_eq!(DistroFamily::from_id("slackware"), None);
+    /// ```
+    pub fn from_id(id: &str) -> Option<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|family| family.ids().contains(&id))
+    }
+
+    /// Returns the package providing the zram-generator, if it has to be installed before the
+    /// configuration takes effect.
+    ///
+    /// # Example
+    /// ```
+    /// use glwiz::functionality::distro::DistroFamily;
+    /// assert_eq!(DistroFamily::Suse.zram_package(), Some("zram-generator"));
+    /// assert_eq!(DistroFamily::Fedora.zram_package(), None);
+    /// ```
+    pub fn zram_package(&self) -> Option<&'static str> {
+        match self {
+            Self::Suse | Self::Gentoo => Some("zram-generator"),
+            _ => None,
+        }
+    }
+
+    /// Returns the firewall service the distribution enables by default, if any, which would
+    /// replace rules loaded with `iptables-restore`.
+    ///
+    /// # Example
+    /// ```
+    /// use glwiz::functionality::distro::DistroFamily;
+    /// assert_eq!(DistroFamily::Suse.default_firewall(), Some("firewalld"));
+    /// assert_eq!(DistroFamily::Arch.default_firewall(), None);
+    /// ```
+    pub fn default_firewall(&self) -> Option<&'static str> {
+        match self {
+            Self::Suse => Some("firewalld"),
+            _ => None,
+        }
+    }
+
+    /// Returns the init system the family uses unless the target shows otherwise.
+    ///
+    /// # Example
+    /// ```
+    /// use glwiz::functionality::{distro::DistroFamily, service::InitSystem};
+    /// assert_eq!(DistroFamily::Alpine.default_init_system(), InitSystem::OpenRc);
+    /// assert_eq!(DistroFamily::Void.default_init_system(), InitSystem::Runit);
+    /// assert_eq!(DistroFamily::Debian.default_init_system(), InitSystem::Systemd);
+    /// ```
+    pub fn default_init_system(&self) -> InitSystem {
+        match self {
+            Self::Alpine | Self::Gentoo => InitSystem::OpenRc,
+            Self::Void => InitSystem::Runit,
+            _ => InitSystem::Systemd,
+        }
+    }
+
+    /// Returns packages the setup relies on that the family’s base system lacks. They are
+    /// installed together with the selected packages.
+    ///
+    /// # Example
+    /// ```
+    /// use glwiz::functionality::distro::DistroFamily;
+    /// assert!(DistroFamily::Void.required_packages().contains(&"curl"));
+    /// assert!(DistroFamily::Arch.required_packages().is_empty());
+    /// ```
+    pub fn required_packages(&self) -> &'static [&'static str] {
+        match self {
+            // The Oh My Zsh installer needs zsh, git and curl; iptables ships the init service.
+            Self::Void | Self::Gentoo => &["zsh", "git", "curl", "iptables"],
+            _ => &[],
+        }
+    }
+}
+
+impl fmt::Display for DistroFamily {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// The os-release IDs of Enterprise Linux and its rebuilds. They are not supported: they share
+/// DNF with Fedora, but not its package names, and most packages need EPEL.
+pub const ENTERPRISE_LINUX_IDS: [&str; 4] = ["rhel", "centos", "rocky", "almalinux"];
+
+/// The distribution of the system being configured, as described by its os-release file.
+///
+/// Both the specific distribution (e.g., Linux Mint 22) and the family whose package manager
+/// and conventions it shares (e.g., Debian) are recorded, so that derivatives are handled like
+/// their base distribution.
+///
+/// # Fields
+/// * `id` - The os-release `ID` (e.g., `"linuxmint"`).
+/// * `id_like` - The os-release `ID_LIKE` entries (e.g., `["ubuntu", "debian"]`).
+/// * `name` - The human-readable name (`PRETTY_NAME`, `NAME` or the ID).
+/// * `version_id` - The os-release `VERSION_ID` (e.g., `"22"`), absent on rolling releases.
+/// * `codename` - The os-release `VERSION_CODENAME` (e.g., `"wilma"`).
+/// * `family` - The supported family, or `None` if the distribution is not supported.
+///
+/// # Example
+/// ```
+/// use glwiz::functionality::distro::{Distro, DistroFamily};
+/// let distro = Distro::from_os_release(
+///     "NAME=\"Linux Mint\"\nID=linuxmint\nID_LIKE=\"ubuntu debian\"\nVERSION_ID=\"22\"\nVERSION_CODENAME=wilma\n",
+/// )
+/// .unwrap();
+/// assert_eq!(distro.id, "linuxmint");
+/// assert_eq!(distro.family, Some(DistroFamily::Debian));
+/// assert_eq!(distro.version_id.as_deref(), Some("22"));
+/// assert_eq!(distro.codename.as_deref(), Some("wilma"));
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Distro {
+    pub id: String,
+    pub id_like: Vec<String>,
+    pub name: String,
+    pub version_id: Option<String>,
+    pub codename: Option<String>,
+    pub family: Option<DistroFamily>,
+}
+
+impl Distro {
+    /// Parses the contents of an os-release file.
+    ///
+    /// Values may be unquoted, or quoted with double or single quotes; backslash escapes in
+    /// double-quoted values are resolved. The family is taken from `ID`, falling back to the
+    /// `ID_LIKE` entries in order. Enterprise Linux (see `ENTERPRISE_LINUX_IDS`) is unsupported
+    /// and has no family, while Fedora derivatives that are merely like it (e.g., Nobara) keep
+    /// theirs.
+    ///
+    /// # Arguments
+    /// * `contents` - The contents of an os-release file.
+    ///
+    /// # Returns
+    /// * `Ok(Distro)` - The parsed distribution.
+    /// * `Err(String)` - An error message if the file has no `ID`.
+    pub fn from_os_release(contents: &str) -> Result<Self, String> {
+        let fields: Vec<(&str, String)> = contents
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty() && !line.starts_with('#'))
+            .filter_map(|line| line.split_once('='))
+            .map(|(key, value)| (key.trim(), unquote(value.trim())))
+            .collect();
+        let field = |key: &str| {
+            fields
+                .iter()
+                .rev()
+                .find(|(k, _)| *k == key)
+                .map(|(_, v)| v.clone())
+                .filter(|v| !v.is_empty())
+        };
+
+        let id = field("ID")
+            .map(|id| id.to_lowercase())
+            .ok_or("os-release has no ID")?;
+        let id_like: Vec<String> = field("ID_LIKE")
+            .map(|like| like.split_whitespace().map(str::to_lowercase).collect())
+            .unwrap_or_default();
+        // Enterprise Linux rebuilds list `fedora` in `ID_LIKE`, but their package names and
+        // repositories differ, so the search stops at the first Enterprise Linux ID.
+        let family = std::iter::once(&id)
+            .chain(id_like.iter())
+            .take_while(|id| !ENTERPRISE_LINUX_IDS.contains(&id.as_str()))
+            .find_map(|id| DistroFamily::from_id(id));
+
+        Ok(Self {
+            name: field("PRETTY_NAME")
+                .or_else(|| field("NAME"))
+                .unwrap_or_else(|| id.clone()),
+            id,
+            id_like,
+            version_id: field("VERSION_ID"),
+            codename: field("VERSION_CODENAME"),
+            family,
+        })
+    }
+
+    /// Creates a distribution from an ID given on the command line (`--distro`).
+    ///
+    /// # Arguments
+    /// * `id` - A distribution ID (e.g., `"ubuntu"`) or family (e.g., `"arch"`).
+    ///
+    /// # Returns
+    /// * `Ok(Distro)` - The distribution, with its family (none for NixOS, which is configured
+    ///   through generated modules).
+    /// * `Err(String)` - An error message if the ID does not belong to a supported family.
+    ///
+    /// # Example
+    /// ```
+    /// use glwiz::functionality::distro::{Distro, DistroFamily};
+    /// assert_eq!(Distro::from_id("Manjaro").unwrap().family, Some(DistroFamily::Arch));
+    /// assert!(Distro::from_id("nixos").unwrap().is_nixos());
+    /// assert!(Distro::from_id("plan9").is_err());
+    /// ```
+    pub fn from_id(id: &str) -> Result<Self, String> {
+        let id = id.trim().to_lowercase();
+        let family = DistroFamily::from_id(&id);
+        if family.is_none() && id != NIXOS_ID {
+            return Err(format!("Unsupported distribution: {}", id));
+        }
+        Ok(Self {
+            name: id.clone(),
+            id,
+            id_like: Vec::new(),
+            version_id: None,
+            codename: None,
+            family,
+        })
+    }
+
+    /// Detects the distribution of a target.
+    ///
+    /// The target’s `/etc/os-release` (or `/usr/lib/os-release`) is parsed. Systems without an
+    /// os-release file are recognized by their legacy release files (e.g., `/etc/arch-release`).
+    ///
+    /// # Arguments
+    /// * `target` - The system whose distribution is detected.
+    ///
+    /// # Returns
+    /// * `Some(Distro)` - The detected distribution; its `family` is `None` if unsupported.
+    /// * `None` - No os-release or release file was found.
+    pub fn detect(target: &Target) -> Option<Self> {
+        OS_RELEASE_PATHS
+            .iter()
+            .filter_map(|path| fs::read_to_string(target.path(path)).ok())
+            .find_map(|contents| Self::from_os_release(&contents).ok())
+            .or_else(|| {
+                DISTRO_RELEASE_FILES
+                    .iter()
+                    .find(|(file, _)| std::path::Path::new(&target.path(file)).exists())
+                    .and_then(|(_, id)| Self::from_id(id).ok())
+            })
+    }
+
+    /// Returns `true` for NixOS, which glwiz configures by generating modules (see `nixos`).
+    pub fn is_nixos(&self) -> bool {
+        self.id == NIXOS_ID
+    }
+
+    /// Returns the family identifier, or `"unknown"` for an unsupported distribution.
+    pub fn family_name(&self) -> &'static str {
+        self.family.map(|f| f.as_str()).unwrap_or("unknown")
+    }
+}
+
+impl fmt::Display for Distro {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.family {
+            Some(family) if family.as_str() != self.id => {
+                write!(f, "{} ({} family)", self.name, family)
+            }
+            _ => f.write_str(&self.name),
+        }
+    }
+}
+
+// Removes the shell quoting of an os-release value.
+fn unquote(value: &str) -> String {
+    if let Some(inner) = value.strip_prefix('"').and_then(|v| v.strip_suffix('"')) {
+        let mut out = String::new();
+        let mut chars = inner.chars();
+        while let Some(c) = chars.next() {
+            if c == '\\' {
+                out.extend(chars.next());
+            } else {
+                out.push(c);
+            }
+        }
+        out
+    } else if let Some(inner) = value.strip_prefix('\'').and_then(|v| v.strip_suffix('\'')) {
+        inner.to_string()
+    } else {
+        value.to_string()
+    }
+}
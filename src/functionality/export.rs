@@ -0,0 +1,604 @@
+/*
+ * GLWiz - The ultimate post-installation setup assistant for GNU/Linux popular distros,
+ * streamlining your configuration process with ease and precision.
+ * 
+ * Copyright (C) 2025  Andrew Kushyk
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published
+ * by the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use super::{
+    commands::sh_quote,
+    distro::{DistroFamily, ENTERPRISE_LINUX_IDS},
+    package_map::PackageMap,
+    plan::{Account, PlanStep, PlanTask},
+    software::{DISTRO_RELEASE_FILES, install_command, refresh_command},
+};
+use std::{fs, path::Path};
+
+// The delimiter of the heredocs embedding the bundled configuration files.
+const HEREDOC_DELIMITER: &str = "GLWIZ_EOF";
+
+// The `ansible_os_family` fact of each distribution family.
+const ANSIBLE_OS_FAMILIES: [(&str, &str); 7] = [
+    ("arch", "Archlinux"),
+    ("debian", "Debian"),
+    ("fedora", "RedHat"),
+    ("suse", "Suse"),
+    ("alpine", "Alpine"),
+    ("void", "Void"),
+    ("gentoo", "Gentoo"),
+];
+
+/// A file produced by an exporter.
+///
+/// # Fields
+/// * `path` - The path of the file, relative to the output directory (e.g., `"files/.zshrc"`).
+/// * `content` - The file contents.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ExportFile {
+    pub path: String,
+    pub content: String,
+}
+
+/// Renders a setup plan as a standalone POSIX shell script.
+///
+/// The script performs the same tasks as a regular glwiz run, in the same order: packages are
+/// installed with the package manager of the detected distribution family, under the names the
+/// package name table gives for it (see `package_map::PackageMap`), existing files trigger the
+/// same overwrite prompt, and failed tasks are collected and reported at the end. The bundled
+/// configuration files are embedded as quoted heredocs, so the script needs no other files.
+/// The family is detected as `distro::Distro::detect` does, from the `ID` and `ID_LIKE` entries
+/// of os-release, so distributions without a legacy release file (e.g., Void Linux) are covered.
+///
+/// # Arguments
+/// * `plan` - The tasks to render (e.g., from `plan::default_plan`).
+///
+/// # Returns
+/// * `Ok(String)` - The shell script.
+/// * `Err(String)` - An error message if a bundled configuration file cannot be read or embedded.
+///
+/// # Errors
+/// Returns an error if:
+/// - A configuration file referenced by the plan cannot be read.
+/// - A configuration file contains a line equal to the heredoc delimiter.
+///
+/// # Example
+/// ```
+/// use glwiz::functionality::{export::export_sh, plan::{PlanStep, PlanTask}};
+/// let plan = vec![PlanTask {
+///     name: "Software installation".to_string(),
+///     step: PlanStep::InstallPackages { packages: vec!["zsh".to_string()] },
+/// }];
+/// let script = export_sh(&plan).unwrap();
+/// assert!(script.starts_with("#!/bin/sh"));
+/// assert!(script.contains("sudo pacman -S --needed --noconfirm zsh"));
+/// assert!(script.contains("sudo apt update && sudo apt install -y zsh"));
+/// assert!(script.contains("void) echo void; return 0 ;;"));
+/// assert!(script.contains("run_task 'Software installation' task_1"));
+/// ```
+///
+/// # See Also
+/// - `plan::default_plan`: Builds the plan of the default setup.
+pub fn export_sh(plan: &[PlanTask]) -> Result<String, String> {
+    let mut script = String::from(
+        r#"#!/bin/sh
+# Generated by glwiz (glwiz export --format sh).
+# Review before running. Run as a regular user with sudo rights.
+
+set -u
+
+if [ "$(id -u)" -eq 0 ] && [ "${ALLOW_ROOT:-0}" != "1" ]; then
+    echo "Running as root is not recommended. Set ALLOW_ROOT=1 to proceed." >&2
+    exit 1
+fi
+
+failed=""
+
+# Succeeds if $1 does not exist or the user agrees to overwrite it.
+confirm_overwrite() {
+    [ -e "$1" ] || return 0
+    printf '%s exists. Overwrite? (y/n) ' "$1"
+    read -r answer < /dev/tty || answer=n
+    [ "$answer" = "y" ] || [ "$answer" = "Y" ]
+}
+
+# Runs a task function and records its name if it fails.
+run_task() {
+    name=$1
+    shift
+    if ! "$@"; then
+        echo "error: $name failed." >&2
+        failed="$failed
+- $name"
+    fi
+}
+"#,
+    );
+    script.push_str(&sh_distro_family());
+
+    for (index, task) in plan.iter().enumerate() {
+        script.push_str(&format!("\n# {}\ntask_{}() {{\n", task.name, index + 1));
+        script.push_str(&render_sh_step(&task.step)?);
+        script.push_str("}\n");
+    }
+
+    script.push('\n');
+    for (index, task) in plan.iter().enumerate() {
+        script.push_str(&format!(
+            "run_task {} task_{}\n",
+            sh_quote(&task.name),
+            index + 1
+        ));
+    }
+
+    script.push_str(
+        r#"
+if [ -n "$failed" ]; then
+    echo "error: Setup failed with errors:$failed" >&2
+    exit 1
+fi
+echo "All set! Your GNU/Linux system is ready to use!"
+"#,
+    );
+    Ok(script)
+}
+
+// Renders the body of a task function for a single plan step.
+fn render_sh_step(step: &PlanStep) -> Result<String, String> {
+    let body = match step {
+        PlanStep::InstallPackages { packages } => {
+            let packages: Vec<&str> = packages.iter().map(String::as_str).collect();
+            let map = PackageMap::load();
+            let mut body = String::from("    case \"$family\" in\n");
+            for family in DistroFamily::ALL {
+                let distro = family.as_str();
+                let (cmd, args) = install_command(distro).unwrap_or_default();
+                let resolved: Vec<String> = map
+                    .resolve(&packages, distro)
+                    .packages
+                    .iter()
+                    .map(|p| sh_quote(p))
+                    .collect();
+                let install = if resolved.is_empty() {
+                    ":".to_string()
+                } else {
+                    let refresh = refresh_command(distro)
+                        .map(|(cmd, args)| format!("sudo {} {} && ", cmd, args.join(" ")))
+                        .unwrap_or_default();
+                    format!(
+                        "{}sudo {} {} {}",
+                        refresh,
+                        cmd,
+                        args.join(" "),
+                        resolved.join(" ")
+                    )
+                };
+                body.push_str(&format!(
+                    "        {})\n            {}\n            ;;\n",
+                    distro, install
+                ));
+            }
+            body.push_str(
+                "        *)\n            echo \"error: Unsupported distribution.\" >&2\n            return 1\n            ;;\n    esac\n",
+            );
+            body
+        }
+        PlanStep::ChangeShell { account, shell } => format!(
+            "    sudo chsh -s {} {}\n",
+            sh_quote(shell),
+            sh_account(*account)
+        ),
+        PlanStep::InstallOmz { url, .. } => format!(
+            "    if [ -d \"$HOME/.oh-my-zsh\" ]; then\n        echo \"Oh My Zsh already installed.\"\n        return 0\n    fi\n    curl -fsSL {} | ZSH=\"$HOME/.oh-my-zsh\" RUNZSH=no CHSH=no bash\n",
+            sh_quote(url)
+        ),
+        PlanStep::ClonePlugin { name, repo_url } => format!(
+            "    dest=\"$HOME/.oh-my-zsh/custom/plugins/{}\"\n    if [ -e \"$dest\" ]; then\n        echo \"{} already installed.\"\n        return 0\n    fi\n    git clone {} \"$dest\"\n",
+            name,
+            name,
+            sh_quote(repo_url)
+        ),
+        PlanStep::InstallUserFile { source, file_name } => {
+            let dest = format!("\"$HOME/{}\"", file_name);
+            format!(
+                "    confirm_overwrite {dest} || {{ echo \"{file_name} skipped.\"; return 0; }}\n    cat > {dest} <<'{delim}'\n{content}{delim}\n",
+                dest = dest,
+                file_name = file_name,
+                delim = HEREDOC_DELIMITER,
+                content = read_heredoc_content(source)?
+            )
+        }
+        PlanStep::InstallSystemFile { source, dest, .. } => format!(
+            "    confirm_overwrite {dest} || {{ echo \"{dest} skipped.\"; return 0; }}\n    sudo mkdir -p {dir} &&\n    sudo tee {dest} > /dev/null <<'{delim}'\n{content}{delim}\n",
+            dest = sh_quote(dest),
+            dir = sh_quote(parent_dir(dest)),
+            delim = HEREDOC_DELIMITER,
+            content = read_heredoc_content(source)?
+        ),
+        PlanStep::RestoreIptables { rules } => format!(
+            "    sudo sh -c {}\n",
+            sh_quote(&format!("iptables-restore < {}", rules))
+        ),
+        PlanStep::CopyToRoot { items } => items
+            .iter()
+            .map(|item| {
+                format!(
+                    "    sudo cp -r \"$HOME/{item}\" /root/{item} || return 1\n",
+                    item = item
+                )
+            })
+            .collect(),
+    };
+    Ok(body)
+}
+
+// Renders the shell function printing the distribution family, and the detection of the
+// running system’s family. It follows `Distro::detect`: the `ID` and `ID_LIKE` entries of
+// os-release are matched in order, stopping at Enterprise Linux, and the legacy release files
+// are only checked without an os-release file.
+fn sh_distro_family() -> String {
+    let mut function = String::from(
+        r#"
+# Prints the distribution family from os-release, or from the legacy release files.
+distro_family() {
+    for file in /etc/os-release /usr/lib/os-release; do
+        [ -r "$file" ] || continue
+        for id in $(. "$file" && echo "${ID:-} ${ID_LIKE:-}"); do
+            case "$id" in
+"#,
+    );
+    function.push_str(&format!(
+        "                {}) return 1 ;;\n",
+        ENTERPRISE_LINUX_IDS.join("|")
+    ));
+    for family in DistroFamily::ALL {
+        function.push_str(&format!(
+            "                {}) echo {}; return 0 ;;\n",
+            family.ids().join("|"),
+            family
+        ));
+    }
+    function.push_str("            esac\n        done\n        return 1\n    done\n");
+    for (file, distro) in DISTRO_RELEASE_FILES {
+        function.push_str(&format!(
+            "    if [ -f {} ]; then echo {}; return 0; fi\n",
+            file, distro
+        ));
+    }
+    function.push_str("    return 1\n}\n\nfamily=$(distro_family) || family=\"\"\n");
+    function
+}
+
+/// Renders a setup plan as an Ansible playbook with the bundled configuration files.
+///
+/// Every task is mapped to an idiomatic module: `package` for software, `user` for login shells,
+/// `git` for Oh My Zsh and its plugins, `copy` for configuration files, `iptables_state` to load
+/// the firewall rules, and a `systemd` handler restarting the units that read a changed file.
+/// Software gets one `package` task per `ansible_os_family`, listing the names the package name
+/// table gives for that family (see `package_map::PackageMap`), as `export_sh` does per distro.
+/// The bundled configuration files are saved under `files/`, and each task is tagged with
+/// `glwiz` and with its glwiz task name (e.g., `zram-swap-setup`), so that single tasks can be
+/// selected with `--tags`. Existing files are backed up before they are replaced.
+///
+/// # Arguments
+/// * `plan` - The tasks to render (e.g., from `plan::default_plan`).
+///
+/// # Returns
+/// * `Ok(Vec<ExportFile>)` - `playbook.yml` followed by the configuration files it references.
+/// * `Err(String)` - An error message if a bundled configuration file cannot be read.
+///
+/// # Example
+/// ```
+/// use glwiz::functionality::{export::export_ansible, plan::{Account, PlanStep, PlanTask}};
+/// let plan = vec![
+///     PlanTask {
+///         name: "Software installation".to_string(),
+///         step: PlanStep::InstallPackages { packages: vec!["zsh".to_string()] },
+///     },
+///     PlanTask {
+///         name: "Shell change for root".to_string(),
+///         step: PlanStep::ChangeShell { account: Account::Root, shell: "/usr/bin/zsh".to_string() },
+///     },
+/// ];
+/// let files = export_ansible(&plan).unwrap();
+/// assert_eq!(files[0].path, "playbook.yml");
+/// assert!(files[0].content.contains("when: ansible_os_family == 'RedHat'"));
+/// assert!(files[0].content.contains("when: ansible_os_family == 'Void'"));
+/// assert!(files[0].content.contains("tags: [glwiz, shell-change-for-root]"));
+/// assert!(files[0].content.contains("ansible.builtin.user:"));
+/// ```
+///
+/// # See Also
+/// - `export_sh`: Renders the same plan as a shell script.
+pub fn export_ansible(plan: &[PlanTask]) -> Result<Vec<ExportFile>, String> {
+    let mut files = Vec::new();
+    let mut units: Vec<String> = Vec::new();
+    let mut tasks = String::new();
+
+    for task in plan {
+        let tag = tag_name(&task.name);
+        match &task.step {
+            PlanStep::InstallPackages { packages } => {
+                let packages: Vec<&str> = packages.iter().map(String::as_str).collect();
+                let map = PackageMap::load();
+                for (distro, os_family) in ANSIBLE_OS_FAMILIES {
+                    let resolved = map.resolve(&packages, distro).packages;
+                    if resolved.is_empty() {
+                        continue;
+                    }
+                    tasks.push_str(&yaml_task(
+                        &format!("{} ({})", task.name, os_family),
+                        &tag,
+                        true,
+                        "ansible.builtin.package",
+                        &[
+                            ("name", yaml_list(&resolved)),
+                            ("state", "present".to_string()),
+                        ],
+                        &[("when", format!("ansible_os_family == '{}'", os_family))],
+                    ));
+                }
+            }
+            PlanStep::ChangeShell { account, shell } => {
+                let name = match account {
+                    Account::User => "{{ ansible_user_id }}",
+                    Account::Root => "root",
+                };
+                tasks.push_str(&yaml_task(
+                    &task.name,
+                    &tag,
+                    true,
+                    "ansible.builtin.user",
+                    &[("name", yaml_str(name)), ("shell", yaml_str(shell))],
+                    &[],
+                ));
+            }
+            PlanStep::InstallOmz { repo_url, .. } => {
+                tasks.push_str(&yaml_task(
+                    &task.name,
+                    &tag,
+                    false,
+                    "ansible.builtin.git",
+                    &[
+                        ("repo", yaml_str(repo_url)),
+                        ("dest", yaml_str("{{ ansible_env.HOME }}/.oh-my-zsh")),
+                        ("depth", "1".to_string()),
+                        ("update", "false".to_string()),
+                    ],
+                    &[],
+                ));
+            }
+            PlanStep::ClonePlugin { name, repo_url } => {
+                let dest = format!(
+                    "{{{{ ansible_env.HOME }}}}/.oh-my-zsh/custom/plugins/{}",
+                    name
+                );
+                tasks.push_str(&yaml_task(
+                    &task.name,
+                    &tag,
+                    false,
+                    "ansible.builtin.git",
+                    &[
+                        ("repo", yaml_str(repo_url)),
+                        ("dest", yaml_str(&dest)),
+                        ("depth", "1".to_string()),
+                        ("update", "false".to_string()),
+                    ],
+                    &[],
+                ));
+            }
+            PlanStep::InstallUserFile { source, file_name } => {
+                let src = bundle_file(&mut files, source)?;
+                let dest = format!("{{{{ ansible_env.HOME }}}}/{}", file_name);
+                tasks.push_str(&yaml_task(
+                    &task.name,
+                    &tag,
+                    false,
+                    "ansible.builtin.copy",
+                    &[
+                        ("src", yaml_str(&src)),
+                        ("dest", yaml_str(&dest)),
+                        ("mode", yaml_str("0644")),
+                        ("backup", "true".to_string()),
+                    ],
+                    &[],
+                ));
+            }
+            PlanStep::InstallSystemFile { source, dest, unit } => {
+                let src = bundle_file(&mut files, source)?;
+                tasks.push_str(&yaml_task(
+                    &format!("{} (directory)", task.name),
+                    &tag,
+                    true,
+                    "ansible.builtin.file",
+                    &[
+                        ("path", yaml_str(parent_dir(dest))),
+                        ("state", "directory".to_string()),
+                        ("mode", yaml_str("0755")),
+                    ],
+                    &[],
+                ));
+                let mut extra = Vec::new();
+                if let Some(unit) = unit {
+                    extra.push(("notify", yaml_str(&format!("Restart {}", unit))));
+                    if !units.contains(unit) {
+                        units.push(unit.clone());
+                    }
+                }
+                tasks.push_str(&yaml_task(
+                    &task.name,
+                    &tag,
+                    true,
+                    "ansible.builtin.copy",
+                    &[
+                        ("src", yaml_str(&src)),
+                        ("dest", yaml_str(dest)),
+                        ("mode", yaml_str("0644")),
+                        ("backup", "true".to_string()),
+                    ],
+                    &extra,
+                ));
+            }
+            PlanStep::RestoreIptables { rules } => {
+                tasks.push_str(&yaml_task(
+                    &task.name,
+                    &tag,
+                    true,
+                    "community.general.iptables_state",
+                    &[("state", "restored".to_string()), ("path", yaml_str(rules))],
+                    &[],
+                ));
+            }
+            PlanStep::CopyToRoot { items } => {
+                tasks.push_str(&yaml_task(
+                    &task.name,
+                    &tag,
+                    true,
+                    "ansible.builtin.copy",
+                    &[
+                        ("src", yaml_str("{{ ansible_env.HOME }}/{{ item }}")),
+                        ("dest", yaml_str("/root/")),
+                        ("remote_src", "true".to_string()),
+                    ],
+                    &[("loop", yaml_list(items))],
+                ));
+            }
+        }
+    }
+
+    let mut playbook = String::from(
+        "# Generated by glwiz (glwiz export --format ansible).\n\
+         # Requires the community.general collection for iptables_state.\n\
+         - name: GLWiz default setup\n  hosts: all\n  tasks:\n",
+    );
+    playbook.push_str(&tasks);
+    if !units.is_empty() {
+        playbook.push_str("  handlers:\n");
+        for unit in &units {
+            playbook.push_str(&format!(
+                "    - name: {}\n      become: true\n      ansible.builtin.systemd:\n        name: {}\n        state: restarted\n        daemon_reload: true\n",
+                yaml_str(&format!("Restart {}", unit)),
+                yaml_str(unit)
+            ));
+        }
+    }
+
+    files.insert(
+        0,
+        ExportFile {
+            path: "playbook.yml".to_string(),
+            content: playbook,
+        },
+    );
+    Ok(files)
+}
+
+// Renders one playbook task. `params` are the module parameters and `extra` additional task
+// keywords (e.g., `loop` or `notify`); their values must already be valid YAML.
+fn yaml_task(
+    name: &str,
+    tag: &str,
+    become_root: bool,
+    module: &str,
+    params: &[(&str, String)],
+    extra: &[(&str, String)],
+) -> String {
+    let mut task = format!(
+        "    - name: {}\n      tags: [glwiz, {}]\n",
+        yaml_str(name),
+        tag
+    );
+    if become_root {
+        task.push_str("      become: true\n");
+    }
+    task.push_str(&format!("      {}:\n", module));
+    for (key, value) in params {
+        task.push_str(&format!("        {}: {}\n", key, value));
+    }
+    for (key, value) in extra {
+        task.push_str(&format!("      {}: {}\n", key, value));
+    }
+    task
+}
+
+// Adds a configuration file to the exported files under `files/` and returns its path.
+fn bundle_file(files: &mut Vec<ExportFile>, source: &str) -> Result<String, String> {
+    let name = Path::new(source)
+        .file_name()
+        .map(|n| n.to_string_lossy().into_owned())
+        .ok_or_else(|| format!("Invalid path: {}", source))?;
+    let path = format!("files/{}", name);
+    if !files.iter().any(|f| f.path == path) {
+        let content =
+            fs::read_to_string(source).map_err(|e| format!("Failed to read {}: {}", source, e))?;
+        files.push(ExportFile {
+            path: path.clone(),
+            content,
+        });
+    }
+    Ok(path)
+}
+
+// Turns a task name into a tag (e.g., "ZRAM swap setup" into "zram-swap-setup").
+fn tag_name(name: &str) -> String {
+    name.split(|c: char| !c.is_ascii_alphanumeric())
+        .filter(|part| !part.is_empty())
+        .map(|part| part.to_ascii_lowercase())
+        .collect::<Vec<_>>()
+        .join("-")
+}
+
+// Renders a YAML double-quoted string.
+fn yaml_str(value: &str) -> String {
+    format!("\"{}\"", value.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+// Renders a YAML flow sequence of double-quoted strings.
+fn yaml_list(values: &[String]) -> String {
+    let items: Vec<String> = values.iter().map(|v| yaml_str(v)).collect();
+    format!("[{}]", items.join(", "))
+}
+
+// Reads a configuration file for embedding in a heredoc, ensuring it ends with a newline.
+fn read_heredoc_content(source: &str) -> Result<String, String> {
+    let mut content =
+        fs::read_to_string(source).map_err(|e| format!("Failed to read {}: {}", source, e))?;
+    if content.lines().any(|line| line == HEREDOC_DELIMITER) {
+        return Err(format!(
+            "{} contains the line {} and cannot be embedded",
+            source, HEREDOC_DELIMITER
+        ));
+    }
+    if !content.is_empty() && !content.ends_with('\n') {
+        content.push('\n');
+    }
+    Ok(content)
+}
+
+// Returns the directory part of an absolute path (e.g., "/etc/iptables").
+fn parent_dir(path: &str) -> &str {
+    match path.rfind('/') {
+        Some(0) | None => "/",
+        Some(index) => &path[..index],
+    }
+}
+
+// Returns the shell expression naming an account.
+fn sh_account(account: Account) -> &'static str {
+    match account {
+        Account::User => "\"$USER\"",
+        Account::Root => "root",
+    }
+}
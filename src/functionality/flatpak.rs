@@ -0,0 +1,355 @@
+/*
+ * GLWiz - The ultimate post-installation setup assistant for GNU/Linux popular distros,
+ * streamlining your configuration process with ease and precision.
+ * 
+ * Copyright (C) 2025  Andrew Kushyk
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published
+ * by the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+use super::{
+    commands::{capture_user_command, run_user_command},
+    distro::DistroFamily,
+    package_manager::for_family,
+    package_map::PackageMap,
+    software::{MAX_SUGGESTIONS, PackageStatus, SoftwareReport, suggest_packages},
+    target::Target,
+};
+use colored::Colorize;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+
+/// The Flatpak executable, which is also the name of its native package.
+pub const FLATPAK_COMMAND: &str = "flatpak";
+
+/// The name glwiz gives the Flathub remote.
+pub const FLATHUB_REMOTE: &str = "flathub";
+
+/// The repository file describing the Flathub remote.
+pub const FLATHUB_URL: &str = "https://dl.flathub.org/repo/flathub.flatpakrepo";
+
+/// The key of the package name table (see `package_map::PackageMap`) holding a package’s
+/// Flathub app ID.
+pub const FLATPAK_MAP_KEY: &str = "flatpak";
+
+/// Where Flatpak apps and the Flathub remote are installed.
+///
+/// # Example
+/// ```
+/// use glwiz::functionality::flatpak::FlatpakScope;
+/// assert_eq!(FlatpakScope::default(), FlatpakScope::System);
+/// assert_eq!(FlatpakScope::User.as_arg(), "--user");
+/// ```
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum FlatpakScope {
+    /// The system-wide installation, shared by all users and changed with root privileges.
+    #[default]
+    System,
+    /// The current user’s installation under `~/.local/share/flatpak`.
+    User,
+}
+
+impl FlatpakScope {
+    /// Returns the `flatpak` option selecting the installation.
+    pub fn as_arg(self) -> &'static str {
+        match self {
+            Self::System => "--system",
+            Self::User => "--user",
+        }
+    }
+}
+
+/// Returns `true` if a name is a Flatpak app ID (e.g., `"org.mozilla.firefox"`).
+///
+/// App IDs are reverse-DNS names with at least three components of letters, digits, `_` and
+/// `-`, where no component starts with a digit.
+///
+/// # Example
+/// ```
+/// use glwiz::functionality::flatpak::is_app_id;
+/// assert!(is_app_id("org.gimp.GIMP"));
+/// assert!(!is_app_id("gimp"));
+/// assert!(!is_app_id("org.2048.Game"));
+/// ```
+pub fn is_app_id(name: &str) -> bool {
+    let components: Vec<&str> = name.split('.').collect();
+    components.len() >= 3
+        && components.iter().all(|component| {
+            component
+                .chars()
+                .next()
+                .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+                && component
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+        })
+}
+
+/// Resolves package names to Flathub app IDs.
+///
+/// App IDs are kept as they are; other names are looked up under the `flatpak` key of the
+/// package name table (e.g., `firefox` becomes `org.mozilla.firefox`).
+///
+/// # Arguments
+/// * `names` - App IDs or package names (e.g., `&["firefox", "org.gimp.GIMP"]`).
+/// * `map` - The package name table.
+///
+/// # Returns
+/// A tuple of the app IDs and the names that have none.
+///
+/// # Example
+/// ```
+/// use glwiz::functionality::{flatpak::resolve_apps, package_map::PackageMap};
+/// let map = PackageMap::parse("[firefox]\nflatpak = \"org.mozilla.firefox\"\n").unwrap();
+/// let (apps, unknown) = resolve_apps(&["firefox", "org.gimp.GIMP", "vlc"], &map);
+/// assert_eq!(apps, ["org.mozilla.firefox", "org.gimp.GIMP"]);
+/// assert_eq!(unknown, ["vlc"]);
+/// ```
+pub fn resolve_apps(names: &[&str], map: &PackageMap) -> (Vec<String>, Vec<String>) {
+    let mut apps = Vec::new();
+    let mut unknown = Vec::new();
+    for name in names {
+        if is_app_id(name) {
+            apps.push(name.to_string());
+            continue;
+        }
+        let ids: Vec<String> = map
+            .lookup(name, FLATPAK_MAP_KEY)
+            .unwrap_or_default()
+            .into_iter()
+            .filter(|id| is_app_id(id))
+            .collect();
+        if ids.is_empty() {
+            unknown.push(name.to_string());
+        } else {
+            apps.extend(ids);
+        }
+    }
+    (apps, unknown)
+}
+
+/// Parses the output of `flatpak list --columns=application` (or `flatpak search` with the
+/// same column) into app IDs.
+///
+/// # Example
+/// ```
+/// use glwiz::functionality::flatpak::parse_app_list;
+/// assert_eq!(
+///     parse_app_list("org.mozilla.firefox\nio.mpv.Mpv\n\n"),
+///     ["org.mozilla.firefox", "io.mpv.Mpv"]
+/// );
+/// ```
+pub fn parse_app_list(output: &str) -> Vec<String> {
+    output
+        .lines()
+        .map(str::trim)
+        .filter(|line| is_app_id(line))
+        .map(String::from)
+        .collect()
+}
+
+// Runs `flatpak` on the target: with root privileges for the system installation, as the
+// current user for the user installation.
+fn run_flatpak(target: &Target, scope: FlatpakScope, args: &[&str]) -> Result<(), String> {
+    match scope {
+        FlatpakScope::System => target.run_command(FLATPAK_COMMAND, args),
+        FlatpakScope::User => run_user_command(FLATPAK_COMMAND, args),
+    }
+}
+
+// Runs a read-only `flatpak` query on the target and returns its output.
+fn capture_flatpak(target: &Target, scope: FlatpakScope, args: &[&str]) -> Result<String, String> {
+    match scope {
+        FlatpakScope::System => target.capture_command(FLATPAK_COMMAND, args),
+        FlatpakScope::User => capture_user_command(FLATPAK_COMMAND, args),
+    }
+}
+
+// Returns the last component of an app ID in lowercase (e.g., `vlc` for `org.videolan.VLC`).
+fn short_name(id: &str) -> String {
+    id.rsplit('.').next().unwrap_or(id).to_lowercase()
+}
+
+// Suggests Flathub apps for a name that has no app ID, comparing it with the last component
+// of the IDs `flatpak search` finds.
+fn suggest_apps(target: &Target, scope: FlatpakScope, name: &str) -> Vec<String> {
+    let query = short_name(name);
+    let found = capture_flatpak(target, scope, &["search", "--columns=application", &query])
+        .map(|output| parse_app_list(&output))
+        .unwrap_or_default();
+    let short_names: Vec<String> = found.iter().map(|id| short_name(id)).collect();
+    let mut suggestions: Vec<String> = found
+        .iter()
+        .filter(|id| short_name(id) == query)
+        .cloned()
+        .collect();
+    for close in suggest_packages(&query, &short_names) {
+        suggestions.extend(found.iter().filter(|id| short_name(id) == close).cloned());
+    }
+    suggestions.retain(|id| id != name);
+    suggestions.truncate(MAX_SUGGESTIONS);
+    suggestions
+}
+
+/// Installs Flatpak applications from Flathub.
+///
+/// This function installs `flatpak` through the distribution’s package manager when it is
+/// missing, adds the Flathub remote to the chosen installation, and installs the listed apps
+/// from it. Apps may be given by app ID or by a package name the package name table maps to
+/// one (see `resolve_apps`). Each app is checked on Flathub and installed on its own, so one
+/// unknown or failing app does not stop the others; apps that are already installed are
+/// skipped. The status of every app is printed and returned for the task result.
+///
+/// The system installation is changed with root privileges, inside the target for a mounted
+/// root. The user installation is changed as the current user and is only available for the
+/// running host.
+///
+/// # Arguments
+/// * `apps` - App IDs or package names (e.g., `&["firefox", "org.gimp.GIMP"]`).
+/// * `scope` - The installation to add the remote and the apps to.
+/// * `distro` - The Linux distribution identifier, for installing `flatpak` itself.
+/// * `target` - The system the apps are installed into.
+///
+/// # Returns
+/// A `SoftwareReport` with the status of each app, whose `status` is `0` if all of them are
+/// installed.
+///
+/// # Errors
+/// The report’s status is `1` if:
+/// - The user installation is requested for a mounted root.
+/// - `flatpak` is missing and cannot be installed, or the distribution is not supported.
+/// - The Flathub remote cannot be added.
+/// - An app is not found on Flathub or fails to install.
+///
+/// # Example
+/// ```no_run
+/// // Requires sudo privileges and network access.
+/// use glwiz::functionality::{
+///     flatpak::{flatpak_setup, FlatpakScope},
+///     target::Target,
+/// };
+/// let report = flatpak_setup(&["firefox"], FlatpakScope::System, "fedora", &Target::host());
+/// assert_eq!(report.status, 0);
+/// ```
+///
+/// # See Also
+/// - `software::software_setup`: Installs the native packages.
+/// - `profile::ProfilePackages`: Lists the apps to install as Flatpaks.
+pub fn flatpak_setup(
+    apps: &[&str],
+    scope: FlatpakScope,
+    distro: &str,
+    target: &Target,
+) -> SoftwareReport {
+    if scope == FlatpakScope::User && target.is_chroot() {
+        eprintln!(
+            "{} User Flatpak apps cannot be installed into a mounted root; use the system scope.",
+            "error:".red()
+        );
+        return SoftwareReport::failed();
+    }
+    let Some(family) = DistroFamily::from_id(distro) else {
+        eprintln!("{} Unsupported distribution: {}", "error:".red(), distro);
+        return SoftwareReport::failed();
+    };
+
+    let manager = for_family(family, target);
+    let flatpak = manager.package_name(FLATPAK_COMMAND);
+    if !manager.is_installed(&flatpak).unwrap_or(false) {
+        println!("Installing {} with {}...", FLATPAK_COMMAND, manager.name());
+        // A failed refresh is not fatal: the install reports the problem itself.
+        if manager.refreshes_before_install() || manager.index_is_stale() {
+            let _ = manager.refresh_index();
+        }
+        if let Err(e) = manager.install(&[&flatpak]) {
+            eprintln!(
+                "{} Cannot install {}: {}",
+                "error:".red(),
+                FLATPAK_COMMAND,
+                e
+            );
+            return SoftwareReport::failed();
+        }
+    }
+
+    let remote_add = [
+        "remote-add",
+        "--if-not-exists",
+        scope.as_arg(),
+        FLATHUB_REMOTE,
+        FLATHUB_URL,
+    ];
+    if let Err(e) = run_flatpak(target, scope, &remote_add) {
+        eprintln!("{} Cannot add the Flathub remote: {}", "error:".red(), e);
+        return SoftwareReport::failed();
+    }
+
+    // A failed query is not fatal: `flatpak install` skips installed apps itself.
+    let installed: HashSet<String> = capture_flatpak(
+        target,
+        scope,
+        &["list", "--app", "--columns=application", scope.as_arg()],
+    )
+    .map(|output| parse_app_list(&output).into_iter().collect())
+    .unwrap_or_default();
+
+    let (ids, unknown) = resolve_apps(apps, &PackageMap::load());
+    let mut statuses: Vec<(String, PackageStatus)> = unknown
+        .iter()
+        .map(|name| {
+            let suggestions = suggest_apps(target, scope, name);
+            (name.clone(), PackageStatus::NotFound(suggestions))
+        })
+        .collect();
+    for id in &ids {
+        let status = if installed.contains(id) {
+            PackageStatus::AlreadyPresent
+        } else if capture_flatpak(
+            target,
+            scope,
+            &["remote-info", scope.as_arg(), FLATHUB_REMOTE, id],
+        )
+        .is_err()
+        {
+            PackageStatus::NotFound(suggest_apps(target, scope, id))
+        } else {
+            println!("Installing {} from Flathub...", id.green());
+            let install = [
+                "install",
+                "--noninteractive",
+                "-y",
+                scope.as_arg(),
+                FLATHUB_REMOTE,
+                id,
+            ];
+            match run_flatpak(target, scope, &install) {
+                Ok(()) => PackageStatus::Installed,
+                Err(e) => {
+                    eprintln!("{} Cannot install {}: {}", "error:".red(), id, e);
+                    PackageStatus::Failed
+                }
+            }
+        };
+        statuses.push((id.clone(), status));
+    }
+
+    let report = SoftwareReport::new(statuses);
+    report.print();
+    if report.status == 0 {
+        println!("Flatpak apps {}.", "installed".green());
+    } else {
+        eprintln!("{} Some Flatpak apps were not installed.", "error:".red());
+    }
+    report
+}
@@ -0,0 +1,70 @@
+/*
+ * GLWiz - The ultimate post-installation setup assistant for GNU/Linux popular distros,
+ * streamlining your configuration process with ease and precision.
+ * 
+ * Copyright (C) 2025  Andrew Kushyk
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published
+ * by the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use std::sync::atomic::{AtomicBool, Ordering};
+
+// Set by the signal handler once SIGINT or SIGTERM has been received.
+static INTERRUPTED: AtomicBool = AtomicBool::new(false);
+
+/// Installs handlers for `SIGINT` (Ctrl-C) and `SIGTERM` that let the current step finish.
+///
+/// The first signal only records the interruption: the step in progress either completes or,
+/// if its commands were killed by the same signal, fails without leaving a half-written file
+/// behind (every file write goes through `atomic`). No further steps are started afterwards,
+/// and the run is recorded as interrupted so it can be resumed or undone. A second signal
+/// aborts immediately.
+///
+/// # Example
+/// ```
+/// use glwiz::functionality::interrupt::{install_handler, interrupted};
+/// install_handler();
+/// assert!(!interrupted());
+/// ```
+///
+/// # Safety
+/// This function uses `unsafe` to call `libc::signal`. The handler only touches an atomic flag
+/// and calls the async-signal-safe `write` and `_exit`.
+///
+/// # See Also
+/// - `journal`: Records the interrupted run for `--resume` and `undo`.
+pub fn install_handler() {
+    let handler = handle_signal as extern "C" fn(libc::c_int);
+    unsafe {
+        libc::signal(libc::SIGINT, handler as libc::sighandler_t);
+        libc::signal(libc::SIGTERM, handler as libc::sighandler_t);
+    }
+}
+
+/// Returns `true` once an interrupt has been received.
+pub fn interrupted() -> bool {
+    INTERRUPTED.load(Ordering::SeqCst)
+}
+
+// Records the first signal and aborts on the second.
+extern "C" fn handle_signal(_signal: libc::c_int) {
+    const NOTICE: &[u8] =
+        b"\nInterrupted: finishing the current step (press Ctrl-C again to abort).\n";
+    if INTERRUPTED.swap(true, Ordering::SeqCst) {
+        unsafe { libc::_exit(130) };
+    }
+    unsafe {
+        libc::write(2, NOTICE.as_ptr().cast(), NOTICE.len());
+    }
+}
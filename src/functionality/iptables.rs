@@ -18,9 +18,43 @@
  * along with this program.  If not, see <https://www.gnu.org/licenses/>.
  */
 
-use super::commands::{run_sudo_command, run_sudo_command_with_stdin};
+use super::atomic::write_file_as_root;
+use super::commands::{capture_user_command, run_sudo_command};
+use super::distro::DistroFamily;
+use super::service::InitSystem;
+use super::target::Target;
+use super::template::{TemplateVars, read_source};
 use colored::Colorize;
-use std::fs;
+
+/// The bundled iptables rules, relative to the glwiz binary.
+pub const IPTABLES_RULES_SOURCE: &str = "../configs/iptables.rules";
+
+/// The system location of the iptables rules.
+pub const IPTABLES_RULES_PATH: &str = "/etc/iptables/iptables.rules";
+
+/// The location of the iptables rules loaded by the OpenRC `iptables` service on Alpine Linux.
+pub const IPTABLES_OPENRC_RULES_PATH: &str = "/etc/iptables/rules-save";
+
+/// The location of the iptables rules loaded by the `iptables` OpenRC service and the
+/// `iptables-restore` systemd unit on Gentoo.
+pub const IPTABLES_GENTOO_RULES_PATH: &str = "/var/lib/iptables/rules-save";
+
+/// Returns the location of the iptables rules on a distribution family.
+///
+/// # Example
+/// ```
+/// use glwiz::functionality::{distro::DistroFamily, iptables::rules_path};
+/// assert_eq!(rules_path(Some(DistroFamily::Alpine)), "/etc/iptables/rules-save");
+/// assert_eq!(rules_path(Some(DistroFamily::Gentoo)), "/var/lib/iptables/rules-save");
+/// assert_eq!(rules_path(None), "/etc/iptables/iptables.rules");
+/// ```
+pub fn rules_path(family: Option<DistroFamily>) -> &'static str {
+    match family {
+        Some(DistroFamily::Alpine) => IPTABLES_OPENRC_RULES_PATH,
+        Some(DistroFamily::Gentoo) => IPTABLES_GENTOO_RULES_PATH,
+        _ => IPTABLES_RULES_PATH,
+    }
+}
 
 /// Sets up the iptables configuration file for network security.
 ///
@@ -29,6 +63,16 @@ use std::fs;
 /// It checks for the source file’s existence and prompts the user to overwrite the destination if it
 /// exists, making the operation idempotent. The function uses `sudo` to write to the system directory,
 /// ensuring proper permissions. It is part of the post-installation setup to enhance network security.
+/// A source ending in `.tmpl` is rendered with `vars` first, so that values such as a trusted SSH
+/// source address can differ per host. On Alpine Linux the rules are written to
+/// `/etc/iptables/rules-save`, and on Gentoo to `/var/lib/iptables/rules-save`, where their
+/// services load them (see `rules_path`).
+///
+/// # Arguments
+/// * `src` - The rules to install (normally `IPTABLES_RULES_SOURCE`).
+/// * `vars` - The variables available if the source is a template.
+/// * `target` - The system to configure; the destination is resolved below its root.
+/// * `family` - The distribution family, if known, which determines the destination.
 ///
 /// # Returns
 /// * `0` - The rules file was successfully created or skipped (user chose not to overwrite).
@@ -36,40 +80,44 @@ use std::fs;
 ///
 /// # Errors
 /// Returns `1` if:
-/// - The source file `../configs/iptables.rules` does not exist.
-/// - Reading the source file fails due to permissions or I/O errors.
+/// - The source file (e.g., `../configs/iptables.rules`) does not exist.
+/// - Reading the source file fails due to permissions or I/O errors, or the template is invalid.
 /// - Writing to `/etc/iptables/iptables.rules` fails due to permissions or `sudo` issues.
 ///
 /// # Example
 /// ```should_panic
 /// // Requires ../configs/iptables.rules and sudo privileges.
-/// use glwiz::functionality::iptables::iptables_file_setup;
-/// let result = iptables_file_setup();
+/// use glwiz::functionality::{iptables::{iptables_file_setup, IPTABLES_RULES_SOURCE}, target::Target, template::TemplateVars};
+/// let result = iptables_file_setup(IPTABLES_RULES_SOURCE, &TemplateVars::new(), &Target::host(), None);
 /// assert_eq!(result, 0);
 /// ```
 ///
 /// # See Also
-/// - `commands::run_sudo_command_with_stdin`: Used to write the rules file with `sudo`.
+/// - `atomic::write_file_as_root`: Used to write the rules file with `sudo`.
 /// - `prog_fun::read_input`: Used to prompt for overwrite confirmation.
 /// - `iptables_rules_setup`: Applies the configured rules.
-pub fn iptables_file_setup() -> i8 {
-    let src = "../configs/iptables.rules";
-    let dest = "/etc/iptables/iptables.rules";
+pub fn iptables_file_setup(
+    src: &str,
+    vars: &TemplateVars,
+    target: &Target,
+    family: Option<DistroFamily>,
+) -> i8 {
+    let dest = target.path(rules_path(family));
 
     if !std::path::Path::new(src).exists() {
         eprintln!("{} Source file {} not found.", "error:".red(), src);
         return 1;
     }
 
-    let rules = match fs::read_to_string(src) {
+    let rules = match read_source(src, vars) {
         Ok(r) => r,
         Err(e) => {
-            eprintln!("{} Failed to read {}: {}", "error:".red(), src, e);
+            eprintln!("{} {}", "error:".red(), e);
             return 1;
         }
     };
 
-    if std::path::Path::new(dest).exists() {
+    if std::path::Path::new(&dest).exists() {
         println!("{} exists. Overwrite? (y/n)", dest);
         let input = super::prog_fun::read_input().trim().to_lowercase();
         if input != "y" {
@@ -78,7 +126,7 @@ pub fn iptables_file_setup() -> i8 {
         }
     }
 
-    match run_sudo_command_with_stdin("tee", &[dest], rules) {
+    match write_file_as_root(&dest, &rules) {
         Ok(_) => {
             println!("iptables rules {}.", "created".green());
             0
@@ -96,33 +144,81 @@ pub fn iptables_file_setup() -> i8 {
 /// activating the firewall configuration set up by `iptables_file_setup`. It executes the command
 /// with `sudo` to ensure proper permissions and is part of the "glwiz" project’s post-installation
 /// setup to secure the system’s network. The function logs success or failure with descriptive messages.
+/// When the target is a mounted root, the rules are not loaded into the host’s firewall; they take
+/// effect once the installed system boots. On distributions whose default firewall service
+/// (e.g., firewalld on openSUSE) is running, the rules are not loaded either, since the service
+/// would replace them; a warning explains how to switch to the installed rules. On OpenRC and
+/// runit systems the `iptables` service is enabled (`rc-update add iptables`, or a link in
+/// `/var/service`), and on Gentoo with systemd the `iptables-restore` unit, so that the rules are
+/// restored at every boot.
+///
+/// # Arguments
+/// * `target` - The system to configure.
+/// * `family` - The distribution family, if known, whose firewall defaults and init system are
+///   respected.
 ///
 /// # Returns
-/// * `0` - The iptables rules were successfully applied.
+/// * `0` - The iptables rules were successfully applied (or deferred for a mounted root or an
+///   active firewall service).
 /// * `1` - An error occurred, such as a missing rules file or `sudo` command failure.
 ///
 /// # Errors
 /// Returns `1` if:
 /// - The rules file `/etc/iptables/iptables.rules` does not exist or is invalid.
 /// - The `iptables-restore` command fails due to permissions or syntax errors in the rules.
+/// - The OpenRC or runit `iptables` service cannot be enabled.
 ///
 /// # Example
 /// ```
-/// use glwiz::functionality::iptables::iptables_rules_setup;
-/// let result = iptables_rules_setup();
+/// use glwiz::functionality::{iptables::iptables_rules_setup, target::Target};
+/// let result = iptables_rules_setup(&Target::host(), None);
 /// assert_eq!(result, 0); // Rules applied successfully
 /// ```
 ///
 /// # See Also
 /// - `commands::run_sudo_command`: Used to execute `iptables-restore` with `sudo`.
 /// - `iptables_file_setup`: Sets up the rules file before application.
-pub fn iptables_rules_setup() -> i8 {
-    let rules_path = "/etc/iptables/iptables.rules";
+pub fn iptables_rules_setup(target: &Target, family: Option<DistroFamily>) -> i8 {
+    let rules_path = rules_path(family);
 
-    match run_sudo_command(
-        "bash",
-        &["-c", &format!("iptables-restore < {}", rules_path)],
-    ) {
+    let init = InitSystem::detect(target, family);
+    if let Some(service) = restore_service(init, family) {
+        if let Err(e) = init.enable(service, target) {
+            eprintln!(
+                "{} Failed to enable the iptables service: {}",
+                "error:".red(),
+                e
+            );
+            return 1;
+        }
+        println!("iptables service {}.", "enabled".green());
+    }
+
+    if target.is_chroot() {
+        println!(
+            "iptables rules {} until {} boots.",
+            "deferred".green(),
+            target.get_root()
+        );
+        return 0;
+    }
+
+    let active_firewall = family.and_then(|f| f.default_firewall()).filter(|service| {
+        capture_user_command("systemctl", &["is-active", "--quiet", service]).is_ok()
+    });
+    if let Some(service) = active_firewall {
+        eprintln!(
+            "{} {} is active and manages the firewall; the rules in {} were not loaded. \
+             Run `sudo systemctl disable --now {}` to use them instead.",
+            "warning:".yellow(),
+            service,
+            rules_path,
+            service
+        );
+        return 0;
+    }
+
+    match run_sudo_command("sh", &["-c", &format!("iptables-restore < {}", rules_path)]) {
         Ok(_) => {
             println!("iptables rules {}.", "applied".green());
             0
@@ -133,3 +229,271 @@ pub fn iptables_rules_setup() -> i8 {
         }
     }
 }
+
+/// Translates iptables rules into the `networking.firewall` options of a NixOS module.
+///
+/// NixOS’s firewall drops incoming traffic that is not explicitly allowed and accepts replies
+/// and loopback traffic, so the matching `INPUT` rules need no translation. `INPUT` rules that
+/// accept a TCP or UDP port (or port range) from anywhere become `allowedTCPPorts`,
+/// `allowedUDPPorts` and their range variants; other `INPUT` rules are appended to the
+/// `nixos-fw` chain. Since the firewall leaves outgoing and forwarded traffic alone, the
+/// `OUTPUT` and `FORWARD` rules and policies are loaded into `glwiz-output` and
+/// `glwiz-forward` chains through `extraCommands`, and removed again by `extraStopCommands`.
+/// Custom chains are created with a `glwiz-` prefix. Only IPv4 is filtered, as with
+/// `iptables-restore`.
+///
+/// # Arguments
+/// * `rules` - Rules in `iptables-save` format (e.g., the bundled `iptables.rules`).
+///
+/// # Returns
+/// The `networking.firewall = { ... };` attribute, indented for a module’s top level.
+///
+/// # Example
+/// ```
+/// use glwiz::functionality::iptables::nixos_firewall;
+/// let rules = "*filter\n:INPUT DROP [0:0]\n:OUTPUT DROP [0:0]\n\
+///     -A INPUT -i lo -j ACCEPT\n-A INPUT -p tcp --dport 22 -j ACCEPT\n\
+///     -A OUTPUT -p udp --dport 53 -j ACCEPT\nCOMMIT\n";
+/// let firewall = nixos_firewall(rules);
+/// assert!(firewall.contains("allowedTCPPorts = [ 22 ];"));
+/// assert!(firewall.contains("iptables -A glwiz-output -p udp --dport 53 -j ACCEPT"));
+/// assert!(firewall.contains("iptables -A glwiz-output -j DROP"));
+/// assert!(firewall.contains("iptables -X glwiz-output"));
+/// ```
+///
+/// # See Also
+/// - `nixos`: Assembles the NixOS module.
+pub fn nixos_firewall(rules: &str) -> String {
+    let mut policies: Vec<(&str, &str)> = Vec::new();
+    let mut chains: Vec<(&str, Vec<&str>)> = Vec::new();
+    let (mut tcp_ports, mut udp_ports) = (Vec::new(), Vec::new());
+    let (mut tcp_ranges, mut udp_ranges) = (Vec::new(), Vec::new());
+    let mut input_rules = Vec::new();
+
+    for line in rules.lines() {
+        let line = line.split('#').next().unwrap_or_default().trim();
+        if let Some(declaration) = line.strip_prefix(':') {
+            let mut parts = declaration.split_whitespace();
+            if let (Some(chain), Some(policy)) = (parts.next(), parts.next()) {
+                policies.push((chain, policy));
+            }
+            continue;
+        }
+        let Some((chain, spec)) = line.strip_prefix("-A ").and_then(|r| r.split_once(' ')) else {
+            continue;
+        };
+        let spec = spec.trim();
+        if chain != "INPUT" {
+            match chains.iter_mut().find(|(name, _)| *name == chain) {
+                Some((_, specs)) => specs.push(spec),
+                None => chains.push((chain, vec![spec])),
+            }
+        } else if !input_rule_is_default(spec) {
+            match accepted_port(spec) {
+                Some(("tcp", port)) => match port.split_once(':') {
+                    Some(range) => tcp_ranges.push(range),
+                    None => tcp_ports.push(port),
+                },
+                Some((_, port)) => match port.split_once(':') {
+                    Some(range) => udp_ranges.push(range),
+                    None => udp_ports.push(port),
+                },
+                None => input_rules.push(spec),
+            }
+        }
+    }
+
+    // OUTPUT and FORWARD get a glwiz chain hooked into them; custom chains are renamed.
+    let policy = |chain: &str| {
+        policies
+            .iter()
+            .find(|(name, _)| *name == chain)
+            .map(|(_, policy)| *policy)
+    };
+    let custom: Vec<&str> = policies
+        .iter()
+        .filter(|(_, policy)| *policy == "-")
+        .map(|(name, _)| *name)
+        .collect();
+    let hooked: Vec<&str> = ["OUTPUT", "FORWARD"]
+        .into_iter()
+        .filter(|chain| {
+            policy(chain) == Some("DROP") || chains.iter().any(|(name, _)| name == chain)
+        })
+        .collect();
+    let created: Vec<String> = custom
+        .iter()
+        .chain(&hooked)
+        .map(|chain| glwiz_chain(chain))
+        .collect();
+    let rename = |spec: &str, accept: &str| {
+        let mut tokens: Vec<String> = spec.split_whitespace().map(String::from).collect();
+        for i in 1..tokens.len() {
+            if tokens[i - 1] == "-j" || tokens[i - 1] == "-g" {
+                if tokens[i] == "ACCEPT" {
+                    tokens[i] = accept.to_string();
+                } else if custom.contains(&tokens[i].as_str()) {
+                    tokens[i] = glwiz_chain(&tokens[i]);
+                }
+            }
+        }
+        tokens.join(" ")
+    };
+
+    let mut start = Vec::new();
+    for chain in &created {
+        start.push(format!("iptables -N {} 2>/dev/null || true", chain));
+        start.push(format!("iptables -F {}", chain));
+    }
+    for chain in &hooked {
+        start.push(format!(
+            "iptables -D {} -j {} 2>/dev/null || true",
+            chain,
+            glwiz_chain(chain)
+        ));
+        start.push(format!("iptables -A {} -j {}", chain, glwiz_chain(chain)));
+    }
+    for (chain, specs) in chains
+        .iter()
+        .filter(|(chain, _)| hooked.contains(chain) || custom.contains(chain))
+    {
+        for spec in specs {
+            start.push(format!(
+                "iptables -A {} {}",
+                glwiz_chain(chain),
+                rename(spec, "ACCEPT")
+            ));
+        }
+    }
+    for chain in &hooked {
+        let ends_with_drop = chains
+            .iter()
+            .find(|(name, _)| name == chain)
+            .and_then(|(_, specs)| specs.last())
+            .is_some_and(|spec| *spec == "-j DROP");
+        if policy(chain) == Some("DROP") && !ends_with_drop {
+            start.push(format!("iptables -A {} -j DROP", glwiz_chain(chain)));
+        }
+    }
+    for spec in &input_rules {
+        start.push(format!(
+            "iptables -A nixos-fw {}",
+            rename(spec, "nixos-fw-accept")
+        ));
+    }
+
+    let mut stop: Vec<String> = hooked
+        .iter()
+        .map(|chain| {
+            format!(
+                "iptables -D {} -j {} 2>/dev/null || true",
+                chain,
+                glwiz_chain(chain)
+            )
+        })
+        .collect();
+    stop.extend(
+        created
+            .iter()
+            .map(|chain| format!("iptables -F {} 2>/dev/null || true", chain)),
+    );
+    stop.extend(
+        created
+            .iter()
+            .map(|chain| format!("iptables -X {} 2>/dev/null || true", chain)),
+    );
+
+    format!(
+        "  networking.firewall = {{\n    enable = true;\n    allowPing = false;\n    \
+         allowedTCPPorts = [ {}];\n    allowedUDPPorts = [ {}];\n    \
+         allowedTCPPortRanges = [ {}];\n    allowedUDPPortRanges = [ {}];\n    \
+         extraCommands = ''\n{}    '';\n    extraStopCommands = ''\n{}    '';\n  }};\n",
+        nix_list(tcp_ports.iter().map(|port| port.to_string())),
+        nix_list(udp_ports.iter().map(|port| port.to_string())),
+        nix_list(tcp_ranges.iter().map(nix_range)),
+        nix_list(udp_ranges.iter().map(nix_range)),
+        nix_lines(&start),
+        nix_lines(&stop)
+    )
+}
+
+// Returns the service that restores the saved rules at boot, if glwiz has to enable it. On
+// other systemd distributions the rules are loaded from `IPTABLES_RULES_PATH` by the unit the
+// iptables package enables.
+fn restore_service(init: InitSystem, family: Option<DistroFamily>) -> Option<&'static str> {
+    match (init, family) {
+        (InitSystem::Systemd, Some(DistroFamily::Gentoo)) => Some("iptables-restore"),
+        (InitSystem::Systemd, _) => None,
+        _ => Some("iptables"),
+    }
+}
+
+// Returns the name of the chain glwiz loads the rules of an iptables chain into.
+fn glwiz_chain(chain: &str) -> String {
+    format!("glwiz-{}", chain.to_lowercase())
+}
+
+// Renders Nix list elements, each followed by a space.
+fn nix_list(items: impl Iterator<Item = String>) -> String {
+    items.map(|item| format!("{} ", item)).collect()
+}
+
+// Renders a port range as a NixOS firewall range attribute set.
+fn nix_range((from, to): &(&str, &str)) -> String {
+    format!("{{ from = {}; to = {}; }}", from, to)
+}
+
+// Renders shell commands as the lines of a Nix indented string, escaping `''` and `${`.
+fn nix_lines(commands: &[String]) -> String {
+    commands
+        .iter()
+        .map(|command| {
+            format!(
+                "      {}\n",
+                command.replace("''", "'''").replace("${", "''${")
+            )
+        })
+        .collect()
+}
+
+// Returns `true` for `INPUT` rules that NixOS’s firewall applies by itself: accepting replies
+// and loopback traffic, and dropping invalid or all remaining packets.
+fn input_rule_is_default(spec: &str) -> bool {
+    let tokens: Vec<&str> = spec.split_whitespace().collect();
+    match tokens.as_slice() {
+        ["-j", "DROP" | "REJECT"] | ["-i", "lo", "-j", "ACCEPT"] => true,
+        [
+            "-m",
+            "conntrack" | "state",
+            "--ctstate" | "--state",
+            state,
+            "-j",
+            target,
+        ] => matches!(
+            (*state, *target),
+            ("RELATED,ESTABLISHED" | "ESTABLISHED,RELATED", "ACCEPT") | ("INVALID", "DROP")
+        ),
+        _ => false,
+    }
+}
+
+// Returns the protocol and port (or `from:to` range) of an `INPUT` rule that accepts a TCP or
+// UDP destination port from any source, optionally for new connections only.
+fn accepted_port(spec: &str) -> Option<(&str, &str)> {
+    let tokens: Vec<&str> = spec.split_whitespace().collect();
+    if !tokens.len().is_multiple_of(2) {
+        return None;
+    }
+    let (mut protocol, mut port, mut accepted) = (None, None, false);
+    for pair in tokens.chunks(2) {
+        match (pair[0], pair[1]) {
+            ("-p", name @ ("tcp" | "udp")) => protocol = Some(name),
+            ("--dport", value) => port = Some(value),
+            ("-j", "ACCEPT") => accepted = true,
+            ("-m", "tcp" | "udp" | "conntrack" | "state") | ("--ctstate" | "--state", "NEW") => {}
+            _ => return None,
+        }
+    }
+    let port = port.filter(|port| port.split(':').all(|part| part.parse::<u16>().is_ok()))?;
+    accepted.then_some((protocol?, port))
+}
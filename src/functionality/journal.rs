@@ -0,0 +1,392 @@
+/*
+ * GLWiz - The ultimate post-installation setup assistant for GNU/Linux popular distros,
+ * streamlining your configuration process with ease and precision.
+ * 
+ * Copyright (C) 2025  Andrew Kushyk
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published
+ * by the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use super::{
+    atomic::{copy_path, copy_path_as_root},
+    commands::{run_sudo_command, run_user_command},
+    env::get_env_var,
+};
+use colored::Colorize;
+use serde::{Deserialize, Serialize};
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    sync::Mutex,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+/// The name of the run record inside the state directory.
+pub const RUN_RECORD_FILE: &str = "run.toml";
+
+// The run being recorded, with the state directory it is saved in.
+static CURRENT: Mutex<Option<(PathBuf, RunRecord)>> = Mutex::new(None);
+
+/// The state of a recorded run.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum RunStatus {
+    /// The run is in progress, or the process died without recording an outcome.
+    Running,
+    /// The run stopped after Ctrl-C or `SIGTERM`.
+    Interrupted,
+    /// The run finished with failed tasks.
+    Failed,
+    /// The run finished successfully.
+    Completed,
+    /// The files changed by the run were restored with `glwiz undo`.
+    Undone,
+}
+
+/// A file or directory changed by a run, with the backup needed to undo the change.
+///
+/// # Fields
+/// * `path` - The path that was written.
+/// * `backup` - A copy of the previous contents, or `None` if the path did not exist.
+/// * `privileged` - Whether the path is root-owned and must be restored with `sudo`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FileChange {
+    pub path: String,
+    pub backup: Option<String>,
+    pub privileged: bool,
+}
+
+/// The record of a setup run, saved after every step so that an interrupted run can be resumed
+/// or undone.
+///
+/// # Fields
+/// * `status` - The state of the run.
+/// * `started` - The start time, in seconds since the Unix epoch.
+/// * `completed` - The tasks that completed successfully, in order.
+/// * `changes` - The files written, in order.
+///
+/// # Example
+/// ```
+/// use glwiz::functionality::journal::{RunRecord, RunStatus};
+/// let record = RunRecord::new();
+/// assert_eq!(record.status, RunStatus::Running);
+/// assert!(record.completed.is_empty());
+/// ```
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RunRecord {
+    pub status: RunStatus,
+    pub started: u64,
+    #[serde(default)]
+    pub completed: Vec<String>,
+    #[serde(default)]
+    pub changes: Vec<FileChange>,
+}
+
+impl Default for RunRecord {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl RunRecord {
+    /// Creates the record of a run starting now.
+    pub fn new() -> Self {
+        Self {
+            status: RunStatus::Running,
+            started: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|d| d.as_secs())
+                .unwrap_or(0),
+            completed: Vec::new(),
+            changes: Vec::new(),
+        }
+    }
+
+    /// Loads the run record from a state directory.
+    ///
+    /// # Arguments
+    /// * `dir` - The state directory (see `state_dir`).
+    ///
+    /// # Returns
+    /// * `Ok(Some(RunRecord))` - The recorded run.
+    /// * `Ok(None)` - No run has been recorded.
+    /// * `Err(String)` - An error message if the record cannot be read or parsed.
+    pub fn load(dir: &Path) -> Result<Option<Self>, String> {
+        let path = dir.join(RUN_RECORD_FILE);
+        if !path.exists() {
+            return Ok(None);
+        }
+        let contents = fs::read_to_string(&path)
+            .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+        toml::from_str(&contents)
+            .map(Some)
+            .map_err(|e| format!("Invalid run record {}: {}", path.display(), e))
+    }
+
+    /// Saves the run record to a state directory, creating it if needed.
+    ///
+    /// # Arguments
+    /// * `dir` - The state directory (see `state_dir`).
+    ///
+    /// # Returns
+    /// * `Ok(())` - The record was saved.
+    /// * `Err(String)` - An error message if the record cannot be written.
+    pub fn save(&self, dir: &Path) -> Result<(), String> {
+        fs::create_dir_all(dir)
+            .map_err(|e| format!("Failed to create {}: {}", dir.display(), e))?;
+        let contents =
+            toml::to_string(self).map_err(|e| format!("Failed to serialize run record: {}", e))?;
+        let path = dir.join(RUN_RECORD_FILE);
+        let tmp = dir.join(format!("{}.tmp", RUN_RECORD_FILE));
+        fs::write(&tmp, contents)
+            .and_then(|()| fs::rename(&tmp, &path))
+            .map_err(|e| format!("Failed to write {}: {}", path.display(), e))
+    }
+}
+
+/// Returns the directory holding the run record and the backups of changed files.
+///
+/// This is `$XDG_STATE_HOME/glwiz`, or `~/.local/state/glwiz` if `XDG_STATE_HOME` is unset.
+///
+/// # Returns
+/// * `Ok(PathBuf)` - The state directory.
+/// * `Err(String)` - An error message if neither `XDG_STATE_HOME` nor `HOME` is set.
+pub fn state_dir() -> Result<PathBuf, String> {
+    match get_env_var("XDG_STATE_HOME") {
+        Ok(dir) if !dir.is_empty() => Ok(Path::new(&dir).join("glwiz")),
+        _ => get_env_var("HOME").map(|home| Path::new(&home).join(".local/state/glwiz")),
+    }
+}
+
+/// Starts recording a run, or continues the previously interrupted one.
+///
+/// A new run discards the record and backups of the previous one. When resuming, the previous
+/// run must not have completed or been undone; its completed tasks are skipped and its backups
+/// are kept, so `glwiz undo` still restores the state from before the first attempt.
+///
+/// # Arguments
+/// * `resume` - Whether to continue the previous run.
+///
+/// # Returns
+/// * `Ok(())` - Recording started.
+/// * `Err(String)` - An error message if there is nothing to resume, the backups of the
+///   previous run cannot be removed, or the record cannot be written.
+pub fn begin(resume: bool) -> Result<(), String> {
+    let dir = state_dir()?;
+    let previous = RunRecord::load(&dir)?;
+
+    let record = if resume {
+        match previous {
+            Some(mut record)
+                if !matches!(record.status, RunStatus::Completed | RunStatus::Undone) =>
+            {
+                println!(
+                    "Resuming previous run ({} task(s) already done).",
+                    record.completed.len().to_string().green()
+                );
+                record.status = RunStatus::Running;
+                record
+            }
+            _ => return Err("There is no interrupted run to resume.".to_string()),
+        }
+    } else {
+        if let Some(previous) = previous
+            && matches!(previous.status, RunStatus::Running | RunStatus::Interrupted)
+        {
+            println!(
+                "{} Discarding the record of the previous unfinished run.",
+                "warning:".yellow()
+            );
+        }
+        remove_backups(&dir)?;
+        RunRecord::new()
+    };
+
+    record.save(&dir)?;
+    *lock() = Some((dir, record));
+    Ok(())
+}
+
+/// Returns `true` if the run being resumed already completed the task.
+///
+/// # Arguments
+/// * `task` - The task name, as used in the setup report.
+pub fn is_completed(task: &str) -> bool {
+    lock()
+        .as_ref()
+        .is_some_and(|(_, record)| record.completed.iter().any(|t| t == task))
+}
+
+/// Records that a task completed successfully.
+///
+/// # Arguments
+/// * `task` - The task name, as used in the setup report.
+pub fn complete_task(task: &str) {
+    update(|record| record.completed.push(task.to_string()));
+}
+
+/// Records the outcome of the run and stops recording.
+///
+/// # Arguments
+/// * `status` - The final state of the run.
+pub fn finish(status: RunStatus) {
+    update(|record| record.status = status);
+    *lock() = None;
+}
+
+/// Backs up a path before it is changed, so that the change can be undone.
+///
+/// Only the first change to a path is recorded, keeping the original contents. Nothing is
+/// recorded when no run is being recorded (e.g., when glwiz is used as a library).
+///
+/// # Arguments
+/// * `path` - The file or directory about to be written.
+/// * `privileged` - Whether the path is root-owned and must be copied with `sudo`.
+///
+/// # Returns
+/// * `Ok(())` - The path was backed up (or did not exist).
+/// * `Err(String)` - An error message if the backup could not be made; the caller must not
+///   change the path then.
+pub fn record_change(path: &str, privileged: bool) -> Result<(), String> {
+    let mut current = lock();
+    let Some((dir, record)) = current.as_mut() else {
+        return Ok(());
+    };
+    if record.changes.iter().any(|c| c.path == path) {
+        return Ok(());
+    }
+
+    let exists = if privileged {
+        run_sudo_command("test", &["-e", path]).is_ok()
+    } else {
+        Path::new(path).exists()
+    };
+    let backup = if exists {
+        let backup_dir = dir.join("backup");
+        fs::create_dir_all(&backup_dir)
+            .map_err(|e| format!("Failed to create {}: {}", backup_dir.display(), e))?;
+        let name = Path::new(path)
+            .file_name()
+            .map(|n| n.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        let backup = backup_dir
+            .join(format!("{}-{}", record.changes.len(), name))
+            .to_string_lossy()
+            .into_owned();
+        let args = ["-a", path, backup.as_str()];
+        if privileged {
+            run_sudo_command("cp", &args)?;
+        } else {
+            run_user_command("cp", &args)?;
+        }
+        Some(backup)
+    } else {
+        None
+    };
+
+    record.changes.push(FileChange {
+        path: path.to_string(),
+        backup,
+        privileged,
+    });
+    record.save(dir)
+}
+
+/// Restores the files changed by the last recorded run.
+///
+/// Changed files are restored from their backups and files the run created are removed, in
+/// reverse order. Installed packages, changed login shells and loaded firewall rules are not
+/// reverted.
+///
+/// # Returns
+/// * `Ok(())` - Every change was undone.
+/// * `Err(String)` - An error message if there is no run to undo or a file could not be restored.
+///
+/// # Example
+/// ```no_run
+/// // Requires a recorded run and sudo privileges.
+/// use glwiz::functionality::journal::undo_last_run;
+/// undo_last_run().unwrap();
+/// ```
+pub fn undo_last_run() -> Result<(), String> {
+    let dir = state_dir()?;
+    let mut record = match RunRecord::load(&dir)? {
+        Some(record) if record.status != RunStatus::Undone => record,
+        _ => return Err("There is no recorded run to undo.".to_string()),
+    };
+
+    let mut failed = 0;
+    for change in record.changes.iter().rev() {
+        let result = match (&change.backup, change.privileged) {
+            (Some(backup), true) => copy_path_as_root(backup, &change.path),
+            (Some(backup), false) => copy_path(backup, &change.path),
+            (None, true) => run_sudo_command("rm", &["-rf", &change.path]),
+            (None, false) => run_user_command("rm", &["-rf", &change.path]),
+        };
+        match result {
+            Ok(()) if change.backup.is_some() => {
+                println!("{} {}.", change.path, "restored".green())
+            }
+            Ok(()) => println!("{} {}.", change.path, "removed".green()),
+            Err(e) => {
+                eprintln!(
+                    "{} Failed to restore {}: {}",
+                    "error:".red(),
+                    change.path,
+                    e
+                );
+                failed += 1;
+            }
+        }
+    }
+
+    if failed > 0 {
+        return Err(format!("{} file(s) could not be restored.", failed));
+    }
+    record.status = RunStatus::Undone;
+    record.save(&dir)?;
+    remove_backups(&dir)?;
+    println!(
+        "{} Installed packages, login shells and loaded firewall rules are not reverted.",
+        "note:".yellow()
+    );
+    Ok(())
+}
+
+// Removes the backups of the previous run. Backups of privileged paths are root-owned, so they
+// are removed with `sudo` if the current user cannot remove them; a stale backup left behind
+// would otherwise receive the next run’s copy of the same path.
+fn remove_backups(dir: &Path) -> Result<(), String> {
+    let backup_dir = dir.join("backup");
+    if !backup_dir.exists() || fs::remove_dir_all(&backup_dir).is_ok() {
+        return Ok(());
+    }
+    run_sudo_command("rm", &["-rf", &backup_dir.to_string_lossy()])
+        .map_err(|e| format!("Failed to remove {}: {}", backup_dir.display(), e))
+}
+
+// Locks the current run, recovering from a poisoned lock.
+fn lock() -> std::sync::MutexGuard<'static, Option<(PathBuf, RunRecord)>> {
+    CURRENT.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+// Applies a change to the current run and saves it.
+fn update(change: impl FnOnce(&mut RunRecord)) {
+    if let Some((dir, record)) = lock().as_mut() {
+        change(record);
+        if let Err(e) = record.save(dir) {
+            eprintln!("{} {}", "warning:".yellow(), e);
+        }
+    }
+}
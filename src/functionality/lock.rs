@@ -0,0 +1,251 @@
+/*
+ * GLWiz - The ultimate post-installation setup assistant for GNU/Linux popular distros,
+ * streamlining your configuration process with ease and precision.
+ * 
+ * Copyright (C) 2025  Andrew Kushyk
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published
+ * by the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use super::{commands::run_sudo_command, env::get_env_var};
+use colored::Colorize;
+use std::{
+    fs::{File, OpenOptions, Permissions},
+    io::{Read, Seek, Write},
+    os::{
+        fd::AsRawFd,
+        unix::fs::{OpenOptionsExt, PermissionsExt},
+    },
+    path::Path,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+/// The system-wide lock file, shared by all users.
+pub const SYSTEM_LOCK_PATH: &str = "/run/glwiz.lock";
+
+/// The name of the per-user lock file, created in `$XDG_RUNTIME_DIR`. Without a runtime
+/// directory, `/tmp/glwiz-<UID>.lock` is used instead.
+pub const USER_LOCK_FILE: &str = "glwiz.lock";
+
+/// The process holding a lock, as recorded in the lock file.
+///
+/// # Fields
+/// * `pid` - The process ID of the holder.
+/// * `started` - When the holder took the lock, in seconds since the Unix epoch.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LockHolder {
+    pub pid: u32,
+    pub started: u64,
+}
+
+impl LockHolder {
+    /// Parses the contents of a lock file.
+    ///
+    /// # Arguments
+    /// * `contents` - The lock file contents (`pid=<PID>` and `started=<SECONDS>` lines).
+    ///
+    /// # Returns
+    /// * `Some(LockHolder)` - The recorded holder.
+    /// * `None` - The file does not describe a holder (e.g., it is empty).
+    ///
+    /// # Example
+    /// ```
+    /// use glwiz::functionality::lock::LockHolder;
+    /// let holder = LockHolder::parse("pid=4242\nstarted=1760000000\n").unwrap();
+    /// assert_eq!(holder.pid, 4242);
+    /// assert_eq!(holder.started, 1760000000);
+    /// assert!(LockHolder::parse("").is_none());
+    /// ```
+    pub fn parse(contents: &str) -> Option<Self> {
+        let value = |key: &str| {
+            contents
+                .lines()
+                .find_map(|line| line.strip_prefix(key)?.strip_prefix('='))
+                .and_then(|v| v.trim().parse().ok())
+        };
+        Some(Self {
+            pid: value("pid")? as u32,
+            started: value("started")?,
+        })
+    }
+
+    /// Describes the holder for messages (e.g., `"PID 4242, started 2025-10-09 08:53:20 UTC"`).
+    ///
+    /// # Example
+    /// ```
+    /// use glwiz::functionality::lock::LockHolder;
+    /// let holder = LockHolder { pid: 4242, started: 1760000000 };
+    /// assert_eq!(holder.describe(), "PID 4242, started 2025-10-09 08:53:20 UTC");
+    /// ```
+    pub fn describe(&self) -> String {
+        format!("PID {}, started {}", self.pid, format_utc(self.started))
+    }
+}
+
+/// An exclusive lock held for the duration of a glwiz run.
+///
+/// The lock is an advisory `flock` on a lock file, so it is released automatically when the
+/// `RunLock` is dropped or the process exits, even after a crash.
+#[derive(Debug)]
+pub struct RunLock {
+    _files: Vec<File>,
+}
+
+impl RunLock {
+    /// Takes the system-wide lock (`/run/glwiz.lock`) and the current user’s lock.
+    ///
+    /// This function uses `unsafe` to call `libc::getuid` and `libc::flock`.
+    ///
+    /// If another glwiz run holds either lock, its PID and start time are reported. With
+    /// `wait`, the call blocks until the lock is released; otherwise it fails immediately.
+    ///
+    /// # Arguments
+    /// * `wait` - Whether to wait for a running instance to finish.
+    ///
+    /// # Returns
+    /// * `Ok(RunLock)` - Both locks are held until the value is dropped.
+    /// * `Err(String)` - An error message naming the holder, or describing why a lock file could
+    ///   not be opened.
+    ///
+    /// # Example
+    /// ```no_run
+    /// // Requires sudo privileges to create /run/glwiz.lock on first use.
+    /// use glwiz::functionality::lock::RunLock;
+    /// let _lock = RunLock::acquire(false).unwrap();
+    /// // ... run the setup ...
+    /// ```
+    pub fn acquire(wait: bool) -> Result<Self, String> {
+        let user_lock = match get_env_var("XDG_RUNTIME_DIR") {
+            Ok(dir) if Path::new(&dir).is_dir() => format!("{}/{}", dir, USER_LOCK_FILE),
+            _ => format!("/tmp/glwiz-{}.lock", unsafe { libc::getuid() }),
+        };
+
+        ensure_system_lock_file()?;
+        let system = lock_file(SYSTEM_LOCK_PATH, 0o666, wait)?;
+        let user = lock_file(&user_lock, 0o600, wait)?;
+        Ok(Self {
+            _files: vec![system, user],
+        })
+    }
+}
+
+// Creates the system lock file, writable by every user, if it does not exist yet. The mode is
+// set again after creating the file directly (e.g., as root), since the umask narrows it.
+fn ensure_system_lock_file() -> Result<(), String> {
+    if Path::new(SYSTEM_LOCK_PATH).exists() {
+        return Ok(());
+    }
+    if let Ok(file) = OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .mode(0o666)
+        .open(SYSTEM_LOCK_PATH)
+    {
+        return file
+            .set_permissions(Permissions::from_mode(0o666))
+            .map_err(|e| format!("Failed to make {} writable: {}", SYSTEM_LOCK_PATH, e));
+    }
+    run_sudo_command("install", &["-m", "0666", "/dev/null", SYSTEM_LOCK_PATH])
+        .map_err(|e| format!("Failed to create {}: {}", SYSTEM_LOCK_PATH, e))
+}
+
+// Opens a lock file and takes an exclusive lock on it, then records this process as holder.
+fn lock_file(path: &str, mode: u32, wait: bool) -> Result<File, String> {
+    let mut file = OpenOptions::new()
+        .read(true)
+        .write(true)
+        .create(true)
+        .truncate(false)
+        .mode(mode)
+        .open(path)
+        .map_err(|e| format!("Failed to open lock file {}: {}", path, e))?;
+
+    if !try_flock(&file, false)? {
+        let mut contents = String::new();
+        let _ = file.read_to_string(&mut contents);
+        let holder = LockHolder::parse(&contents)
+            .map(|h| h.describe())
+            .unwrap_or_else(|| "unknown process".to_string());
+        if !wait {
+            return Err(format!(
+                "Another glwiz run holds {} ({}). Use --wait to wait for it to finish.",
+                path, holder
+            ));
+        }
+        println!(
+            "{} for the glwiz run holding {} ({})...",
+            "Waiting".yellow(),
+            path,
+            holder
+        );
+        try_flock(&file, true)?;
+    }
+
+    let started = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0);
+    let record = format!("pid={}\nstarted={}\n", std::process::id(), started);
+    file.set_len(0)
+        .and_then(|()| file.rewind())
+        .and_then(|()| file.write_all(record.as_bytes()))
+        .map_err(|e| format!("Failed to write lock file {}: {}", path, e))?;
+    Ok(file)
+}
+
+// Takes an exclusive `flock`, returning `Ok(false)` if it is held elsewhere and `blocking` is
+// not set.
+fn try_flock(file: &File, blocking: bool) -> Result<bool, String> {
+    let operation = if blocking {
+        libc::LOCK_EX
+    } else {
+        libc::LOCK_EX | libc::LOCK_NB
+    };
+    loop {
+        if unsafe { libc::flock(file.as_raw_fd(), operation) } == 0 {
+            return Ok(true);
+        }
+        let err = std::io::Error::last_os_error();
+        match err.raw_os_error() {
+            Some(libc::EWOULDBLOCK) => return Ok(false),
+            Some(libc::EINTR) => continue,
+            _ => return Err(format!("Failed to lock: {}", err)),
+        }
+    }
+}
+
+// Formats seconds since the Unix epoch as a UTC date and time.
+fn format_utc(secs: u64) -> String {
+    let days = (secs / 86_400) as i64;
+    let rem = secs % 86_400;
+    // Civil-from-days conversion (Howard Hinnant's algorithm).
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1_460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    format!(
+        "{:04}-{:02}-{:02} {:02}:{:02}:{:02} UTC",
+        year,
+        month,
+        day,
+        rem / 3_600,
+        rem % 3_600 / 60,
+        rem % 60
+    )
+}
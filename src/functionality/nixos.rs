@@ -0,0 +1,327 @@
+/*
+ * GLWiz - The ultimate post-installation setup assistant for GNU/Linux popular distros,
+ * streamlining your configuration process with ease and precision.
+ * 
+ * Copyright (C) 2025  Andrew Kushyk
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published
+ * by the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use super::{atomic::write_file, prog_fun::read_input};
+use colored::Colorize;
+use std::{fs, path::Path};
+
+/// The os-release `ID` of NixOS.
+pub const NIXOS_ID: &str = "nixos";
+
+/// The directory, below the working directory, the generated modules are written to.
+pub const NIXOS_OUTPUT_DIR: &str = "glwiz-nixos";
+
+/// The file name of the generated NixOS module.
+pub const NIXOS_MODULE_FILE: &str = "glwiz.nix";
+
+/// The file name of the generated home-manager module.
+pub const HOME_MANAGER_MODULE_FILE: &str = "home.nix";
+
+/// The file name of the Vim configuration referenced by the home-manager module.
+pub const HOME_MANAGER_VIMRC_FILE: &str = "vimrc";
+
+/// The settings a NixOS module is generated from.
+///
+/// # Fields
+/// * `packages` - The nixpkgs attributes installed into `environment.systemPackages`.
+/// * `omz_theme` - The Oh My Zsh theme, if set.
+/// * `omz_plugins` - The Oh My Zsh plugins enabled in `.zshrc`.
+/// * `custom_plugins` - Plugins that are not part of Oh My Zsh; each is taken from the nixpkgs
+///   package of the same name.
+/// * `firewall` - The `networking.firewall` attribute (see `iptables::nixos_firewall`).
+/// * `zram_swap` - The `zramSwap` attribute (see `zram::nixos_zram_swap`).
+///
+/// # Example
+/// ```
+/// use glwiz::functionality::nixos::NixosSettings;
+/// let settings = NixosSettings {
+///     packages: vec!["git".to_string()],
+///     ..Default::default()
+/// };
+/// assert!(settings.custom_plugins.is_empty());
+/// ```
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct NixosSettings {
+    pub packages: Vec<String>,
+    pub omz_theme: Option<String>,
+    pub omz_plugins: Vec<String>,
+    pub custom_plugins: Vec<String>,
+    pub firewall: String,
+    pub zram_swap: String,
+}
+
+/// Renders a NixOS module applying the setup declaratively.
+///
+/// The module installs the packages system-wide, makes Zsh the default shell of all users
+/// (root included) and enables Oh My Zsh with the theme and plugins of the `.zshrc`, adding
+/// the plugins that are not part of Oh My Zsh through a custom directory built from nixpkgs.
+/// The firewall and zram swap attributes are included as given.
+///
+/// # Arguments
+/// * `settings` - The settings to render.
+///
+/// # Returns
+/// The module, ready to be imported from `configuration.nix`.
+///
+/// # Example
+/// ```
+/// use glwiz::functionality::nixos::{nixos_module, NixosSettings};
+/// let module = nixos_module(&NixosSettings {
+///     packages: vec!["git".to_string(), "vim".to_string()],
+///     omz_plugins: vec!["git".to_string(), "zsh-autosuggestions".to_string()],
+///     custom_plugins: vec!["zsh-autosuggestions".to_string()],
+///     ..Default::default()
+/// });
+/// assert!(module.contains("environment.systemPackages = with pkgs; [\n    git\n    vim\n  ];"));
+/// assert!(module.contains("users.defaultUserShell = pkgs.zsh;"));
+/// assert!(module.contains("plugins = [ \"git\" \"zsh-autosuggestions\" ];"));
+/// assert!(module.contains("${pkgs.zsh-autosuggestions}/share/zsh-autosuggestions/zsh-autosuggestions.zsh"));
+/// ```
+///
+/// # See Also
+/// - `home_manager_module`: The per-user counterpart.
+pub fn nixos_module(settings: &NixosSettings) -> String {
+    let mut module = format!(
+        "# Generated by glwiz. Import it from /etc/nixos/configuration.nix\n\
+         # (imports = [ ./{}/{} ];) and apply it with `sudo nixos-rebuild switch`.\n\
+         {{ pkgs, ... }}:\n\n{}{{\n",
+        NIXOS_OUTPUT_DIR,
+        NIXOS_MODULE_FILE,
+        omz_custom(&settings.custom_plugins)
+    );
+    module.push_str("  environment.systemPackages = with pkgs; [\n");
+    for package in &settings.packages {
+        module.push_str(&format!("    {}\n", nix_package(package)));
+    }
+    module.push_str("  ];\n\n  users.defaultUserShell = pkgs.zsh;\n\n");
+    module.push_str("  programs.zsh = {\n    enable = true;\n    ohMyZsh = {\n");
+    module.push_str(&omz_options(settings, "      "));
+    module.push_str("    };\n  };\n");
+    for attribute in [&settings.firewall, &settings.zram_swap] {
+        if !attribute.is_empty() {
+            module.push('\n');
+            module.push_str(attribute);
+        }
+    }
+    module.push_str("}\n");
+    module
+}
+
+/// Renders a home-manager module with the per-user part of the setup.
+///
+/// The module enables Zsh with Oh My Zsh for the user, as `nixos_module` does system-wide, and
+/// optionally installs the Vim configuration from `HOME_MANAGER_VIMRC_FILE` next to it. The
+/// bundled `.zshrc` is not installed, since home-manager generates the file.
+///
+/// # Arguments
+/// * `settings` - The settings to render.
+/// * `vimrc` - Whether to install the Vim configuration.
+///
+/// # Returns
+/// The module, ready to be imported from a home-manager configuration.
+///
+/// # Example
+/// ```
+/// use glwiz::functionality::nixos::{home_manager_module, NixosSettings};
+/// let module = home_manager_module(&NixosSettings::default(), true);
+/// assert!(module.contains("oh-my-zsh = {"));
+/// assert!(module.contains("home.file.\".vimrc\".source = ./vimrc;"));
+/// ```
+pub fn home_manager_module(settings: &NixosSettings, vimrc: bool) -> String {
+    let mut module = format!(
+        "# Generated by glwiz. Import it from your home-manager configuration\n\
+         # (imports = [ ./{}/{} ];).\n\
+         {{ pkgs, ... }}:\n\n{}{{\n",
+        NIXOS_OUTPUT_DIR,
+        HOME_MANAGER_MODULE_FILE,
+        omz_custom(&settings.custom_plugins)
+    );
+    module.push_str("  programs.zsh = {\n    enable = true;\n    oh-my-zsh = {\n");
+    module.push_str(&omz_options(settings, "      "));
+    module.push_str("    };\n  };\n");
+    if vimrc {
+        module.push_str(&format!(
+            "\n  home.file.\".vimrc\".source = ./{};\n",
+            HOME_MANAGER_VIMRC_FILE
+        ));
+    }
+    module.push_str("}\n");
+    module
+}
+
+/// Writes the generated NixOS module, and optionally the home-manager module, to a directory.
+///
+/// Nothing on the system is changed: the files are written below `out_dir` and the user is told
+/// how to import them and apply the configuration with `nixos-rebuild`. Existing files trigger
+/// the usual overwrite prompt.
+///
+/// # Arguments
+/// * `settings` - The settings to render.
+/// * `vimrc` - The Vim configuration for the home-manager module, if any.
+/// * `out_dir` - The directory to write to (normally `NIXOS_OUTPUT_DIR`).
+/// * `home_manager` - Whether to write the home-manager module as well.
+///
+/// # Returns
+/// * `0` - The modules were written or skipped.
+/// * `1` - The directory or a file could not be written.
+///
+/// # Example
+/// ```no_run
+/// // Prompts before overwriting existing files.
+/// use glwiz::functionality::nixos::{nixos_modules_setup, NixosSettings, NIXOS_OUTPUT_DIR};
+/// let result = nixos_modules_setup(&NixosSettings::default(), None, NIXOS_OUTPUT_DIR, false);
+/// assert_eq!(result, 0);
+/// ```
+pub fn nixos_modules_setup(
+    settings: &NixosSettings,
+    vimrc: Option<&str>,
+    out_dir: &str,
+    home_manager: bool,
+) -> i8 {
+    if let Err(e) = fs::create_dir_all(out_dir) {
+        eprintln!("{} Failed to create {}: {}", "error:".red(), out_dir, e);
+        return 1;
+    }
+
+    let mut files = vec![(NIXOS_MODULE_FILE, nixos_module(settings))];
+    if home_manager {
+        files.push((
+            HOME_MANAGER_MODULE_FILE,
+            home_manager_module(settings, vimrc.is_some()),
+        ));
+        if let Some(vimrc) = vimrc {
+            files.push((HOME_MANAGER_VIMRC_FILE, vimrc.to_string()));
+        }
+    }
+
+    for (name, contents) in &files {
+        let path = Path::new(out_dir).join(name).to_string_lossy().into_owned();
+        if Path::new(&path).exists() {
+            println!("{} exists. Overwrite? (y/n)", path);
+            if read_input().trim().to_lowercase() != "y" {
+                println!("{} {}.", path, "skipped".green());
+                continue;
+            }
+        }
+        if let Err(e) = write_file(&path, contents) {
+            eprintln!("{} {}", "error:".red(), e);
+            return 1;
+        }
+        println!("{} {}.", path, "written".green());
+    }
+
+    let dir = fs::canonicalize(out_dir)
+        .map(|dir| dir.display().to_string())
+        .unwrap_or_else(|_| out_dir.to_string());
+    println!(
+        "Add {}/{} to the imports of /etc/nixos/configuration.nix and run {}.",
+        dir,
+        NIXOS_MODULE_FILE,
+        "sudo nixos-rebuild switch".green()
+    );
+    if home_manager {
+        println!(
+            "Add {}/{} to the imports of your home-manager configuration.",
+            dir, HOME_MANAGER_MODULE_FILE
+        );
+    }
+    0
+}
+
+// Renders the `let` binding of the Oh My Zsh custom directory holding the plugins that are not
+// part of Oh My Zsh, or nothing if there are none. Oh My Zsh loads `<name>.plugin.zsh`, which
+// sources the script the nixpkgs package installs.
+fn omz_custom(plugins: &[String]) -> String {
+    if plugins.is_empty() {
+        return String::new();
+    }
+    let mut binding = String::from(
+        "let\n  # Zsh plugins that are not part of Oh My Zsh, taken from nixpkgs.\n  \
+         omzCustom = pkgs.runCommand \"glwiz-omz-custom\" { } ''\n",
+    );
+    for plugin in plugins {
+        binding.push_str(&format!(
+            "    mkdir -p $out/plugins/{name}\n    \
+             echo \"source ${{{package}}}/share/{name}/{name}.zsh\" \\\n      \
+             > $out/plugins/{name}/{name}.plugin.zsh\n",
+            name = plugin,
+            package = nix_attribute(plugin)
+        ));
+    }
+    binding.push_str("  '';\nin\n");
+    binding
+}
+
+// Renders the Oh My Zsh options shared by the NixOS and home-manager modules.
+fn omz_options(settings: &NixosSettings, indent: &str) -> String {
+    let mut options = format!("{}enable = true;\n", indent);
+    if let Some(theme) = &settings.omz_theme {
+        options.push_str(&format!("{}theme = {};\n", indent, nix_string(theme)));
+    }
+    let plugins: String = settings
+        .omz_plugins
+        .iter()
+        .map(|plugin| format!("{} ", nix_string(plugin)))
+        .collect();
+    options.push_str(&format!("{}plugins = [ {}];\n", indent, plugins));
+    if !settings.custom_plugins.is_empty() {
+        options.push_str(&format!("{}custom = \"${{omzCustom}}\";\n", indent));
+    }
+    options
+}
+
+// Renders a package as an element of a `with pkgs;` list.
+fn nix_package(name: &str) -> String {
+    if is_attribute_path(name) {
+        name.to_string()
+    } else {
+        nix_attribute(name)
+    }
+}
+
+// Renders a package as a `pkgs` attribute, quoting names that are not attribute paths.
+fn nix_attribute(name: &str) -> String {
+    if is_attribute_path(name) {
+        format!("pkgs.{}", name)
+    } else {
+        format!("pkgs.{}", nix_string(name))
+    }
+}
+
+// Returns `true` if a name is a dot-separated path of Nix identifiers (e.g., `python3Packages.pip`).
+fn is_attribute_path(name: &str) -> bool {
+    name.split('.').all(|part| {
+        part.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_')
+            && part
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | '-' | '\''))
+    })
+}
+
+// Quotes a Nix string, escaping backslashes, quotes and interpolations.
+fn nix_string(value: &str) -> String {
+    format!(
+        "\"{}\"",
+        value
+            .replace('\\', "\\\\")
+            .replace('"', "\\\"")
+            .replace("${", "\\${")
+    )
+}
@@ -0,0 +1,196 @@
+/*
+ * GLWiz - The ultimate post-installation setup assistant for GNU/Linux popular distros,
+ * streamlining your configuration process with ease and precision.
+ * 
+ * Copyright (C) 2025  Andrew Kushyk
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published
+ * by the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+/// The pacman package manager (Arch Linux and derivatives).
+pub mod pacman;
+
+/// The APT package manager (Debian and derivatives).
+pub mod apt;
+
+/// The DNF package manager (Fedora and derivatives).
+pub mod dnf;
+
+/// The zypper package manager (openSUSE and SUSE Linux Enterprise).
+pub mod zypper;
+
+/// The apk package manager (Alpine Linux).
+pub mod apk;
+
+/// The xbps package manager (Void Linux).
+pub mod xbps;
+
+/// The Portage package manager (Gentoo Linux).
+pub mod portage;
+
+use super::{distro::DistroFamily, target::Target};
+
+/// A package found by `PackageManager::search`.
+///
+/// # Fields
+/// * `name` - The package name (e.g., `"zsh"`).
+/// * `version` - The available version, if the tool reports it.
+/// * `description` - The one-line package summary.
+/// * `installed` - Whether the package is installed, if the tool reports it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PackageInfo {
+    pub name: String,
+    pub version: Option<String>,
+    pub description: String,
+    pub installed: bool,
+}
+
+/// A package reported by `PackageManager::list_installed`.
+///
+/// # Fields
+/// * `name` - The package name (e.g., `"zsh"`).
+/// * `version` - The installed version (e.g., `"5.9-5"`).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InstalledPackage {
+    pub name: String,
+    pub version: String,
+}
+
+/// Operations on a distribution’s package manager.
+///
+/// Each implementation runs its tool inside a `target::Target`, so the same calls work on the
+/// running host and on a mounted root. Changes run with root privileges; queries run as the
+/// current user and their output is parsed into typed results, so that tasks can check the
+/// system’s state (e.g., whether `zsh` is installed) before acting.
+///
+/// # Example
+/// ```no_run
+/// // Requires an Arch Linux system.
+/// use glwiz::functionality::{
+///     package_manager::{pacman::Pacman, PackageManager},
+///     target::Target,
+/// };
+/// let pacman = Pacman::new(Target::host());
+/// if !pacman.is_installed("zsh").unwrap() {
+///     pacman.install(&["zsh"]).unwrap();
+/// }
+/// ```
+///
+/// # See Also
+/// - `for_family`: Selects the package manager of a distribution family.
+pub trait PackageManager {
+    /// Returns the name of the underlying tool (e.g., `"pacman"`).
+    fn name(&self) -> &'static str;
+
+    /// Returns the name the package manager knows a package by. Most use the plain names
+    /// glwiz lists; Portage needs `category/name` atoms.
+    fn package_name(&self, package: &str) -> String {
+        package.to_string()
+    }
+
+    /// Refreshes the package index from the configured repositories. pacman upgrades the
+    /// whole system here, since Arch does not support partial upgrades.
+    fn refresh_index(&self) -> Result<(), String>;
+
+    /// Returns `true` if the index should be refreshed before installing packages. pacman
+    /// returns `false`, since refreshing means a full upgrade there, and so does Portage, whose
+    /// mirrors allow one synchronization a day (see `index_is_stale`).
+    fn refreshes_before_install(&self) -> bool {
+        true
+    }
+
+    /// Returns `true` if the local index is too old to install from without refreshing it
+    /// first. Only meaningful where `refreshes_before_install` is `false`: pacman checks the
+    /// age of its sync databases, Portage that of the Gentoo repository.
+    fn index_is_stale(&self) -> bool {
+        false
+    }
+
+    /// Refreshes the package index and upgrades every installed package without prompting.
+    fn upgrade(&self) -> Result<(), String>;
+
+    /// Installs packages without prompting.
+    fn install(&self, packages: &[&str]) -> Result<(), String>;
+
+    /// Removes packages without prompting.
+    fn remove(&self, packages: &[&str]) -> Result<(), String>;
+
+    /// Returns every package that `remove_with_dependents` would remove for `packages`: the
+    /// packages themselves and the installed packages depending on them. Tools that refuse to
+    /// remove a package others depend on only return `packages`.
+    fn removal_set(&self, packages: &[&str]) -> Result<Vec<String>, String> {
+        Ok(packages.iter().map(|package| package.to_string()).collect())
+    }
+
+    /// Removes packages and the installed packages depending on them without prompting.
+    fn remove_with_dependents(&self, packages: &[&str]) -> Result<(), String> {
+        self.remove(packages)
+    }
+
+    /// Returns the packages that `remove_orphans` would remove. Tools whose removals already
+    /// take these dependencies along (zypper, apk) return none.
+    fn orphans(&self) -> Result<Vec<String>, String> {
+        Ok(Vec::new())
+    }
+
+    /// Removes packages that were installed as dependencies and are no longer needed.
+    fn remove_orphans(&self) -> Result<(), String>;
+
+    /// Returns `true` if a package is installed.
+    fn is_installed(&self, package: &str) -> Result<bool, String>;
+
+    /// Returns `true` if a package can be installed from the configured repositories.
+    fn is_available(&self, package: &str) -> Result<bool, String>;
+
+    /// Searches the repositories for packages matching a query.
+    fn search(&self, query: &str) -> Result<Vec<PackageInfo>, String>;
+
+    /// Lists the installed packages with their versions.
+    fn list_installed(&self) -> Result<Vec<InstalledPackage>, String>;
+}
+
+/// Returns the package manager of a distribution family, running inside `target`.
+///
+/// # Arguments
+/// * `family` - The distribution family.
+/// * `target` - The system the package manager operates on.
+///
+/// # Returns
+/// A boxed `PackageManager` for the family.
+///
+/// # Example
+/// ```
+/// use glwiz::functionality::{distro::DistroFamily, package_manager::for_family, target::Target};
+/// let manager = for_family(DistroFamily::Fedora, &Target::host());
+/// assert_eq!(manager.name(), "dnf");
+/// ```
+pub fn for_family(family: DistroFamily, target: &Target) -> Box<dyn PackageManager> {
+    let target = target.clone();
+    match family {
+        DistroFamily::Arch => Box::new(pacman::Pacman::new(target)),
+        DistroFamily::Debian => Box::new(apt::Apt::new(target)),
+        DistroFamily::Fedora => Box::new(dnf::Dnf::new(target)),
+        DistroFamily::Suse => Box::new(zypper::Zypper::new(target)),
+        DistroFamily::Alpine => Box::new(apk::Apk::new(target)),
+        DistroFamily::Void => Box::new(xbps::Xbps::new(target)),
+        DistroFamily::Gentoo => Box::new(portage::Portage::new(target)),
+    }
+}
+
+// Builds an argument list from fixed arguments followed by package names.
+fn with_packages<'a>(args: &[&'a str], packages: &[&'a str]) -> Vec<&'a str> {
+    let mut full = args.to_vec();
+    full.extend_from_slice(packages);
+    full
+}
@@ -0,0 +1,151 @@
+/*
+ * GLWiz - The ultimate post-installation setup assistant for GNU/Linux popular distros,
+ * streamlining your configuration process with ease and precision.
+ * 
+ * Copyright (C) 2025  Andrew Kushyk
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published
+ * by the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use super::{InstalledPackage, PackageInfo, PackageManager, with_packages};
+use crate::functionality::target::Target;
+
+/// The apk executable.
+pub const COMMAND: &str = "apk";
+
+/// The arguments that refresh the repository indexes.
+pub const REFRESH_ARGS: [&str; 1] = ["update"];
+
+/// The arguments that install packages without prompting.
+pub const INSTALL_ARGS: [&str; 2] = ["--no-interactive", "add"];
+
+/// The apk package manager, operating on a target system.
+#[derive(Debug, Clone)]
+pub struct Apk {
+    target: Target,
+}
+
+impl Apk {
+    /// Creates an apk instance operating on `target`.
+    pub fn new(target: Target) -> Self {
+        Self { target }
+    }
+}
+
+impl PackageManager for Apk {
+    fn name(&self) -> &'static str {
+        COMMAND
+    }
+
+    fn refresh_index(&self) -> Result<(), String> {
+        self.target.run_command(COMMAND, &REFRESH_ARGS)
+    }
+
+    fn upgrade(&self) -> Result<(), String> {
+        self.target
+            .stream_command(COMMAND, &["--no-interactive", "--update-cache", "upgrade"])
+    }
+
+    fn install(&self, packages: &[&str]) -> Result<(), String> {
+        self.target
+            .run_command(COMMAND, &with_packages(&INSTALL_ARGS, packages))
+    }
+
+    fn remove(&self, packages: &[&str]) -> Result<(), String> {
+        self.target.run_command(
+            COMMAND,
+            &with_packages(&["--no-interactive", "del"], packages),
+        )
+    }
+
+    // `apk del` drops the dependencies no longer needed by the world file itself.
+    fn remove_orphans(&self) -> Result<(), String> {
+        Ok(())
+    }
+
+    fn is_installed(&self, package: &str) -> Result<bool, String> {
+        Ok(self
+            .target
+            .capture_command(COMMAND, &["info", "-e", package])
+            .is_ok())
+    }
+
+    fn is_available(&self, package: &str) -> Result<bool, String> {
+        self.target
+            .capture_command(COMMAND, &["search", "--exact", package])
+            .map(|output| !output.trim().is_empty())
+    }
+
+    fn search(&self, query: &str) -> Result<Vec<PackageInfo>, String> {
+        self.target
+            .capture_command(COMMAND, &["search", "-v", query])
+            .map(|output| parse_search(&output))
+    }
+
+    fn list_installed(&self) -> Result<Vec<InstalledPackage>, String> {
+        self.target
+            .capture_command(COMMAND, &["info", "-v"])
+            .map(|output| output.lines().filter_map(split_version).collect())
+    }
+}
+
+/// Splits an apk package identifier (`name-version-rN`) into its name and version.
+///
+/// # Example
+/// ```
+/// use glwiz::functionality::package_manager::apk::split_version;
+/// let package = split_version("py3-pip-24.0-r2").unwrap();
+/// assert_eq!(package.name, "py3-pip");
+/// assert_eq!(package.version, "24.0-r2");
+/// assert!(split_version("zsh").is_none());
+/// ```
+pub fn split_version(identifier: &str) -> Option<InstalledPackage> {
+    let identifier = identifier.trim();
+    let mut parts = identifier.rsplitn(3, '-');
+    let release = parts.next()?;
+    let version = parts.next()?;
+    let name = parts.next()?;
+    (release.starts_with('r') && version.starts_with(|c: char| c.is_ascii_digit())).then(|| {
+        InstalledPackage {
+            name: name.to_string(),
+            version: format!("{}-{}", version, release),
+        }
+    })
+}
+
+/// Parses the output of `apk search -v` (`name-version-rN - description` per line).
+///
+/// # Example
+/// ```
+/// use glwiz::functionality::package_manager::apk::parse_search;
+/// let results = parse_search("zsh-5.9-r4 - Very advanced and programmable command interpreter (shell)\n");
+/// assert_eq!(results[0].name, "zsh");
+/// assert_eq!(results[0].version.as_deref(), Some("5.9-r4"));
+/// assert_eq!(results[0].description, "Very advanced and programmable command interpreter (shell)");
+/// ```
+pub fn parse_search(output: &str) -> Vec<PackageInfo> {
+    output
+        .lines()
+        .filter_map(|line| {
+            let (identifier, description) = line.split_once(" - ")?;
+            let package = split_version(identifier)?;
+            Some(PackageInfo {
+                name: package.name,
+                version: Some(package.version),
+                description: description.trim().to_string(),
+                installed: false,
+            })
+        })
+        .collect()
+}
@@ -0,0 +1,189 @@
+/*
+ * GLWiz - The ultimate post-installation setup assistant for GNU/Linux popular distros,
+ * streamlining your configuration process with ease and precision.
+ * 
+ * Copyright (C) 2025  Andrew Kushyk
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published
+ * by the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use super::{InstalledPackage, PackageInfo, PackageManager, with_packages};
+use crate::functionality::target::Target;
+
+/// The APT executable.
+pub const COMMAND: &str = "apt";
+
+/// The arguments that refresh the package lists.
+pub const REFRESH_ARGS: [&str; 1] = ["update"];
+
+/// The arguments that install packages without prompting.
+pub const INSTALL_ARGS: [&str; 2] = ["install", "-y"];
+
+// The `dpkg-query` format listing name, version and abbreviated status.
+const LIST_FORMAT: &str = "${Package}\\t${Version}\\t${db:Status-Abbrev}\\n";
+
+/// The APT package manager, operating on a target system.
+#[derive(Debug, Clone)]
+pub struct Apt {
+    target: Target,
+}
+
+impl Apt {
+    /// Creates an APT instance operating on `target`.
+    pub fn new(target: Target) -> Self {
+        Self { target }
+    }
+}
+
+impl PackageManager for Apt {
+    fn name(&self) -> &'static str {
+        COMMAND
+    }
+
+    fn refresh_index(&self) -> Result<(), String> {
+        self.target.run_command(COMMAND, &REFRESH_ARGS)
+    }
+
+    fn upgrade(&self) -> Result<(), String> {
+        self.refresh_index()?;
+        self.target.stream_command(COMMAND, &["full-upgrade", "-y"])
+    }
+
+    fn install(&self, packages: &[&str]) -> Result<(), String> {
+        self.target
+            .run_command(COMMAND, &with_packages(&INSTALL_ARGS, packages))
+    }
+
+    fn remove(&self, packages: &[&str]) -> Result<(), String> {
+        self.target
+            .run_command(COMMAND, &with_packages(&["remove", "-y"], packages))
+    }
+
+    fn removal_set(&self, packages: &[&str]) -> Result<Vec<String>, String> {
+        self.target
+            .capture_command("apt-get", &with_packages(&["-s", "remove"], packages))
+            .map(|output| parse_simulated_removal(&output))
+    }
+
+    fn orphans(&self) -> Result<Vec<String>, String> {
+        self.target
+            .capture_command("apt-get", &["-s", "autoremove"])
+            .map(|output| parse_simulated_removal(&output))
+    }
+
+    fn remove_orphans(&self) -> Result<(), String> {
+        self.target.run_command(COMMAND, &["autoremove", "-y"])
+    }
+
+    fn is_installed(&self, package: &str) -> Result<bool, String> {
+        Ok(self
+            .target
+            .capture_command("dpkg-query", &["-W", "-f=${Status}", package])
+            .is_ok_and(|status| status.ends_with("installed") && !status.contains("not-installed")))
+    }
+
+    // `apt-cache show` exits with a failure status for unknown and purely virtual packages.
+    fn is_available(&self, package: &str) -> Result<bool, String> {
+        Ok(self
+            .target
+            .capture_command("apt-cache", &["show", package])
+            .is_ok())
+    }
+
+    fn search(&self, query: &str) -> Result<Vec<PackageInfo>, String> {
+        self.target
+            .capture_command("apt-cache", &["search", query])
+            .map(|output| parse_search(&output))
+    }
+
+    fn list_installed(&self) -> Result<Vec<InstalledPackage>, String> {
+        let format = format!("-f={}", LIST_FORMAT);
+        self.target
+            .capture_command("dpkg-query", &["-W", &format])
+            .map(|output| parse_dpkg_list(&output))
+    }
+}
+
+/// Parses `dpkg-query -W` output listing name, version and abbreviated status, tab-separated.
+///
+/// Only fully installed packages (status `ii`) are returned.
+///
+/// # Example
+/// ```
+/// use glwiz::functionality::package_manager::apt::parse_dpkg_list;
+/// let output = "zsh\t5.9-4+b1\tii \nvim\t2:9.0.1378-2\trc \n";
+/// let packages = parse_dpkg_list(output);
+/// assert_eq!(packages.len(), 1);
+/// assert_eq!(packages[0].name, "zsh");
+/// assert_eq!(packages[0].version, "5.9-4+b1");
+/// ```
+pub fn parse_dpkg_list(output: &str) -> Vec<InstalledPackage> {
+    output
+        .lines()
+        .filter_map(|line| {
+            let mut fields = line.split('\t');
+            let name = fields.next()?;
+            let version = fields.next()?;
+            let status = fields.next()?;
+            status.starts_with("ii").then(|| InstalledPackage {
+                name: name.to_string(),
+                version: version.to_string(),
+            })
+        })
+        .collect()
+}
+
+/// Parses the output of `apt-cache search` (`name - description` per line).
+///
+/// # Example
+/// ```
+/// use glwiz::functionality::package_manager::apt::parse_search;
+/// let results = parse_search("zsh - shell with lots of features\nzsh-doc - zsh documentation\n");
+/// assert_eq!(results[0].name, "zsh");
+/// assert_eq!(results[0].description, "shell with lots of features");
+/// assert_eq!(results[0].version, None);
+/// ```
+pub fn parse_search(output: &str) -> Vec<PackageInfo> {
+    output
+        .lines()
+        .filter_map(|line| line.split_once(" - "))
+        .map(|(name, description)| PackageInfo {
+            name: name.trim().to_string(),
+            version: None,
+            description: description.trim().to_string(),
+            installed: false,
+        })
+        .collect()
+}
+
+/// Parses the output of `apt-get -s remove` (or `autoremove`), returning the names of the
+/// removed packages.
+///
+/// Each removal is simulated on a `Remv name [version]` line.
+///
+/// # Example
+/// ```
+/// use glwiz::functionality::package_manager::apt::parse_simulated_removal;
+/// let output = "The following packages will be REMOVED:\n  aisleriot gnome-games\n\
+///               Remv gnome-games [1:43+1]\nRemv aisleriot [1:3.22.23-1]\n";
+/// assert_eq!(parse_simulated_removal(output), vec!["gnome-games", "aisleriot"]);
+/// ```
+pub fn parse_simulated_removal(output: &str) -> Vec<String> {
+    output
+        .lines()
+        .filter_map(|line| line.strip_prefix("Remv "))
+        .filter_map(|line| line.split_whitespace().next())
+        .map(String::from)
+        .collect()
+}
@@ -0,0 +1,225 @@
+/*
+ * GLWiz - The ultimate post-installation setup assistant for GNU/Linux popular distros,
+ * streamlining your configuration process with ease and precision.
+ * 
+ * Copyright (C) 2025  Andrew Kushyk
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published
+ * by the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use super::{InstalledPackage, PackageInfo, PackageManager, with_packages};
+use crate::functionality::target::Target;
+
+/// The DNF executable.
+pub const COMMAND: &str = "dnf";
+
+/// The arguments that refresh the repository metadata.
+pub const REFRESH_ARGS: [&str; 1] = ["makecache"];
+
+/// The arguments that install packages without prompting.
+pub const INSTALL_ARGS: [&str; 2] = ["install", "-y"];
+
+// The `rpm -qa` format listing name and version-release, tab-separated.
+const LIST_FORMAT: &str = "%{NAME}\\t%{VERSION}-%{RELEASE}\\n";
+
+/// The DNF package manager, operating on a target system.
+#[derive(Debug, Clone)]
+pub struct Dnf {
+    target: Target,
+}
+
+impl Dnf {
+    /// Creates a DNF instance operating on `target`.
+    pub fn new(target: Target) -> Self {
+        Self { target }
+    }
+}
+
+impl PackageManager for Dnf {
+    fn name(&self) -> &'static str {
+        COMMAND
+    }
+
+    fn refresh_index(&self) -> Result<(), String> {
+        self.target.run_command(COMMAND, &REFRESH_ARGS)
+    }
+
+    fn upgrade(&self) -> Result<(), String> {
+        self.target
+            .stream_command(COMMAND, &["upgrade", "-y", "--refresh"])
+    }
+
+    fn install(&self, packages: &[&str]) -> Result<(), String> {
+        self.target
+            .run_command(COMMAND, &with_packages(&INSTALL_ARGS, packages))
+    }
+
+    fn remove(&self, packages: &[&str]) -> Result<(), String> {
+        self.target
+            .run_command(COMMAND, &with_packages(&["remove", "-y"], packages))
+    }
+
+    // dnf removes the packages depending on a removed package itself, including those that
+    // require a library or file it provides, so the declined transaction is the only reliable
+    // list.
+    fn removal_set(&self, packages: &[&str]) -> Result<Vec<String>, String> {
+        self.target
+            .preview_command(COMMAND, &with_packages(&["remove", "--assumeno"], packages))
+            .map(|output| parse_transaction(&output))
+    }
+
+    fn orphans(&self) -> Result<Vec<String>, String> {
+        self.target
+            .preview_command(COMMAND, &["autoremove", "--assumeno"])
+            .map(|output| parse_transaction(&output))
+    }
+
+    fn remove_orphans(&self) -> Result<(), String> {
+        self.target.run_command(COMMAND, &["autoremove", "-y"])
+    }
+
+    fn is_installed(&self, package: &str) -> Result<bool, String> {
+        Ok(self.target.capture_command("rpm", &["-q", package]).is_ok())
+    }
+
+    // Capabilities such as `python3dist(requests)` are installable too, so providers count.
+    fn is_available(&self, package: &str) -> Result<bool, String> {
+        self.target
+            .capture_command(COMMAND, &["repoquery", "-q", "--whatprovides", package])
+            .map(|output| !output.trim().is_empty())
+    }
+
+    fn search(&self, query: &str) -> Result<Vec<PackageInfo>, String> {
+        self.target
+            .capture_command(COMMAND, &["search", "-q", query])
+            .map(|output| parse_search(&output))
+    }
+
+    fn list_installed(&self) -> Result<Vec<InstalledPackage>, String> {
+        self.target
+            .capture_command("rpm", &["-qa", "--qf", LIST_FORMAT])
+            .map(|output| parse_rpm_list(&output))
+    }
+}
+
+/// Parses the transaction table printed by `dnf remove` or `dnf autoremove`, returning the names
+/// of the removed packages.
+///
+/// The packages are listed under `Removing:` headings (e.g., `Removing dependent packages:`)
+/// up to the next heading or the transaction summary. Names too long for their column are
+/// printed on a line of their own, followed by the rest of the row.
+///
+/// # Example
+/// ```
+/// use glwiz::functionality::package_manager::dnf::parse_transaction;
+/// let output = " Package        Arch    Version      Repository  Size\n\
+///               Removing:\n gnome-mines    x86_64  40.1-3.fc40  @fedora     1.2 M\n\
+///               Removing dependent packages:\n gnome-games-extra-long-name\n\
+///                               noarch  1-1.fc40     @fedora     10 k\n\
+///               Removing unused dependencies:\n libgnome-games-support x86_64 1.8-1.fc40 @fedora 99 k\n\n\
+///               Transaction Summary\n Remove  3 Packages\n\nOperation aborted.\n";
+/// assert_eq!(
+///     parse_transaction(output),
+///     vec!["gnome-mines", "gnome-games-extra-long-name", "libgnome-games-support"]
+/// );
+/// ```
+pub fn parse_transaction(output: &str) -> Vec<String> {
+    let mut removed: Vec<String> = Vec::new();
+    let mut in_section = false;
+    let mut wrapped = false;
+    for line in output.lines() {
+        if !line.starts_with(char::is_whitespace) {
+            in_section = line.starts_with("Removing") && line.trim_end().ends_with(':');
+            wrapped = false;
+            continue;
+        }
+        let fields: Vec<&str> = line.split_whitespace().collect();
+        if !in_section || fields.is_empty() {
+            continue;
+        }
+        if wrapped {
+            wrapped = false;
+            continue;
+        }
+        wrapped = fields.len() == 1;
+        if !removed.iter().any(|name| name == fields[0]) {
+            removed.push(fields[0].to_string());
+        }
+    }
+    removed
+}
+
+/// Parses `rpm -qa` output listing name and version-release, tab-separated.
+///
+/// # Example
+/// ```
+/// use glwiz::functionality::package_manager::dnf::parse_rpm_list;
+/// let packages = parse_rpm_list("zsh\t5.9-14.fc40\nvim-enhanced\t9.1.785-1.fc40\n");
+/// assert_eq!(packages[1].name, "vim-enhanced");
+/// assert_eq!(packages[1].version, "9.1.785-1.fc40");
+/// ```
+pub fn parse_rpm_list(output: &str) -> Vec<InstalledPackage> {
+    output
+        .lines()
+        .filter_map(|line| line.split_once('\t'))
+        .map(|(name, version)| InstalledPackage {
+            name: name.to_string(),
+            version: version.trim().to_string(),
+        })
+        .collect()
+}
+
+/// Parses the output of `dnf search`, as printed by both DNF 4 and DNF 5.
+///
+/// DNF 4 prints `name.arch : summary` under `=== ... Matched ===` headings; DNF 5 prints
+/// indented `name.arch<TAB>summary` lines under `Matched fields:` headings. The architecture
+/// suffix is removed and duplicate names are dropped.
+///
+/// # Example
+/// ```
+/// use glwiz::functionality::package_manager::dnf::parse_search;
+/// let dnf4 = "====== Name Exactly Matched: zsh ======\nzsh.x86_64 : Powerful interactive shell\n";
+/// let dnf5 = "Matched fields: name (exact)\n zsh.x86_64\tPowerful interactive shell\n";
+/// assert_eq!(parse_search(dnf4), parse_search(dnf5));
+/// assert_eq!(parse_search(dnf4)[0].name, "zsh");
+/// assert_eq!(parse_search(dnf4)[0].description, "Powerful interactive shell");
+/// ```
+pub fn parse_search(output: &str) -> Vec<PackageInfo> {
+    let mut results: Vec<PackageInfo> = Vec::new();
+    for line in output.lines() {
+        let line = line.trim();
+        if line.starts_with('=') || line.starts_with("Matched fields") {
+            continue;
+        }
+        let Some((name_arch, description)) =
+            line.split_once(" : ").or_else(|| line.split_once('\t'))
+        else {
+            continue;
+        };
+        let name_arch = name_arch.trim();
+        let name = name_arch
+            .rsplit_once('.')
+            .map_or(name_arch, |(name, _)| name);
+        if results.iter().any(|r| r.name == name) {
+            continue;
+        }
+        results.push(PackageInfo {
+            name: name.to_string(),
+            version: None,
+            description: description.trim().to_string(),
+            installed: false,
+        });
+    }
+    results
+}
@@ -0,0 +1,241 @@
+/*
+ * GLWiz - The ultimate post-installation setup assistant for GNU/Linux popular distros,
+ * streamlining your configuration process with ease and precision.
+ * 
+ * Copyright (C) 2025  Andrew Kushyk
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published
+ * by the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use super::{InstalledPackage, PackageInfo, PackageManager, with_packages};
+use crate::functionality::target::Target;
+use std::{
+    fs,
+    time::{Duration, SystemTime},
+};
+
+/// The pacman executable.
+pub const COMMAND: &str = "pacman";
+
+/// The arguments that install packages without prompting. They do not synchronize the
+/// databases: Arch does not support partial upgrades, so syncing happens only with `-Syu`.
+pub const INSTALL_ARGS: [&str; 3] = ["-S", "--needed", "--noconfirm"];
+
+/// The arguments that synchronize the databases and upgrade the system without prompting.
+pub const UPGRADE_ARGS: [&str; 2] = ["-Syu", "--noconfirm"];
+
+/// The directory holding the synchronized package databases.
+pub const SYNC_DIR: &str = "/var/lib/pacman/sync";
+
+/// How old the synchronized databases may be before the system is upgraded to install.
+pub const SYNC_MAX_AGE: Duration = Duration::from_secs(24 * 60 * 60);
+
+/// The arguments that print the names of the packages a cascading removal would remove.
+pub const REMOVE_PRINT_ARGS: [&str; 3] = ["-Rcp", "--print-format", "%n"];
+
+/// The pacman package manager, operating on a target system.
+#[derive(Debug, Clone)]
+pub struct Pacman {
+    target: Target,
+}
+
+impl Pacman {
+    /// Creates a pacman instance operating on `target`.
+    pub fn new(target: Target) -> Self {
+        Self { target }
+    }
+}
+
+impl PackageManager for Pacman {
+    fn name(&self) -> &'static str {
+        COMMAND
+    }
+
+    // Syncing without upgrading (`-Sy`) leaves a partial upgrade behind, so the databases are
+    // only ever synchronized together with a full upgrade.
+    fn refresh_index(&self) -> Result<(), String> {
+        self.upgrade()
+    }
+
+    fn refreshes_before_install(&self) -> bool {
+        false
+    }
+
+    fn index_is_stale(&self) -> bool {
+        sync_is_stale(&self.target.path(SYNC_DIR), SystemTime::now())
+    }
+
+    fn upgrade(&self) -> Result<(), String> {
+        self.target.stream_command(COMMAND, &UPGRADE_ARGS)
+    }
+
+    fn install(&self, packages: &[&str]) -> Result<(), String> {
+        self.target
+            .run_command(COMMAND, &with_packages(&INSTALL_ARGS, packages))
+    }
+
+    fn remove(&self, packages: &[&str]) -> Result<(), String> {
+        self.target
+            .run_command(COMMAND, &with_packages(&["-Rns", "--noconfirm"], packages))
+    }
+
+    fn removal_set(&self, packages: &[&str]) -> Result<Vec<String>, String> {
+        self.target
+            .capture_command(COMMAND, &with_packages(&REMOVE_PRINT_ARGS, packages))
+            .map(|output| output.lines().map(String::from).collect())
+    }
+
+    fn remove_with_dependents(&self, packages: &[&str]) -> Result<(), String> {
+        self.target
+            .run_command(COMMAND, &with_packages(&["-Rcns", "--noconfirm"], packages))
+    }
+
+    // `pacman -Qdtq` exits with a failure status when there are no orphans.
+    fn orphans(&self) -> Result<Vec<String>, String> {
+        Ok(self
+            .target
+            .capture_command(COMMAND, &["-Qdtq"])
+            .map(|output| output.lines().map(String::from).collect())
+            .unwrap_or_default())
+    }
+
+    fn remove_orphans(&self) -> Result<(), String> {
+        let orphans = self.orphans()?;
+        if orphans.is_empty() {
+            return Ok(());
+        }
+        let orphans: Vec<&str> = orphans.iter().map(String::as_str).collect();
+        self.remove(&orphans)
+    }
+
+    fn is_installed(&self, package: &str) -> Result<bool, String> {
+        Ok(self
+            .target
+            .capture_command(COMMAND, &["-Q", package])
+            .is_ok())
+    }
+
+    // Groups such as `gnome` are installable too, but only known to `pacman -Sg`.
+    fn is_available(&self, package: &str) -> Result<bool, String> {
+        Ok(self
+            .target
+            .capture_command(COMMAND, &["-Si", package])
+            .is_ok()
+            || self
+                .target
+                .capture_command(COMMAND, &["-Sg", package])
+                .is_ok())
+    }
+
+    // `pacman -Ss` exits with a failure status when nothing matches.
+    fn search(&self, query: &str) -> Result<Vec<PackageInfo>, String> {
+        Ok(self
+            .target
+            .capture_command(COMMAND, &["-Ss", query])
+            .map(|output| parse_search(&output))
+            .unwrap_or_default())
+    }
+
+    fn list_installed(&self) -> Result<Vec<InstalledPackage>, String> {
+        self.target
+            .capture_command(COMMAND, &["-Q"])
+            .map(|output| parse_query(&output))
+    }
+}
+
+/// Returns `true` if the databases in a sync directory are missing or one of them is older than
+/// `SYNC_MAX_AGE` at `now`.
+///
+/// # Example
+/// ```
+/// use glwiz::functionality::package_manager::pacman::sync_is_stale;
+/// assert!(sync_is_stale("/nonexistent/sync", std::time::SystemTime::now()));
+/// ```
+pub fn sync_is_stale(sync_dir: &str, now: SystemTime) -> bool {
+    let Ok(entries) = fs::read_dir(sync_dir) else {
+        return true;
+    };
+    let modified: Vec<SystemTime> = entries
+        .filter_map(Result::ok)
+        .filter(|entry| entry.path().extension().is_some_and(|ext| ext == "db"))
+        .filter_map(|entry| entry.metadata().and_then(|meta| meta.modified()).ok())
+        .collect();
+    modified.is_empty()
+        || modified.iter().any(|time| {
+            now.duration_since(*time)
+                .is_ok_and(|age| age > SYNC_MAX_AGE)
+        })
+}
+
+/// Parses the output of `pacman -Q` (`name version` per line).
+///
+/// # Example
+/// ```
+/// use glwiz::functionality::package_manager::pacman::parse_query;
+/// let packages = parse_query("zsh 5.9-5\nvim 9.1.0785-1\n");
+/// assert_eq!(packages[1].name, "vim");
+/// assert_eq!(packages[1].version, "9.1.0785-1");
+/// ```
+pub fn parse_query(output: &str) -> Vec<InstalledPackage> {
+    output
+        .lines()
+        .filter_map(|line| line.split_once(' '))
+        .map(|(name, version)| InstalledPackage {
+            name: name.to_string(),
+            version: version.trim().to_string(),
+        })
+        .collect()
+}
+
+/// Parses the output of `pacman -Ss`.
+///
+/// Each result is a `repo/name version [groups] [installed]` line followed by an indented
+/// description line.
+///
+/// # Example
+/// ```
+/// use glwiz::functionality::package_manager::pacman::parse_search;
+/// let output = "extra/zsh 5.9-5 [installed]\n    A very advanced and programmable command interpreter\n\
+///               extra/zsh-completions 0.35.0-1\n    Additional completion definitions for Zsh\n";
+/// let results = parse_search(output);
+/// assert_eq!(results.len(), 2);
+/// assert_eq!(results[0].name, "zsh");
+/// assert_eq!(results[0].version.as_deref(), Some("5.9-5"));
+/// assert!(results[0].installed);
+/// assert!(!results[1].installed);
+/// assert_eq!(results[1].description, "Additional completion definitions for Zsh");
+/// ```
+pub fn parse_search(output: &str) -> Vec<PackageInfo> {
+    let mut results: Vec<PackageInfo> = Vec::new();
+    for line in output.lines() {
+        if line.starts_with(char::is_whitespace) {
+            if let Some(last) = results.last_mut() {
+                last.description = line.trim().to_string();
+            }
+            continue;
+        }
+        let mut fields = line.split_whitespace();
+        let Some(name) = fields.next() else {
+            continue;
+        };
+        let name = name.rsplit('/').next().unwrap_or(name);
+        results.push(PackageInfo {
+            name: name.to_string(),
+            version: fields.next().map(String::from),
+            description: String::new(),
+            installed: line.contains("[installed"),
+        });
+    }
+    results
+}
@@ -0,0 +1,419 @@
+/*
+ * GLWiz - The ultimate post-installation setup assistant for GNU/Linux popular distros,
+ * streamlining your configuration process with ease and precision.
+ * 
+ * Copyright (C) 2025  Andrew Kushyk
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published
+ * by the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use super::{InstalledPackage, PackageInfo, PackageManager, with_packages};
+use crate::functionality::{
+    atomic::write_file_as_root, distro::DistroFamily, package_map::PackageMap, target::Target,
+};
+use std::{
+    fs,
+    path::Path,
+    time::{Duration, SystemTime},
+};
+
+/// The Portage installation executable.
+pub const COMMAND: &str = "emerge";
+
+/// The arguments that install packages without prompting. `--keep-going` lets one failed
+/// build not stop the packages that do not depend on it.
+pub const INSTALL_ARGS: [&str; 2] = ["--ask=n", "--keep-going"];
+
+/// The arguments that synchronize the ebuild repositories.
+pub const REFRESH_ARGS: [&str; 1] = ["--sync"];
+
+/// The file `emerge --sync` updates once the Gentoo repository is synchronized.
+pub const SYNC_TIMESTAMP_PATH: &str = "/var/db/repos/gentoo/metadata/timestamp.chk";
+
+/// How old the Gentoo repository may be before it is synchronized to install. Gentoo mirrors
+/// ban clients that synchronize more than once a day.
+pub const SYNC_MAX_AGE: Duration = Duration::from_secs(24 * 60 * 60);
+
+/// The directory holding the ebuild repositories, one per subdirectory.
+pub const REPOS_PATH: &str = "/var/db/repos";
+
+/// The package database listing one `category/name-version` directory per installed package.
+pub const VDB_PATH: &str = "/var/db/pkg";
+
+/// The Portage USE flag configuration, either a file or a directory of files.
+pub const PACKAGE_USE_PATH: &str = "/etc/portage/package.use";
+
+/// The file glwiz writes its USE flags to when `package.use` is a directory.
+pub const PACKAGE_USE_FILE: &str = "/etc/portage/package.use/glwiz";
+
+/// USE flags the setup relies on, so that they hold whatever the profile and `make.conf`
+/// disable: git clones Oh My Zsh and its plugins over HTTPS, and the installer is downloaded
+/// with curl.
+pub const PACKAGE_USE: [(&str, &str); 2] = [("dev-vcs/git", "curl"), ("net-misc/curl", "ssl")];
+
+/// The Portage package manager, operating on a target system.
+///
+/// Packages are given either as `category/name` atoms or by names that the package name table
+/// (see `package_map::PackageMap`) maps to an atom.
+/// Installs stream emerge’s output, since building from source can take hours, and they can be
+/// resumed: packages merged before an interruption or failure are installed and skipped when
+/// the setup runs again.
+#[derive(Debug, Clone)]
+pub struct Portage {
+    target: Target,
+    map: PackageMap,
+}
+
+impl Portage {
+    /// Creates a Portage instance operating on `target`, resolving atoms through the package
+    /// name table (see `PackageMap::load`).
+    pub fn new(target: Target) -> Self {
+        Self {
+            target,
+            map: PackageMap::load(),
+        }
+    }
+
+    // Adds the `PACKAGE_USE` entries for the given atoms that are not configured yet.
+    fn write_package_use(&self, atoms: &[&str]) -> Result<(), String> {
+        let use_path = self.target.path(PACKAGE_USE_PATH);
+        let path = if Path::new(&use_path).is_dir() {
+            self.target.path(PACKAGE_USE_FILE)
+        } else {
+            use_path
+        };
+        let existing = fs::read_to_string(&path).unwrap_or_default();
+        match package_use_entries(atoms, &existing) {
+            Some(contents) => write_file_as_root(&path, &contents),
+            None => Ok(()),
+        }
+    }
+}
+
+impl PackageManager for Portage {
+    fn name(&self) -> &'static str {
+        "portage"
+    }
+
+    fn package_name(&self, package: &str) -> String {
+        atom(package, &self.map)
+    }
+
+    fn refresh_index(&self) -> Result<(), String> {
+        self.target.stream_command(COMMAND, &REFRESH_ARGS)
+    }
+
+    // Synchronizing on every run (including `--resume`) would get the host banned by the
+    // mirrors, so the repository is only synchronized once it is stale.
+    fn refreshes_before_install(&self) -> bool {
+        false
+    }
+
+    fn index_is_stale(&self) -> bool {
+        timestamp_is_stale(&self.target.path(SYNC_TIMESTAMP_PATH), SystemTime::now())
+    }
+
+    fn upgrade(&self) -> Result<(), String> {
+        self.refresh_index()?;
+        self.target.stream_command(
+            COMMAND,
+            &["--ask=n", "--update", "--deep", "--newuse", "@world"],
+        )
+    }
+
+    fn install(&self, packages: &[&str]) -> Result<(), String> {
+        let atoms: Vec<String> = packages.iter().map(|p| atom(p, &self.map)).collect();
+        let atoms: Vec<&str> = atoms.iter().map(String::as_str).collect();
+        self.write_package_use(&atoms)?;
+        self.target
+            .stream_command(COMMAND, &with_packages(&INSTALL_ARGS, &atoms))
+    }
+
+    // `--depclean` keeps the packages in @world, so they are deselected first.
+    fn remove(&self, packages: &[&str]) -> Result<(), String> {
+        let atoms: Vec<String> = packages.iter().map(|p| atom(p, &self.map)).collect();
+        let atoms: Vec<&str> = atoms.iter().map(String::as_str).collect();
+        self.target
+            .run_command(COMMAND, &with_packages(&["--ask=n", "--deselect"], &atoms))?;
+        self.target
+            .stream_command(COMMAND, &with_packages(&["--ask=n", "--depclean"], &atoms))
+    }
+
+    fn orphans(&self) -> Result<Vec<String>, String> {
+        self.target
+            .preview_command(COMMAND, &["--pretend", "--depclean"])
+            .map(|output| parse_depclean(&output))
+    }
+
+    fn remove_orphans(&self) -> Result<(), String> {
+        self.target
+            .stream_command(COMMAND, &["--ask=n", "--depclean"])
+    }
+
+    fn is_installed(&self, package: &str) -> Result<bool, String> {
+        let atom = atom(package, &self.map);
+        Ok(self
+            .list_installed()?
+            .iter()
+            .any(|installed| installed.name == atom))
+    }
+
+    // Looks the atom up in the ebuild repositories, which is much faster than `emerge --search`.
+    fn is_available(&self, package: &str) -> Result<bool, String> {
+        let repos = fs::read_dir(self.target.path(REPOS_PATH))
+            .map_err(|e| format!("Failed to read {}: {}", REPOS_PATH, e))?;
+        let atom = atom(package, &self.map);
+        let atom = atom.as_str();
+        Ok(repos.filter_map(|repo| repo.ok()).any(|repo| {
+            let repo = repo.path();
+            match atom.split_once('/') {
+                Some(_) => repo.join(atom).is_dir(),
+                None => fs::read_dir(&repo).is_ok_and(|categories| {
+                    categories
+                        .filter_map(|category| category.ok())
+                        .any(|category| category.path().join(atom).is_dir())
+                }),
+            }
+        }))
+    }
+
+    fn search(&self, query: &str) -> Result<Vec<PackageInfo>, String> {
+        self.target
+            .capture_command(COMMAND, &["--search", query])
+            .map(|output| parse_search(&output))
+    }
+
+    fn list_installed(&self) -> Result<Vec<InstalledPackage>, String> {
+        let vdb = self.target.path(VDB_PATH);
+        let categories =
+            fs::read_dir(&vdb).map_err(|e| format!("Failed to read {}: {}", vdb, e))?;
+        let mut packages = Vec::new();
+        for category in categories.flatten() {
+            let category_name = category.file_name().to_string_lossy().into_owned();
+            let Ok(entries) = fs::read_dir(category.path()) else {
+                continue;
+            };
+            packages.extend(entries.flatten().filter_map(|entry| {
+                split_cpv(&format!(
+                    "{}/{}",
+                    category_name,
+                    entry.file_name().to_string_lossy()
+                ))
+            }));
+        }
+        Ok(packages)
+    }
+}
+
+/// Returns `true` if a repository's sync timestamp file is missing or older than `SYNC_MAX_AGE`
+/// at `now`.
+///
+/// # Example
+/// ```
+/// use glwiz::functionality::package_manager::portage::timestamp_is_stale;
+/// assert!(timestamp_is_stale("/nonexistent/timestamp.chk", std::time::SystemTime::now()));
+/// ```
+pub fn timestamp_is_stale(path: &str, now: SystemTime) -> bool {
+    fs::metadata(path)
+        .and_then(|meta| meta.modified())
+        .map_or(true, |time| {
+            now.duration_since(time).is_ok_and(|age| age > SYNC_MAX_AGE)
+        })
+}
+
+/// Returns the `category/name` atom of a package, looking up names without a category in the
+/// package name table. Unknown names are returned unchanged for emerge to resolve.
+///
+/// # Example
+/// ```
+/// use glwiz::functionality::{package_manager::portage::atom, package_map::PackageMap};
+/// let map = PackageMap::parse("[zsh]\ngentoo = \"app-shells/zsh\"\n").unwrap();
+/// assert_eq!(atom("zsh", &map), "app-shells/zsh");
+/// assert_eq!(atom("app-misc/tmux", &map), "app-misc/tmux");
+/// assert_eq!(atom("tmux", &map), "tmux");
+/// ```
+pub fn atom(package: &str, map: &PackageMap) -> String {
+    if package.contains('/') {
+        return package.to_string();
+    }
+    match map
+        .lookup(package, DistroFamily::Gentoo.as_str())
+        .as_deref()
+    {
+        Some([atom]) => atom.clone(),
+        _ => package.to_string(),
+    }
+}
+
+/// Splits a `category/name-version` identifier into the `category/name` atom and the version,
+/// including its revision.
+///
+/// The version starts at the first `-` followed by a digit after which only the version and an
+/// optional `-rN` revision remain, so names containing such a part (e.g., `font-adobe-100dpi`)
+/// are kept whole. Entries that are being merged (`-MERGING-…`) are ignored.
+///
+/// # Example
+/// ```
+/// use glwiz::functionality::package_manager::portage::split_cpv;
+/// let zsh = split_cpv("app-shells/zsh-5.9-r6").unwrap();
+/// assert_eq!(zsh.name, "app-shells/zsh");
+/// assert_eq!(zsh.version, "5.9-r6");
+/// let font = split_cpv("media-fonts/font-adobe-100dpi-1.0.4").unwrap();
+/// assert_eq!(font.name, "media-fonts/font-adobe-100dpi");
+/// assert!(split_cpv("app-shells/-MERGING-zsh-5.9").is_none());
+/// assert!(split_cpv("app-shells/zsh").is_none());
+/// ```
+pub fn split_cpv(cpv: &str) -> Option<InstalledPackage> {
+    let (category, package) = cpv.trim().split_once('/')?;
+    if package.starts_with('-') {
+        return None;
+    }
+    package
+        .match_indices('-')
+        .map(|(index, _)| index)
+        .find(|&index| is_version(&package[index + 1..]))
+        .map(|index| InstalledPackage {
+            name: format!("{}/{}", category, &package[..index]),
+            version: package[index + 1..].to_string(),
+        })
+}
+
+// Returns `true` for a version with an optional revision (e.g., `5.9`, `1.0.4_p1-r2`).
+fn is_version(version: &str) -> bool {
+    let (version, revision) = match version.rsplit_once("-r") {
+        Some((version, revision)) if revision.bytes().all(|b| b.is_ascii_digit()) => {
+            (version, Some(revision))
+        }
+        _ => (version, None),
+    };
+    version.starts_with(|c: char| c.is_ascii_digit())
+        && !version.contains('-')
+        && revision.is_none_or(|revision| !revision.is_empty())
+}
+
+/// Returns the contents of the `package.use` file with the `PACKAGE_USE` entries for `atoms`
+/// appended, or `None` if the file already has all of them.
+///
+/// # Arguments
+/// * `atoms` - The atoms being installed.
+/// * `existing` - The current contents of the file.
+///
+/// # Example
+/// ```
+/// use glwiz::functionality::package_manager::portage::package_use_entries;
+/// let contents = package_use_entries(&["dev-vcs/git", "app-shells/zsh"], "").unwrap();
+/// assert_eq!(contents, "dev-vcs/git curl\n");
+/// assert_eq!(package_use_entries(&["dev-vcs/git"], &contents), None);
+/// ```
+pub fn package_use_entries(atoms: &[&str], existing: &str) -> Option<String> {
+    let mut contents = existing.to_string();
+    for (atom, flags) in PACKAGE_USE.iter().filter(|(atom, _)| atoms.contains(atom)) {
+        let entry = format!("{} {}", atom, flags);
+        if existing.lines().any(|line| line.trim() == entry) {
+            continue;
+        }
+        if !contents.is_empty() && !contents.ends_with('\n') {
+            contents.push('\n');
+        }
+        contents.push_str(&entry);
+        contents.push('\n');
+    }
+    (contents != existing).then_some(contents)
+}
+
+/// Parses the output of `emerge --pretend --depclean`, returning the atoms it would unmerge.
+///
+/// Each atom is printed on a line of its own after `These are the packages that would be
+/// unmerged:`, followed by indented `selected:`, `protected:` and `omitted:` lines.
+///
+/// # Example
+/// ```
+/// use glwiz::functionality::package_manager::portage::parse_depclean;
+/// let output = "\
+/// >>> These are the packages that would be unmerged:
+///
+///  dev-libs/libfoo
+///     selected: 1.2.3
+///    protected: none
+///      omitted: none
+///
+/// All selected packages: =dev-libs/libfoo-1.2.3
+/// ";
+/// assert_eq!(parse_depclean(output), vec!["dev-libs/libfoo"]);
+/// ```
+pub fn parse_depclean(output: &str) -> Vec<String> {
+    output
+        .lines()
+        .skip_while(|line| !line.contains("would be unmerged"))
+        .skip(1)
+        .take_while(|line| !line.starts_with("All selected packages"))
+        .map(str::trim)
+        .filter(|line| line.contains('/') && !line.contains(char::is_whitespace))
+        .map(String::from)
+        .collect()
+}
+
+/// Parses the output of `emerge --search`, which describes each package in a block starting
+/// with `*  category/name` followed by indented `Key: value` lines.
+///
+/// # Example
+/// ```
+/// use glwiz::functionality::package_manager::portage::parse_search;
+/// let output = "\
+/// *  app-shells/zsh
+///       Latest version available: 5.9-r6
+///       Latest version installed: 5.9-r6
+///       Description:   UNIX Shell similar to the Korn shell
+///
+/// *  app-shells/zsh-completions
+///       Latest version available: 0.35.0
+///       Latest version installed: [ Not Installed ]
+///       Description:   Additional completion definitions for Zsh
+/// ";
+/// let results = parse_search(output);
+/// assert_eq!(results[0].name, "app-shells/zsh");
+/// assert!(results[0].installed);
+/// assert_eq!(results[1].version.as_deref(), Some("0.35.0"));
+/// assert!(!results[1].installed);
+/// ```
+pub fn parse_search(output: &str) -> Vec<PackageInfo> {
+    let mut results: Vec<PackageInfo> = Vec::new();
+    for line in output.lines() {
+        let line = line.trim();
+        if let Some(name) = line.strip_prefix('*') {
+            results.push(PackageInfo {
+                name: name
+                    .split_whitespace()
+                    .next()
+                    .unwrap_or_default()
+                    .to_string(),
+                version: None,
+                description: String::new(),
+                installed: false,
+            });
+            continue;
+        }
+        let (Some(package), Some((key, value))) = (results.last_mut(), line.split_once(':')) else {
+            continue;
+        };
+        let value = value.trim();
+        match key {
+            "Latest version available" => package.version = Some(value.to_string()),
+            "Latest version installed" => package.installed = !value.starts_with('['),
+            "Description" => package.description = value.to_string(),
+            _ => {}
+        }
+    }
+    results
+}
@@ -0,0 +1,192 @@
+/*
+ * GLWiz - The ultimate post-installation setup assistant for GNU/Linux popular distros,
+ * streamlining your configuration process with ease and precision.
+ * 
+ * Copyright (C) 2025  Andrew Kushyk
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published
+ * by the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use super::{InstalledPackage, PackageInfo, PackageManager, with_packages};
+use crate::functionality::target::Target;
+
+/// The xbps installation executable.
+pub const COMMAND: &str = "xbps-install";
+
+/// The arguments that synchronize the repositories.
+pub const REFRESH_ARGS: [&str; 1] = ["-S"];
+
+/// The arguments that synchronize the repositories and install packages without prompting.
+pub const INSTALL_ARGS: [&str; 1] = ["-Sy"];
+
+/// The xbps package manager, operating on a target system.
+#[derive(Debug, Clone)]
+pub struct Xbps {
+    target: Target,
+}
+
+impl Xbps {
+    /// Creates an xbps instance operating on `target`.
+    pub fn new(target: Target) -> Self {
+        Self { target }
+    }
+}
+
+impl PackageManager for Xbps {
+    fn name(&self) -> &'static str {
+        "xbps"
+    }
+
+    fn refresh_index(&self) -> Result<(), String> {
+        self.target.run_command(COMMAND, &REFRESH_ARGS)
+    }
+
+    // xbps has to be updated on its own first when a new version is available.
+    fn upgrade(&self) -> Result<(), String> {
+        self.target.stream_command(COMMAND, &["-Suy", "xbps"])?;
+        self.target.stream_command(COMMAND, &["-uy"])
+    }
+
+    fn install(&self, packages: &[&str]) -> Result<(), String> {
+        self.target
+            .run_command(COMMAND, &with_packages(&INSTALL_ARGS, packages))
+    }
+
+    fn remove(&self, packages: &[&str]) -> Result<(), String> {
+        self.target
+            .run_command("xbps-remove", &with_packages(&["-Ry"], packages))
+    }
+
+    fn orphans(&self) -> Result<Vec<String>, String> {
+        self.target
+            .preview_command("xbps-remove", &["-on"])
+            .map(|output| parse_dry_run(&output))
+    }
+
+    fn remove_orphans(&self) -> Result<(), String> {
+        self.target.run_command("xbps-remove", &["-oy"])
+    }
+
+    fn is_installed(&self, package: &str) -> Result<bool, String> {
+        Ok(self
+            .target
+            .capture_command("xbps-query", &[package])
+            .is_ok())
+    }
+
+    fn is_available(&self, package: &str) -> Result<bool, String> {
+        Ok(self
+            .target
+            .capture_command("xbps-query", &["-R", package])
+            .is_ok())
+    }
+
+    fn search(&self, query: &str) -> Result<Vec<PackageInfo>, String> {
+        self.target
+            .capture_command("xbps-query", &["-Rs", query])
+            .map(|output| parse_search(&output))
+    }
+
+    fn list_installed(&self) -> Result<Vec<InstalledPackage>, String> {
+        self.target
+            .capture_command("xbps-query", &["-l"])
+            .map(|output| parse_list(&output))
+    }
+}
+
+/// Parses the output of an `xbps-remove -n` dry run, returning the names of the removed
+/// packages.
+///
+/// Each package is printed as `pkgver action arch repository size` per line.
+///
+/// # Example
+/// ```
+/// use glwiz::functionality::package_manager::xbps::parse_dry_run;
+/// let output = "libfoo-1.2_1 remove x86_64 https://repo-default.voidlinux.org/current 120KB\n";
+/// assert_eq!(parse_dry_run(output), vec!["libfoo"]);
+/// ```
+pub fn parse_dry_run(output: &str) -> Vec<String> {
+    output
+        .lines()
+        .filter_map(|line| {
+            let mut fields = line.split_whitespace();
+            let pkgver = fields.next()?;
+            (fields.next() == Some("remove")).then(|| split_pkgver(pkgver))?
+        })
+        .map(|package| package.name)
+        .collect()
+}
+
+/// Splits an xbps package identifier (`name-version_revision`) into its name and version.
+///
+/// # Example
+/// ```
+/// use glwiz::functionality::package_manager::xbps::split_pkgver;
+/// let package = split_pkgver("zsh-autosuggestions-0.7.0_2").unwrap();
+/// assert_eq!(package.name, "zsh-autosuggestions");
+/// assert_eq!(package.version, "0.7.0_2");
+/// assert!(split_pkgver("zsh").is_none());
+/// ```
+pub fn split_pkgver(pkgver: &str) -> Option<InstalledPackage> {
+    let (name, version) = pkgver.trim().rsplit_once('-')?;
+    version.contains('_').then(|| InstalledPackage {
+        name: name.to_string(),
+        version: version.to_string(),
+    })
+}
+
+/// Parses the output of `xbps-query -l` (`state pkgver description` per line).
+///
+/// # Example
+/// ```
+/// use glwiz::functionality::package_manager::xbps::parse_list;
+/// let packages = parse_list("ii zsh-5.9_2            Z shell\nii vim-9.1.0_1  Vim editor\n");
+/// assert_eq!(packages[0].name, "zsh");
+/// assert_eq!(packages[1].version, "9.1.0_1");
+/// ```
+pub fn parse_list(output: &str) -> Vec<InstalledPackage> {
+    output
+        .lines()
+        .filter_map(|line| split_pkgver(line.split_whitespace().nth(1)?))
+        .collect()
+}
+
+/// Parses the output of `xbps-query -Rs` (`[*] pkgver description` per line, where `[*]` marks
+/// installed packages and `[-]` the others).
+///
+/// # Example
+/// ```
+/// use glwiz::functionality::package_manager::xbps::parse_search;
+/// let results = parse_search("[*] zsh-5.9_2              Z shell\n[-] zsh-completions-0.35.0_1 Additional completions for Zsh\n");
+/// assert!(results[0].installed);
+/// assert_eq!(results[1].name, "zsh-completions");
+/// assert_eq!(results[1].description, "Additional completions for Zsh");
+/// ```
+pub fn parse_search(output: &str) -> Vec<PackageInfo> {
+    output
+        .lines()
+        .filter_map(|line| {
+            let (state, rest) = line.trim().split_once(' ')?;
+            let rest = rest.trim_start();
+            let (pkgver, description) = rest.split_once(' ').unwrap_or((rest, ""));
+            let package = split_pkgver(pkgver)?;
+            Some(PackageInfo {
+                name: package.name,
+                version: Some(package.version),
+                description: description.trim().to_string(),
+                installed: state == "[*]",
+            })
+        })
+        .collect()
+}
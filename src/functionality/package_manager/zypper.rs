@@ -0,0 +1,247 @@
+/*
+ * GLWiz - The ultimate post-installation setup assistant for GNU/Linux popular distros,
+ * streamlining your configuration process with ease and precision.
+ * 
+ * Copyright (C) 2025  Andrew Kushyk
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published
+ * by the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use super::{InstalledPackage, PackageInfo, PackageManager, dnf::parse_rpm_list, with_packages};
+use crate::functionality::{distro::Distro, target::Target};
+
+/// The zypper executable.
+pub const COMMAND: &str = "zypper";
+
+/// The arguments that refresh the repositories without prompting.
+pub const REFRESH_ARGS: [&str; 2] = ["--non-interactive", "refresh"];
+
+/// The arguments that install packages without prompting.
+pub const INSTALL_ARGS: [&str; 2] = ["--non-interactive", "install"];
+
+// The `rpm -qa` format listing name and version-release, tab-separated.
+const LIST_FORMAT: &str = "%{NAME}\\t%{VERSION}-%{RELEASE}\\n";
+
+/// The zypper package manager, operating on a target system.
+#[derive(Debug, Clone)]
+pub struct Zypper {
+    target: Target,
+}
+
+impl Zypper {
+    /// Creates a zypper instance operating on `target`.
+    pub fn new(target: Target) -> Self {
+        Self { target }
+    }
+}
+
+impl PackageManager for Zypper {
+    fn name(&self) -> &'static str {
+        COMMAND
+    }
+
+    fn refresh_index(&self) -> Result<(), String> {
+        self.target.run_command(COMMAND, &REFRESH_ARGS)
+    }
+
+    fn upgrade(&self) -> Result<(), String> {
+        self.refresh_index()?;
+        self.target.stream_command(
+            COMMAND,
+            &["--non-interactive", upgrade_command(&self.target)],
+        )
+    }
+
+    fn install(&self, packages: &[&str]) -> Result<(), String> {
+        self.target
+            .run_command(COMMAND, &with_packages(&INSTALL_ARGS, packages))
+    }
+
+    fn remove(&self, packages: &[&str]) -> Result<(), String> {
+        self.target.run_command(
+            COMMAND,
+            &with_packages(&["--non-interactive", "remove", "--clean-deps"], packages),
+        )
+    }
+
+    fn removal_set(&self, packages: &[&str]) -> Result<Vec<String>, String> {
+        self.target
+            .preview_command(
+                COMMAND,
+                &with_packages(
+                    &["--non-interactive", "remove", "--dry-run", "--clean-deps"],
+                    packages,
+                ),
+            )
+            .map(|output| parse_removal_summary(&output))
+    }
+
+    // `remove --clean-deps` already removes the dependencies no longer needed.
+    fn remove_orphans(&self) -> Result<(), String> {
+        Ok(())
+    }
+
+    fn is_installed(&self, package: &str) -> Result<bool, String> {
+        Ok(self.target.capture_command("rpm", &["-q", package]).is_ok())
+    }
+
+    // `zypper search` exits with status 104 when nothing matches.
+    fn is_available(&self, package: &str) -> Result<bool, String> {
+        Ok(self
+            .target
+            .capture_command(
+                COMMAND,
+                &["--non-interactive", "search", "--match-exact", package],
+            )
+            .is_ok())
+    }
+
+    // `zypper search` exits with status 104 when nothing matches.
+    fn search(&self, query: &str) -> Result<Vec<PackageInfo>, String> {
+        Ok(self
+            .target
+            .capture_command(
+                COMMAND,
+                &[
+                    "--non-interactive",
+                    "--quiet",
+                    "search",
+                    "-t",
+                    "package",
+                    query,
+                ],
+            )
+            .map(|output| parse_search(&output))
+            .unwrap_or_default())
+    }
+
+    fn list_installed(&self) -> Result<Vec<InstalledPackage>, String> {
+        self.target
+            .capture_command("rpm", &["-qa", "--qf", LIST_FORMAT])
+            .map(|output| parse_rpm_list(&output))
+    }
+}
+
+/// Parses a table printed by zypper into rows keyed by the column headings.
+///
+/// zypper separates columns with `|` and the heading from the rows with a line of dashes.
+/// Lines before the heading (e.g., `Loading repository data...`) are ignored.
+///
+/// # Example
+/// ```
+/// use glwiz::functionality::package_manager::zypper::parse_table;
+/// let output = "S  | Name | Summary\n---+------+--------\ni+ | zsh  | Z shell\n";
+/// let rows = parse_table(output);
+/// assert_eq!(rows[0]["Name"], "zsh");
+/// assert_eq!(rows[0]["S"], "i+");
+/// ```
+pub fn parse_table(output: &str) -> Vec<std::collections::HashMap<String, String>> {
+    let mut lines = output.lines().filter(|line| line.contains('|'));
+    let Some(heading) = lines.next() else {
+        return Vec::new();
+    };
+    let columns: Vec<String> = heading.split('|').map(|c| c.trim().to_string()).collect();
+    lines
+        .filter(|line| !line.starts_with("--"))
+        .map(|line| {
+            columns
+                .iter()
+                .cloned()
+                .zip(line.split('|').map(|c| c.trim().to_string()))
+                .collect()
+        })
+        .collect()
+}
+
+/// Returns the zypper command that upgrades an openSUSE release.
+///
+/// The rolling releases (Tumbleweed, Slowroll) are upgraded with `dist-upgrade`, as openSUSE
+/// recommends; fixed releases such as Leap use `update`.
+///
+/// # Example
+/// ```
+/// use glwiz::functionality::package_manager::zypper::upgrade_command_for;
+/// assert_eq!(upgrade_command_for("opensuse-tumbleweed"), "dist-upgrade");
+/// assert_eq!(upgrade_command_for("opensuse-leap"), "update");
+/// ```
+pub fn upgrade_command_for(id: &str) -> &'static str {
+    match id {
+        "opensuse-tumbleweed" | "opensuse-slowroll" => "dist-upgrade",
+        _ => "update",
+    }
+}
+
+// Returns the upgrade command for the release installed on `target`.
+fn upgrade_command(target: &Target) -> &'static str {
+    Distro::detect(target)
+        .map(|distro| upgrade_command_for(&distro.id))
+        .unwrap_or("update")
+}
+
+/// Parses the output of `zypper search`.
+///
+/// Packages marked `i` or `i+` in the status column are reported as installed.
+///
+/// # Example
+/// ```
+/// use glwiz::functionality::package_manager::zypper::parse_search;
+/// let output = "S  | Name                | Summary                  | Type\n\
+///               ---+---------------------+--------------------------+--------\n\
+///               i+ | zsh                 | Z shell                  | package\n   \
+///                  | zsh-htmldoc         | HTML documentation for zsh | package\n";
+/// let results = parse_search(output);
+/// assert_eq!(results.len(), 2);
+/// assert_eq!(results[0].name, "zsh");
+/// assert!(results[0].installed);
+/// assert_eq!(results[1].description, "HTML documentation for zsh");
+/// assert!(!results[1].installed);
+/// ```
+pub fn parse_search(output: &str) -> Vec<PackageInfo> {
+    parse_table(output)
+        .into_iter()
+        .filter_map(|row| {
+            Some(PackageInfo {
+                name: row.get("Name")?.clone(),
+                version: row.get("Version").cloned(),
+                description: row.get("Summary").cloned().unwrap_or_default(),
+                installed: row.get("S").is_some_and(|s| s.starts_with('i')),
+            })
+        })
+        .collect()
+}
+
+/// Parses the summary of `zypper remove --dry-run`, returning the names of the removed
+/// packages.
+///
+/// The names follow a `The following N packages are going to be REMOVED:` line, on indented
+/// lines up to the next blank line.
+///
+/// # Example
+/// ```
+/// use glwiz::functionality::package_manager::zypper::parse_removal_summary;
+/// let output = "Resolving package dependencies...\n\n\
+///               The following 2 packages are going to be REMOVED:\n  gnome-mines iagno\n\n\
+///               2 packages to remove.\n";
+/// assert_eq!(parse_removal_summary(output), vec!["gnome-mines", "iagno"]);
+/// ```
+pub fn parse_removal_summary(output: &str) -> Vec<String> {
+    output
+        .lines()
+        .skip_while(|line| !line.contains("going to be REMOVED"))
+        .skip(1)
+        .take_while(|line| !line.trim().is_empty())
+        .flat_map(str::split_whitespace)
+        .map(String::from)
+        .collect()
+}
@@ -0,0 +1,290 @@
+/*
+ * GLWiz - The ultimate post-installation setup assistant for GNU/Linux popular distros,
+ * streamlining your configuration process with ease and precision.
+ * 
+ * Copyright (C) 2025  Andrew Kushyk
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published
+ * by the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use super::env::get_env_var;
+use colored::Colorize;
+use serde::Deserialize;
+use std::{
+    collections::BTreeMap,
+    fs,
+    path::{Path, PathBuf},
+};
+
+/// The bundled package name table, relative to the glwiz binary.
+pub const PACKAGE_MAP_SOURCE: &str = "../configs/packages.toml";
+
+/// The file name of the user’s package name table in the glwiz configuration directory.
+pub const USER_PACKAGE_MAP_FILE: &str = "packages.toml";
+
+// The names a package has on one family: a single package, or several (none to skip it).
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(untagged)]
+enum PackageNames {
+    One(String),
+    Many(Vec<String>),
+}
+
+impl PackageNames {
+    fn to_vec(&self) -> Vec<String> {
+        match self {
+            Self::One(name) => vec![name.clone()],
+            Self::Many(names) => names.clone(),
+        }
+    }
+}
+
+/// A table mapping logical package names to the names they have on each distribution family.
+///
+/// Package lists (`prog_fun::default_sw_package`, custom lists and profiles) name packages
+/// once, while distributions disagree on some names (e.g., `fd` is `fd-find` on Debian and
+/// Fedora). The table is stored as TOML with one table per logical name and one key per
+/// family (`DistroFamily::as_str`, plus `nixos` for nixpkgs attributes). A value is a package
+/// name or a list of names; an empty list skips the package on that family.
+///
+/// The bundled table is extended by the user’s `packages.toml` in the glwiz configuration
+/// directory (see `config_dir`), whose entries take precedence per family.
+///
+/// # Example
+/// ```
+/// use glwiz::functionality::package_map::PackageMap;
+/// let map = PackageMap::parse("[fd]\ndebian = \"fd-find\"\n\n[build-essential]\narch = \"base-devel\"\nfedora = [\"gcc\", \"make\"]\n").unwrap();
+/// assert_eq!(map.lookup("fd", "debian"), Some(vec!["fd-find".to_string()]));
+/// assert_eq!(map.lookup("fd", "arch"), Some(vec!["fd".to_string()]));
+/// assert_eq!(map.lookup("fd-find", "arch"), Some(vec!["fd".to_string()]));
+/// assert_eq!(map.lookup("build-essential", "fedora").unwrap().len(), 2);
+/// assert_eq!(map.lookup("tmux", "arch"), None);
+/// ```
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct PackageMap {
+    entries: BTreeMap<String, BTreeMap<String, PackageNames>>,
+}
+
+/// The outcome of resolving a package list through a `PackageMap`.
+///
+/// # Fields
+/// * `packages` - The package names to install on the family, in list order.
+/// * `mapped` - The logical names whose names differ on the family, with their replacements.
+/// * `unknown` - The names not found in the table, which are installed as given.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct PackageResolution {
+    pub packages: Vec<String>,
+    pub mapped: Vec<(String, Vec<String>)>,
+    pub unknown: Vec<String>,
+}
+
+impl PackageMap {
+    /// Parses a package name table.
+    ///
+    /// # Arguments
+    /// * `contents` - The TOML table.
+    ///
+    /// # Returns
+    /// * `Ok(PackageMap)` - The parsed table.
+    /// * `Err(String)` - An error message if the TOML is invalid or a value is neither a name
+    ///   nor a list of names.
+    pub fn parse(contents: &str) -> Result<Self, String> {
+        toml::from_str(contents)
+            .map(|entries| Self { entries })
+            .map_err(|e| format!("Invalid package table: {}", e))
+    }
+
+    /// Loads the bundled table extended by the user’s table.
+    ///
+    /// A missing file is skipped silently; an unreadable or invalid one is skipped with a
+    /// warning, so that a broken table never stops the setup.
+    ///
+    /// # Example
+    /// ```
+    /// use glwiz::functionality::package_map::PackageMap;
+    /// let map = PackageMap::load();
+    /// let _ = map.lookup("vim", "fedora");
+    /// ```
+    pub fn load() -> Self {
+        let mut map = Self::default();
+        let user_map = config_dir().ok().map(|dir| dir.join(USER_PACKAGE_MAP_FILE));
+        for path in std::iter::once(PathBuf::from(PACKAGE_MAP_SOURCE)).chain(user_map) {
+            if !path.is_file() {
+                continue;
+            }
+            match fs::read_to_string(&path)
+                .map_err(|e| e.to_string())
+                .and_then(|contents| Self::parse(&contents))
+            {
+                Ok(other) => map.extend(other),
+                Err(e) => eprintln!(
+                    "{} Ignoring package table {}: {}",
+                    "warning:".yellow(),
+                    path.display(),
+                    e
+                ),
+            }
+        }
+        map
+    }
+
+    /// Adds the entries of another table, replacing the names it gives for a family.
+    ///
+    /// # Example
+    /// ```
+    /// use glwiz::functionality::package_map::PackageMap;
+    /// let mut map = PackageMap::parse("[fd]\ndebian = \"fd-find\"\nfedora = \"fd-find\"\n").unwrap();
+    /// map.extend(PackageMap::parse("[fd]\ndebian = \"fdfind\"\n").unwrap());
+    /// assert_eq!(map.lookup("fd", "debian"), Some(vec!["fdfind".to_string()]));
+    /// assert_eq!(map.lookup("fd", "fedora"), Some(vec!["fd-find".to_string()]));
+    /// ```
+    pub fn extend(&mut self, other: PackageMap) {
+        for (name, families) in other.entries {
+            self.entries.entry(name).or_default().extend(families);
+        }
+    }
+
+    /// Returns the names a package has on a family.
+    ///
+    /// The package is looked up by its logical name, or else by any single name an entry gives
+    /// for a family (e.g., `python3` finds `python`).
+    ///
+    /// # Arguments
+    /// * `name` - The package name from a package list.
+    /// * `family` - The family key (e.g., `"debian"` or `"nixos"`).
+    ///
+    /// # Returns
+    /// * `Some(Vec<String>)` - The names to install, which are the logical name if the entry
+    ///   has none for the family, and empty if the package is skipped there.
+    /// * `None` - The package is not in the table.
+    pub fn lookup(&self, name: &str, family: &str) -> Option<Vec<String>> {
+        let (logical, families) = self.entry(name)?;
+        Some(
+            families
+                .get(family)
+                .map_or_else(|| vec![logical.clone()], PackageNames::to_vec),
+        )
+    }
+
+    /// Returns the logical name of a package, looked up like `lookup` does, so that names
+    /// from different families (e.g., a Flathub app ID and a native name) can be compared.
+    ///
+    /// # Example
+    /// ```
+    /// use glwiz::functionality::package_map::PackageMap;
+    /// let map = PackageMap::parse("[firefox]\nflatpak = \"org.mozilla.firefox\"\n").unwrap();
+    /// assert_eq!(map.logical_name("org.mozilla.firefox"), Some("firefox"));
+    /// assert_eq!(map.logical_name("firefox"), Some("firefox"));
+    /// assert_eq!(map.logical_name("tmux"), None);
+    /// ```
+    pub fn logical_name(&self, name: &str) -> Option<&str> {
+        self.entry(name).map(|(logical, _)| logical.as_str())
+    }
+
+    // Finds the entry of a package by its logical name, or else by any single name it gives
+    // for a family.
+    fn entry(&self, name: &str) -> Option<(&String, &BTreeMap<String, PackageNames>)> {
+        self.entries.get_key_value(name).or_else(|| {
+            self.entries.iter().find(|(_, families)| {
+                families
+                    .values()
+                    .any(|names| *names == PackageNames::One(name.to_string()))
+            })
+        })
+    }
+
+    /// Resolves a package list for a family.
+    ///
+    /// # Arguments
+    /// * `names` - The package names from a package list.
+    /// * `family` - The family key (e.g., `"fedora"`).
+    ///
+    /// # Returns
+    /// The names to install, with the mapped and unknown names for `PackageResolution::report`.
+    ///
+    /// # Example
+    /// ```
+    /// use glwiz::functionality::package_map::PackageMap;
+    /// let map = PackageMap::parse("[vim]\nfedora = \"vim-enhanced\"\n\n[git]\n").unwrap();
+    /// let resolution = map.resolve(&["vim", "git", "foo"], "fedora");
+    /// assert_eq!(resolution.packages, vec!["vim-enhanced", "git", "foo"]);
+    /// assert_eq!(resolution.mapped, vec![("vim".to_string(), vec!["vim-enhanced".to_string()])]);
+    /// assert_eq!(resolution.unknown, vec!["foo"]);
+    /// ```
+    pub fn resolve(&self, names: &[&str], family: &str) -> PackageResolution {
+        let mut resolution = PackageResolution::default();
+        for name in names {
+            let resolved = match self.lookup(name, family) {
+                Some(resolved) => {
+                    if resolved != [*name] {
+                        resolution.mapped.push((name.to_string(), resolved.clone()));
+                    }
+                    resolved
+                }
+                None => {
+                    resolution.unknown.push(name.to_string());
+                    vec![name.to_string()]
+                }
+            };
+            for package in resolved {
+                if !resolution.packages.contains(&package) {
+                    resolution.packages.push(package);
+                }
+            }
+        }
+        resolution
+    }
+}
+
+impl PackageResolution {
+    /// Prints which names were mapped for a family and which are not in the package table.
+    pub fn report(&self, family: &str) {
+        if !self.mapped.is_empty() {
+            let mapped: Vec<String> = self
+                .mapped
+                .iter()
+                .map(|(name, packages)| match packages.as_slice() {
+                    [] => format!("{} (skipped)", name),
+                    _ => format!("{} → {}", name, packages.join(" ")),
+                })
+                .collect();
+            println!(
+                "Package names for {}: {}",
+                family,
+                mapped.join(", ").green()
+            );
+        }
+        if !self.unknown.is_empty() {
+            println!(
+                "{} Not in the package table, installed as given: {}",
+                "warning:".yellow(),
+                self.unknown.join(" ")
+            );
+        }
+    }
+}
+
+/// Returns the directory holding the user’s glwiz configuration.
+///
+/// This is `$XDG_CONFIG_HOME/glwiz`, or `~/.config/glwiz` if `XDG_CONFIG_HOME` is unset.
+///
+/// # Returns
+/// * `Ok(PathBuf)` - The configuration directory.
+/// * `Err(String)` - An error message if neither `XDG_CONFIG_HOME` nor `HOME` is set.
+pub fn config_dir() -> Result<PathBuf, String> {
+    match get_env_var("XDG_CONFIG_HOME") {
+        Ok(dir) if !dir.is_empty() => Ok(Path::new(&dir).join("glwiz")),
+        _ => get_env_var("HOME").map(|home| Path::new(&home).join(".config/glwiz")),
+    }
+}
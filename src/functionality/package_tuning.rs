@@ -0,0 +1,387 @@
+/*
+ * GLWiz - The ultimate post-installation setup assistant for GNU/Linux popular distros,
+ * streamlining your configuration process with ease and precision.
+ * 
+ * Copyright (C) 2025  Andrew Kushyk
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published
+ * by the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+use super::{
+    atomic::write_file_as_root, distro::DistroFamily, repositories::PACMAN_CONF_PATH,
+    target::Target,
+};
+use colored::Colorize;
+use serde::{Deserialize, Serialize};
+use std::fs;
+
+/// The dnf configuration, whose `[main]` section holds the global options.
+pub const DNF_CONF_PATH: &str = "/etc/dnf/dnf.conf";
+
+/// The APT configuration snippet written by glwiz; the `99` prefix makes it override others.
+pub const APT_TUNING_PATH: &str = "/etc/apt/apt.conf.d/99glwiz";
+
+/// The package manager settings applied before software is installed.
+///
+/// # Fields
+/// * `parallel_downloads` - The number of packages downloaded at once by pacman and dnf; `0`
+///   leaves the setting unchanged.
+/// * `color` - Whether pacman colors its output.
+/// * `default_yes` - Whether dnf prompts default to “yes”.
+/// * `install_recommends` - Whether APT installs recommended packages along with the requested
+///   ones.
+///
+/// # Example
+/// ```
+/// use glwiz::functionality::profile::Profile;
+/// let profile = Profile::from_toml(r#"
+/// [packages.tuning]
+/// parallel_downloads = 10
+/// "#).unwrap();
+/// assert_eq!(profile.packages.tuning.parallel_downloads, 10);
+/// assert!(profile.packages.tuning.color);
+/// ```
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PackageTuning {
+    pub parallel_downloads: u32,
+    pub color: bool,
+    pub default_yes: bool,
+    pub install_recommends: bool,
+}
+
+impl Default for PackageTuning {
+    fn default() -> Self {
+        Self {
+            parallel_downloads: 5,
+            color: true,
+            default_yes: true,
+            install_recommends: false,
+        }
+    }
+}
+
+// Returns the key of an active setting line such as `ParallelDownloads = 5` or `Color`.
+fn option_key(line: &str) -> &str {
+    line.split_once('=').map_or(line, |(key, _)| key).trim()
+}
+
+// Returns the range of lines below a `[section]` header, up to the next header.
+fn section_range(lines: &[String], section: &str) -> Option<(usize, usize)> {
+    let header = format!("[{}]", section);
+    let start = lines.iter().position(|line| line.trim() == header)? + 1;
+    let end = lines[start..]
+        .iter()
+        .position(|line| line.trim().starts_with('['))
+        .map_or(lines.len(), |offset| start + offset);
+    Some((start, end))
+}
+
+/// Sets an option in a section of an INI-style configuration, keeping the rest intact.
+///
+/// An active line for the option is replaced; otherwise the first commented-out one (such as
+/// `#ParallelDownloads = 5` in the stock `pacman.conf`) is uncommented and replaced. If there is
+/// none, the option is added after the last line of the section, and a missing section is
+/// appended.
+///
+/// # Arguments
+/// * `conf` - The contents of the configuration file.
+/// * `section` - The section holding the option (e.g., `"options"`).
+/// * `key` - The option name.
+/// * `value` - The value, or `None` for a flag without one (e.g., pacman’s `Color`).
+/// * `separator` - The text between the key and value (`" = "` or `"="`).
+///
+/// # Returns
+/// * `Some(String)` - The updated configuration.
+/// * `None` - The option already has this value.
+///
+/// # Example
+/// ```
+/// use glwiz::functionality::package_tuning::set_config_option;
+/// let conf = "[options]\nHoldPkg = pacman glibc\n#Color\n#ParallelDownloads = 5\n\n[core]\n";
+/// let conf = set_config_option(conf, "options", "Color", None, " = ").unwrap();
+/// let conf = set_config_option(&conf, "options", "ParallelDownloads", Some("10"), " = ").unwrap();
+/// assert_eq!(conf, "[options]\nHoldPkg = pacman glibc\nColor\nParallelDownloads = 10\n\n[core]\n");
+/// assert_eq!(set_config_option(&conf, "options", "Color", None, " = "), None);
+///
+/// let dnf = set_config_option("[main]\ngpgcheck=1\n", "main", "defaultyes", Some("True"), "=");
+/// assert_eq!(dnf.unwrap(), "[main]\ngpgcheck=1\ndefaultyes=True\n");
+/// ```
+pub fn set_config_option(
+    conf: &str,
+    section: &str,
+    key: &str,
+    value: Option<&str>,
+    separator: &str,
+) -> Option<String> {
+    let wanted = match value {
+        Some(value) => format!("{}{}{}", key, separator, value),
+        None => key.to_string(),
+    };
+    let mut lines: Vec<String> = conf.lines().map(str::to_string).collect();
+    let Some((start, end)) = section_range(&lines, section) else {
+        if !lines.is_empty() && lines.last().is_some_and(|line| !line.trim().is_empty()) {
+            lines.push(String::new());
+        }
+        lines.push(format!("[{}]", section));
+        lines.push(wanted);
+        return Some(lines.join("\n") + "\n");
+    };
+
+    let active = (start..end).find(|&i| {
+        let line = lines[i].trim();
+        !line.starts_with('#') && option_key(line) == key
+    });
+    if let Some(i) = active {
+        let current = lines[i].split_once('=').map(|(_, value)| value.trim());
+        if current == value {
+            return None;
+        }
+        lines[i] = wanted;
+    } else if let Some(i) = (start..end).find(|&i| {
+        let line = lines[i].trim();
+        line.starts_with('#') && option_key(line.trim_start_matches('#')) == key
+    }) {
+        lines[i] = wanted;
+    } else {
+        let last = (start..end)
+            .rev()
+            .find(|&i| !lines[i].trim().is_empty())
+            .map_or(start, |i| i + 1);
+        lines.insert(last, wanted);
+    }
+    Some(lines.join("\n") + "\n")
+}
+
+/// Comments out an option in a section of an INI-style configuration.
+///
+/// # Arguments
+/// * `conf` - The contents of the configuration file.
+/// * `section` - The section holding the option.
+/// * `key` - The option name.
+///
+/// # Returns
+/// * `Some(String)` - The updated configuration.
+/// * `None` - The option is not set.
+///
+/// # Example
+/// ```
+/// use glwiz::functionality::package_tuning::unset_config_option;
+/// let conf = "[options]\nColor\n";
+/// assert_eq!(unset_config_option(conf, "options", "Color").unwrap(), "[options]\n#Color\n");
+/// assert_eq!(unset_config_option("[options]\n#Color\n", "options", "Color"), None);
+/// ```
+pub fn unset_config_option(conf: &str, section: &str, key: &str) -> Option<String> {
+    let mut lines: Vec<String> = conf.lines().map(str::to_string).collect();
+    let (start, end) = section_range(&lines, section)?;
+    let mut changed = false;
+    for line in &mut lines[start..end] {
+        if !line.trim().starts_with('#') && option_key(line) == key {
+            *line = format!("#{}", line.trim());
+            changed = true;
+        }
+    }
+    changed.then(|| lines.join("\n") + "\n")
+}
+
+/// Returns the APT configuration snippet for the given settings.
+///
+/// # Arguments
+/// * `tuning` - The settings to apply.
+///
+/// # Returns
+/// * `String` - The contents of `APT_TUNING_PATH`.
+///
+/// # Example
+/// ```
+/// use glwiz::functionality::package_tuning::{apt_config, PackageTuning};
+/// assert_eq!(
+///     apt_config(&PackageTuning::default()),
+///     "APT::Install-Recommends \"false\";\n"
+/// );
+/// ```
+pub fn apt_config(tuning: &PackageTuning) -> String {
+    format!(
+        "APT::Install-Recommends \"{}\";\n",
+        tuning.install_recommends
+    )
+}
+
+/// Returns the pacman configuration with the given settings applied.
+///
+/// # Arguments
+/// * `conf` - The contents of `pacman.conf`.
+/// * `tuning` - The settings to apply.
+///
+/// # Returns
+/// * `Some(String)` - The updated configuration.
+/// * `None` - The configuration already has these settings.
+///
+/// # Example
+/// ```
+/// use glwiz::functionality::package_tuning::{pacman_config, PackageTuning};
+/// let conf = "[options]\n#Color\n#ParallelDownloads = 5\n";
+/// let tuned = pacman_config(conf, &PackageTuning::default()).unwrap();
+/// assert_eq!(tuned, "[options]\nColor\nParallelDownloads = 5\n");
+/// assert_eq!(pacman_config(&tuned, &PackageTuning::default()), None);
+/// ```
+pub fn pacman_config(conf: &str, tuning: &PackageTuning) -> Option<String> {
+    let mut updated = conf.to_string();
+    if tuning.parallel_downloads > 0 {
+        let downloads = tuning.parallel_downloads.to_string();
+        if let Some(tuned) = set_config_option(
+            &updated,
+            "options",
+            "ParallelDownloads",
+            Some(&downloads),
+            " = ",
+        ) {
+            updated = tuned;
+        }
+    }
+    let color = if tuning.color {
+        set_config_option(&updated, "options", "Color", None, " = ")
+    } else {
+        unset_config_option(&updated, "options", "Color")
+    };
+    if let Some(tuned) = color {
+        updated = tuned;
+    }
+    (updated != conf).then_some(updated)
+}
+
+/// Returns the dnf configuration with the given settings applied.
+///
+/// # Arguments
+/// * `conf` - The contents of `dnf.conf`.
+/// * `tuning` - The settings to apply.
+///
+/// # Returns
+/// * `Some(String)` - The updated configuration.
+/// * `None` - The configuration already has these settings.
+///
+/// # Example
+/// ```
+/// use glwiz::functionality::package_tuning::{dnf_config, PackageTuning};
+/// let tuned = dnf_config("[main]\ngpgcheck=True\n", &PackageTuning::default()).unwrap();
+/// assert_eq!(tuned, "[main]\ngpgcheck=True\nmax_parallel_downloads=5\ndefaultyes=True\n");
+/// ```
+pub fn dnf_config(conf: &str, tuning: &PackageTuning) -> Option<String> {
+    let mut updated = conf.to_string();
+    if tuning.parallel_downloads > 0 {
+        let downloads = tuning.parallel_downloads.to_string();
+        if let Some(tuned) = set_config_option(
+            &updated,
+            "main",
+            "max_parallel_downloads",
+            Some(&downloads),
+            "=",
+        ) {
+            updated = tuned;
+        }
+    }
+    let default_yes = if tuning.default_yes { "True" } else { "False" };
+    if let Some(tuned) = set_config_option(&updated, "main", "defaultyes", Some(default_yes), "=") {
+        updated = tuned;
+    }
+    (updated != conf).then_some(updated)
+}
+
+/// Applies package manager settings before software is installed.
+///
+/// This function tunes the package manager of the distribution being configured:
+/// - On Arch, `ParallelDownloads` and `Color` are set in the `[options]` section of
+///   `/etc/pacman.conf`.
+/// - On Fedora, `max_parallel_downloads` and `defaultyes` are set in the `[main]` section of
+///   `/etc/dnf/dnf.conf`.
+/// - On Debian, `APT::Install-Recommends` is set in `/etc/apt/apt.conf.d/99glwiz`.
+///
+/// Existing files are edited line by line, so comments and other settings are kept. Files are
+/// only written if a setting changes; they are written atomically and backed up, so `glwiz undo`
+/// restores them. Other families are left unchanged.
+///
+/// # Arguments
+/// * `tuning` - The settings to apply.
+/// * `family` - The distribution family, if known.
+/// * `target` - The system whose package manager is tuned.
+///
+/// # Returns
+/// * `0` - The settings were applied, were already in place, or the family has none.
+/// * `1` - A configuration file could not be read or written.
+///
+/// # Example
+/// ```no_run
+/// // Requires an Arch system and sudo privileges.
+/// use glwiz::functionality::{
+///     distro::DistroFamily,
+///     package_tuning::{package_tuning_setup, PackageTuning},
+///     target::Target,
+/// };
+/// let result =
+///     package_tuning_setup(&PackageTuning::default(), Some(DistroFamily::Arch), &Target::host());
+/// assert_eq!(result, 0);
+/// ```
+///
+/// # See Also
+/// - `software::software_setup`: Installs software with the tuned package manager.
+pub fn package_tuning_setup(
+    tuning: &PackageTuning,
+    family: Option<DistroFamily>,
+    target: &Target,
+) -> i8 {
+    let path = match family {
+        Some(DistroFamily::Arch) => PACMAN_CONF_PATH,
+        Some(DistroFamily::Fedora) => DNF_CONF_PATH,
+        Some(DistroFamily::Debian) => APT_TUNING_PATH,
+        _ => {
+            println!("No package manager settings for this distribution.");
+            return 0;
+        }
+    };
+
+    let path = target.path(path);
+    let conf = match fs::read_to_string(&path) {
+        Ok(conf) => conf,
+        // glwiz owns the APT snippet, which does not exist before the first run
+        Err(e)
+            if e.kind() == std::io::ErrorKind::NotFound && family == Some(DistroFamily::Debian) =>
+        {
+            String::new()
+        }
+        Err(e) => {
+            eprintln!("{} Cannot read {}: {}", "error:".red(), path, e);
+            return 1;
+        }
+    };
+    let updated = match family {
+        Some(DistroFamily::Arch) => pacman_config(&conf, tuning),
+        Some(DistroFamily::Fedora) => dnf_config(&conf, tuning),
+        _ => Some(apt_config(tuning)).filter(|wanted| *wanted != conf),
+    };
+    match updated {
+        Some(updated) => match write_file_as_root(&path, &updated) {
+            Ok(()) => {
+                println!("{}: {}", path, "updated".green());
+                0
+            }
+            Err(e) => {
+                eprintln!("{} Cannot write {}: {}", "error:".red(), path, e);
+                1
+            }
+        },
+        None => {
+            println!("{}: {}", path, "already tuned".green());
+            0
+        }
+    }
+}
@@ -0,0 +1,198 @@
+/*
+ * GLWiz - The ultimate post-installation setup assistant for GNU/Linux popular distros,
+ * streamlining your configuration process with ease and precision.
+ * 
+ * Copyright (C) 2025  Andrew Kushyk
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published
+ * by the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use super::{
+    configs::{ROOT_CONFIG_ITEMS, VIMRC_SOURCE, ZSHRC_SOURCE},
+    iptables::{IPTABLES_RULES_PATH, IPTABLES_RULES_SOURCE},
+    shell::{
+        OMZ_INSTALL_URL, OMZ_REPO, ZSH_AUTOSUGGESTIONS_REPO, ZSH_PATH, ZSH_SYNTAX_HIGHLIGHTING_REPO,
+    },
+    zram::{ZRAM_CONFIG_PATH, ZRAM_CONFIG_SOURCE},
+};
+
+/// The account a planned step applies to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Account {
+    /// The user running the setup.
+    User,
+    /// The root user.
+    Root,
+}
+
+/// A single action of the default setup, described without performing it.
+///
+/// Steps are rendered by the exporters into other formats, such as a POSIX shell script.
+/// Paths in the user’s home directory are given relative to that directory.
+#[derive(Debug, Clone, PartialEq)]
+pub enum PlanStep {
+    /// Installs packages with the distribution’s package manager.
+    InstallPackages { packages: Vec<String> },
+    /// Sets the login shell of an account.
+    ChangeShell { account: Account, shell: String },
+    /// Installs Oh My Zsh into `~/.oh-my-zsh` with the official installation script (`url`),
+    /// which clones `repo_url`.
+    InstallOmz { url: String, repo_url: String },
+    /// Clones a Zsh plugin into the Oh My Zsh custom plugins directory.
+    ClonePlugin { name: String, repo_url: String },
+    /// Installs a bundled configuration file into the user’s home directory.
+    InstallUserFile { source: String, file_name: String },
+    /// Installs a bundled configuration file into a system location. `unit` names the systemd
+    /// unit that picks the file up, for exporters that restart services on change.
+    InstallSystemFile {
+        source: String,
+        dest: String,
+        unit: Option<String>,
+    },
+    /// Loads an iptables rules file into the running firewall.
+    RestoreIptables { rules: String },
+    /// Copies items from the user’s home directory into root’s.
+    CopyToRoot { items: Vec<String> },
+}
+
+/// A named step of the setup plan.
+///
+/// # Fields
+/// * `name` - The task name, as reported by `gnu_linux_default_setup`.
+/// * `step` - The action the task performs.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PlanTask {
+    pub name: String,
+    pub step: PlanStep,
+}
+
+/// Builds the plan of the default setup for the given package list.
+///
+/// The tasks appear in the order `gnu_linux_default_setup` runs them and carry the same names
+/// (with the user’s shell change named generically), so that an exported plan can be compared
+/// with a regular run.
+///
+/// # Arguments
+/// * `packages` - The packages to install (e.g., `prog_fun::default_sw_package()`).
+///
+/// # Returns
+/// A `Vec<PlanTask>` describing every setup task.
+///
+/// # Example
+/// ```
+/// use glwiz::functionality::plan::{default_plan, PlanStep};
+/// let plan = default_plan(&["zsh", "git"]);
+/// assert_eq!(plan[0].name, "iptables file setup");
+/// assert!(plan.iter().any(|t| t.step == PlanStep::InstallPackages {
+///     packages: vec!["zsh".to_string(), "git".to_string()],
+/// }));
+/// ```
+///
+/// # See Also
+/// - `export`: Renders the plan into other formats.
+pub fn default_plan(packages: &[&str]) -> Vec<PlanTask> {
+    let task = |name: &str, step: PlanStep| PlanTask {
+        name: name.to_string(),
+        step,
+    };
+
+    vec![
+        task(
+            "iptables file setup",
+            PlanStep::InstallSystemFile {
+                source: IPTABLES_RULES_SOURCE.to_string(),
+                dest: IPTABLES_RULES_PATH.to_string(),
+                unit: None,
+            },
+        ),
+        task(
+            "iptables rules setup",
+            PlanStep::RestoreIptables {
+                rules: IPTABLES_RULES_PATH.to_string(),
+            },
+        ),
+        task(
+            "Software installation",
+            PlanStep::InstallPackages {
+                packages: packages.iter().map(|p| p.to_string()).collect(),
+            },
+        ),
+        task(
+            "Shell change for user",
+            PlanStep::ChangeShell {
+                account: Account::User,
+                shell: ZSH_PATH.to_string(),
+            },
+        ),
+        task(
+            "Shell change for root",
+            PlanStep::ChangeShell {
+                account: Account::Root,
+                shell: ZSH_PATH.to_string(),
+            },
+        ),
+        task(
+            "Oh My Zsh installation",
+            PlanStep::InstallOmz {
+                url: OMZ_INSTALL_URL.to_string(),
+                repo_url: OMZ_REPO.to_string(),
+            },
+        ),
+        task(
+            "Zsh autosuggestions installation",
+            PlanStep::ClonePlugin {
+                name: "zsh-autosuggestions".to_string(),
+                repo_url: ZSH_AUTOSUGGESTIONS_REPO.to_string(),
+            },
+        ),
+        task(
+            "Zsh syntax highlighting installation",
+            PlanStep::ClonePlugin {
+                name: "zsh-syntax-highlighting".to_string(),
+                repo_url: ZSH_SYNTAX_HIGHLIGHTING_REPO.to_string(),
+            },
+        ),
+        task(
+            "Zsh user configuration",
+            PlanStep::InstallUserFile {
+                source: ZSHRC_SOURCE.to_string(),
+                file_name: ".zshrc".to_string(),
+            },
+        ),
+        task(
+            "Vim user configuration",
+            PlanStep::InstallUserFile {
+                source: VIMRC_SOURCE.to_string(),
+                file_name: ".vimrc".to_string(),
+            },
+        ),
+        task(
+            "Root configuration",
+            PlanStep::CopyToRoot {
+                items: ROOT_CONFIG_ITEMS
+                    .iter()
+                    .map(|(item, _)| item.to_string())
+                    .collect(),
+            },
+        ),
+        task(
+            "ZRAM swap setup",
+            PlanStep::InstallSystemFile {
+                source: ZRAM_CONFIG_SOURCE.to_string(),
+                dest: ZRAM_CONFIG_PATH.to_string(),
+                unit: Some("systemd-zram-setup@zram0.service".to_string()),
+            },
+        ),
+    ]
+}
@@ -0,0 +1,538 @@
+/*
+ * GLWiz - The ultimate post-installation setup assistant for GNU/Linux popular distros,
+ * streamlining your configuration process with ease and precision.
+ * 
+ * Copyright (C) 2025  Andrew Kushyk
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published
+ * by the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use super::{
+    commands::{command_exists, privilege_command, run_sudo_command},
+    distro::Distro,
+    iptables::rules_path,
+    service::InitSystem,
+    shell::{OMZ_INSTALL_URL, OMZ_REPO},
+    software::install_command,
+    target::Target,
+    zram::config_path,
+};
+use colored::Colorize;
+use std::{
+    ffi::CString,
+    fs::File,
+    net::{TcpStream, ToSocketAddrs},
+    os::fd::AsRawFd,
+    path::Path,
+    process::{Command, Stdio},
+    time::Duration,
+};
+
+/// Commands the setup runs on the host in every mode.
+pub const REQUIRED_COMMANDS: [&str; 5] = ["sh", "cp", "mv", "tee", "install"];
+
+/// Commands the setup downloads with, which the package step may install first.
+pub const DOWNLOAD_COMMANDS: [&str; 2] = ["curl", "git"];
+
+/// The minimum free space, in MiB, required on the target’s root and home file systems.
+pub const MIN_FREE_SPACE_MB: u64 = 1024;
+
+/// Processes that hold the package database while they run.
+pub const PACKAGE_MANAGER_PROCESSES: [&str; 13] = [
+    "pacman",
+    "apt",
+    "apt-get",
+    "dpkg",
+    "unattended-upgr",
+    "dnf",
+    "yum",
+    "rpm",
+    "zypper",
+    "apk",
+    "xbps-install",
+    "emerge",
+    "packagekitd",
+];
+
+/// Lock files that package managers hold with `fcntl` or `flock` while they run: dpkg and APT,
+/// rpm (used by DNF and zypper, in both rpmdb locations) and apk.
+pub const PACKAGE_MANAGER_LOCKS: [&str; 7] = [
+    "/var/lib/dpkg/lock-frontend",
+    "/var/lib/dpkg/lock",
+    "/var/lib/apt/lists/lock",
+    "/var/cache/apt/archives/lock",
+    "/var/lib/rpm/.rpm.lock",
+    "/usr/lib/sysimage/rpm/.rpm.lock",
+    "/lib/apk/db/lock",
+];
+
+/// Files whose presence means a package manager is running: pacman’s lock and zypper’s PID file.
+pub const PACKAGE_MANAGER_LOCK_FILES: [&str; 2] = ["/var/lib/pacman/db.lck", "/run/zypp.pid"];
+
+/// The outcome of a single preflight check.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CheckStatus {
+    /// The check passed.
+    Ok,
+    /// A potential problem that does not stop the setup.
+    Warning,
+    /// A problem that would make the setup fail.
+    Failed,
+}
+
+/// The result of a preflight check.
+///
+/// # Fields
+/// * `name` - What was checked (e.g., `"command git"`).
+/// * `status` - The outcome.
+/// * `detail` - A description of the problem, or of what was found.
+///
+/// # Example
+/// ```
+/// use glwiz::functionality::preflight::{CheckResult, CheckStatus};
+/// let check = CheckResult::new("supported distribution", CheckStatus::Ok, "arch");
+/// assert_eq!(check.status, CheckStatus::Ok);
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct CheckResult {
+    pub name: String,
+    pub status: CheckStatus,
+    pub detail: String,
+}
+
+impl CheckResult {
+    /// Creates a check result.
+    pub fn new(name: &str, status: CheckStatus, detail: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            status,
+            detail: detail.to_string(),
+        }
+    }
+}
+
+/// Checks that the setup can run on a target without changing anything.
+///
+/// The checks cover a supported distribution, the commands the setup runs (including the
+/// package manager inside the target), `sudo` (or `doas`) rights, free disk space, a running package
+/// manager holding its lock, writable destinations, and network access to the hosts Oh My Zsh
+/// and its plugins are downloaded from. Every check runs, so all problems are reported at once.
+///
+/// # Arguments
+/// * `target` - The system to configure.
+/// * `distro` - The distribution given with `--distro`, or `None` to detect it.
+/// * `home_dir` - The user’s home directory, resolved inside the target (e.g., `"/home/user"`).
+/// * `packages` - The packages the setup will install; download tools in this list only warn
+///   when missing.
+///
+/// # Returns
+/// A `Vec<CheckResult>` with one entry per check.
+///
+/// # Example
+/// ```no_run
+/// // Requires sudo privileges and network access.
+/// use glwiz::functionality::{preflight::{print_report, run_preflight}, target::Target};
+/// let results = run_preflight(&Target::host(), None, "/home/user", &["zsh", "git"]);
+/// assert!(print_report(&results));
+/// ```
+///
+/// # See Also
+/// - `print_report`: Prints the results and tells whether the setup may proceed.
+pub fn run_preflight(
+    target: &Target,
+    distro: Option<&Distro>,
+    home_dir: &str,
+    packages: &[&str],
+) -> Vec<CheckResult> {
+    let mut results = Vec::new();
+
+    // Distribution and package manager
+    let distro = distro.cloned().or_else(|| Distro::detect(target));
+    let manager = distro
+        .as_ref()
+        .and_then(|d| install_command(d.family_name()))
+        .map(|(cmd, _)| cmd);
+    results.push(match (&distro, manager) {
+        (Some(distro), Some(_)) => CheckResult::new(
+            "supported distribution",
+            CheckStatus::Ok,
+            &distro.to_string(),
+        ),
+        (Some(distro), None) => CheckResult::new(
+            "supported distribution",
+            CheckStatus::Failed,
+            &format!(
+                "{} is not an Arch, Debian, Fedora, openSUSE, Alpine, Void or Gentoo derivative",
+                distro.name
+            ),
+        ),
+        (None, _) => CheckResult::new(
+            "supported distribution",
+            CheckStatus::Failed,
+            "no os-release or release file found; use --distro",
+        ),
+    });
+    if let Some(manager) = manager {
+        results.push(check_target_command(target, manager));
+    }
+
+    // Commands
+    for command in REQUIRED_COMMANDS {
+        results.push(check_command(command, CheckStatus::Failed, ""));
+    }
+    for command in DOWNLOAD_COMMANDS {
+        if packages.contains(&command) {
+            results.push(check_command(
+                command,
+                CheckStatus::Warning,
+                " (it is in the package list and will be installed first)",
+            ));
+        } else {
+            results.push(check_command(command, CheckStatus::Failed, ""));
+        }
+    }
+    if !target.is_chroot() {
+        results.push(check_command("chsh", CheckStatus::Failed, ""));
+        results.push(check_command("iptables-restore", CheckStatus::Failed, ""));
+    }
+
+    // Privileges
+    results.push(check_privileges());
+
+    // Disk space
+    results.push(check_free_space("/", &target.path("/")));
+    if Path::new(home_dir).exists() {
+        results.push(check_free_space("home", home_dir));
+    }
+
+    // Package manager lock
+    results.push(check_package_manager_idle(target));
+
+    // Destinations
+    let family = distro.as_ref().and_then(|d| d.family);
+    let init = InitSystem::detect(target, family);
+    for path in [rules_path(family), config_path(init)] {
+        results.push(check_parent_dir(&target.path(path)));
+    }
+    results.push(check_parent_dir(&target.path("/root/.zshrc")));
+    results.push(check_writable_dir(home_dir));
+
+    // Network
+    let mut hosts: Vec<String> = [OMZ_INSTALL_URL, OMZ_REPO]
+        .iter()
+        .filter_map(|url| url_host(url))
+        .collect();
+    hosts.dedup();
+    for host in hosts {
+        results.push(check_reachable(&host));
+    }
+
+    results
+}
+
+/// Prints the preflight results, listing every warning and failure.
+///
+/// # Arguments
+/// * `results` - The results of `run_preflight`.
+///
+/// # Returns
+/// * `true` - No check failed; the setup may proceed.
+/// * `false` - At least one check failed.
+///
+/// # Example
+/// ```
+/// use glwiz::functionality::preflight::{print_report, CheckResult, CheckStatus};
+/// let results = vec![
+///     CheckResult::new("command git", CheckStatus::Ok, "/usr/bin/git"),
+///     CheckResult::new("free space on /", CheckStatus::Warning, "900 MiB available"),
+/// ];
+/// assert!(print_report(&results));
+/// ```
+pub fn print_report(results: &[CheckResult]) -> bool {
+    println!("Preflight checks:");
+    for result in results {
+        let status = match result.status {
+            CheckStatus::Ok => "ok".green(),
+            CheckStatus::Warning => "warning".yellow(),
+            CheckStatus::Failed => "failed".red(),
+        };
+        if result.detail.is_empty() {
+            println!("- {}: {}", result.name, status);
+        } else {
+            println!("- {}: {} ({})", result.name, status, result.detail);
+        }
+    }
+
+    let failed = results
+        .iter()
+        .filter(|r| r.status == CheckStatus::Failed)
+        .count();
+    if failed > 0 {
+        eprintln!(
+            "{} {} preflight check(s) failed. Nothing was changed.",
+            "error:".red(),
+            failed
+        );
+    }
+    failed == 0
+}
+
+/// Extracts the host name from an `http(s)` URL.
+///
+/// # Example
+/// ```
+/// use glwiz::functionality::preflight::url_host;
+/// assert_eq!(url_host("https://github.com/ohmyzsh/ohmyzsh.git").as_deref(), Some("github.com"));
+/// assert_eq!(url_host("not a url"), None);
+/// ```
+pub fn url_host(url: &str) -> Option<String> {
+    let rest = url.split_once("://")?.1;
+    let host = rest.split(['/', ':']).next()?;
+    (!host.is_empty()).then(|| host.to_string())
+}
+
+// Checks that a command is available on the host's PATH.
+fn check_command(command: &str, missing: CheckStatus, note: &str) -> CheckResult {
+    let name = format!("command {}", command);
+    if command_exists(command) {
+        CheckResult::new(&name, CheckStatus::Ok, "")
+    } else {
+        CheckResult::new(&name, missing, &format!("not found in PATH{}", note))
+    }
+}
+
+// Checks that a command exists inside the target, where it runs through chroot.
+fn check_target_command(target: &Target, command: &str) -> CheckResult {
+    if !target.is_chroot() {
+        return check_command(command, CheckStatus::Failed, "");
+    }
+    let name = format!("command {} in {}", command, target.get_root());
+    let found = ["/usr/bin", "/usr/sbin", "/bin", "/sbin"]
+        .iter()
+        .any(|dir| Path::new(&target.path(&format!("{}/{}", dir, command))).exists());
+    if found {
+        CheckResult::new(&name, CheckStatus::Ok, "")
+    } else {
+        CheckResult::new(&name, CheckStatus::Failed, "not installed in the target")
+    }
+}
+
+// Checks that sudo (or doas) can be used, prompting for the password once if needed.
+fn check_privileges() -> CheckResult {
+    let command = privilege_command();
+    let name = format!("{} rights", command);
+    if unsafe { libc::geteuid() } == 0 {
+        return CheckResult::new(&name, CheckStatus::Ok, "running as root");
+    }
+    if !command_exists(command) {
+        return CheckResult::new(
+            &name,
+            CheckStatus::Failed,
+            "neither sudo nor doas is installed",
+        );
+    }
+    if run_sudo_command("-n", &["true"]).is_ok() {
+        return CheckResult::new(&name, CheckStatus::Ok, "");
+    }
+    // doas has no way to only validate the credentials, so it runs a no-op instead.
+    let prompt_args: &[&str] = if command == "doas" {
+        &["true"]
+    } else {
+        &["-v"]
+    };
+    let validated = Command::new(command)
+        .args(prompt_args)
+        .stdin(Stdio::inherit())
+        .status()
+        .is_ok_and(|status| status.success());
+    if validated {
+        CheckResult::new(&name, CheckStatus::Ok, "")
+    } else {
+        CheckResult::new(
+            &name,
+            CheckStatus::Failed,
+            &format!("the user cannot run commands with {}", command),
+        )
+    }
+}
+
+// Checks that a file system has at least `MIN_FREE_SPACE_MB` available.
+fn check_free_space(label: &str, path: &str) -> CheckResult {
+    let name = format!("free space on {}", label);
+    let Ok(c_path) = CString::new(path) else {
+        return CheckResult::new(&name, CheckStatus::Warning, "invalid path");
+    };
+    let mut stat: libc::statvfs = unsafe { std::mem::zeroed() };
+    if unsafe { libc::statvfs(c_path.as_ptr(), &mut stat) } != 0 {
+        return CheckResult::new(&name, CheckStatus::Warning, "could not be determined");
+    }
+    let free_mb = stat.f_bavail as u64 * stat.f_frsize as u64 / (1024 * 1024);
+    let detail = format!("{} MiB available", free_mb);
+    if free_mb < MIN_FREE_SPACE_MB {
+        CheckResult::new(
+            &name,
+            CheckStatus::Failed,
+            &format!("{}, {} MiB required", detail, MIN_FREE_SPACE_MB),
+        )
+    } else {
+        CheckResult::new(&name, CheckStatus::Ok, &detail)
+    }
+}
+
+// Checks that no package manager is running and that none of their locks is held.
+fn check_package_manager_idle(target: &Target) -> CheckResult {
+    let name = "package manager lock";
+    let mut unknown: Vec<String> = Vec::new();
+    for path in PACKAGE_MANAGER_LOCK_FILES.map(|p| target.path(p)) {
+        // zypper leaves its PID file behind when it is killed, so the process must be alive.
+        let held = if path.ends_with(".pid") {
+            std::fs::read_to_string(&path)
+                .ok()
+                .and_then(|pid| pid.trim().parse::<u32>().ok())
+                .is_some_and(|pid| Path::new(&format!("/proc/{}", pid)).exists())
+        } else {
+            Path::new(&path).exists()
+        };
+        if held {
+            return CheckResult::new(
+                name,
+                CheckStatus::Failed,
+                &format!("{} exists; another package manager may be running", path),
+            );
+        }
+    }
+    for path in PACKAGE_MANAGER_LOCKS.map(|p| target.path(p)) {
+        if !Path::new(&path).exists() {
+            continue;
+        }
+        match lock_is_held(&path) {
+            Some(true) => {
+                return CheckResult::new(
+                    name,
+                    CheckStatus::Failed,
+                    &format!("{} is locked; another package manager is running", path),
+                );
+            }
+            Some(false) => {}
+            None => unknown.push(path),
+        }
+    }
+
+    let running: Vec<String> = std::fs::read_dir("/proc")
+        .map(|entries| {
+            entries
+                .filter_map(Result::ok)
+                .filter(|e| e.file_name().to_string_lossy().parse::<u32>().is_ok())
+                .filter_map(|e| std::fs::read_to_string(e.path().join("comm")).ok())
+                .map(|comm| comm.trim().to_string())
+                .filter(|comm| PACKAGE_MANAGER_PROCESSES.contains(&comm.as_str()))
+                .collect()
+        })
+        .unwrap_or_default();
+    if !running.is_empty() {
+        CheckResult::new(
+            name,
+            CheckStatus::Failed,
+            &format!("running: {}", running.join(", ")),
+        )
+    } else if !unknown.is_empty() {
+        CheckResult::new(
+            name,
+            CheckStatus::Warning,
+            &format!("could not check {}", unknown.join(", ")),
+        )
+    } else {
+        CheckResult::new(name, CheckStatus::Ok, "")
+    }
+}
+
+// Returns whether a process holds a lock file, testing both `fcntl` record locks (dpkg, rpm)
+// and `flock` locks. Files the current user cannot open are checked with `fuser` through
+// `sudo`. Returns `None` if the lock could not be checked.
+fn lock_is_held(path: &str) -> Option<bool> {
+    let Ok(file) = File::open(path) else {
+        return command_exists("fuser")
+            .then(|| run_sudo_command("-n", &["fuser", "-s", path]).is_ok());
+    };
+    let fd = file.as_raw_fd();
+    let mut record: libc::flock = unsafe { std::mem::zeroed() };
+    record.l_type = libc::F_WRLCK as libc::c_short;
+    record.l_whence = libc::SEEK_SET as libc::c_short;
+    if unsafe { libc::fcntl(fd, libc::F_GETLK, &mut record) } != 0 {
+        return None;
+    }
+    if record.l_type != libc::F_UNLCK as libc::c_short {
+        return Some(true);
+    }
+    if unsafe { libc::flock(fd, libc::LOCK_EX | libc::LOCK_NB) } != 0 {
+        return Some(true);
+    }
+    unsafe { libc::flock(fd, libc::LOCK_UN) };
+    Some(false)
+}
+
+// Checks that the directory a system file is written to exists.
+fn check_parent_dir(path: &str) -> CheckResult {
+    let parent = Path::new(path)
+        .parent()
+        .map(|p| p.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    let name = format!("destination {}", parent);
+    let exists = Path::new(&parent).is_dir() || run_sudo_command("test", &["-d", &parent]).is_ok();
+    if exists {
+        CheckResult::new(&name, CheckStatus::Ok, "")
+    } else {
+        CheckResult::new(&name, CheckStatus::Failed, "directory does not exist")
+    }
+}
+
+// Checks that the user can write to a directory.
+fn check_writable_dir(dir: &str) -> CheckResult {
+    let name = format!("home directory {}", dir);
+    let writable = CString::new(dir)
+        .map(|c| unsafe { libc::access(c.as_ptr(), libc::W_OK) } == 0)
+        .unwrap_or(false);
+    if writable {
+        CheckResult::new(&name, CheckStatus::Ok, "")
+    } else {
+        CheckResult::new(
+            &name,
+            CheckStatus::Failed,
+            "not writable by the current user",
+        )
+    }
+}
+
+// Checks that a host resolves and accepts HTTPS connections.
+fn check_reachable(host: &str) -> CheckResult {
+    let name = format!("network {}", host);
+    let addrs = match (host, 443).to_socket_addrs() {
+        Ok(addrs) => addrs.collect::<Vec<_>>(),
+        Err(e) => {
+            return CheckResult::new(
+                &name,
+                CheckStatus::Failed,
+                &format!("cannot resolve: {}", e),
+            );
+        }
+    };
+    let reachable = addrs
+        .iter()
+        .any(|addr| TcpStream::connect_timeout(addr, Duration::from_secs(5)).is_ok());
+    if reachable {
+        CheckResult::new(&name, CheckStatus::Ok, "")
+    } else {
+        CheckResult::new(&name, CheckStatus::Failed, "port 443 unreachable")
+    }
+}
@@ -0,0 +1,311 @@
+/*
+ * GLWiz - The ultimate post-installation setup assistant for GNU/Linux popular distros,
+ * streamlining your configuration process with ease and precision.
+ * 
+ * Copyright (C) 2025  Andrew Kushyk
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published
+ * by the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use super::{
+    aur::AurHelper,
+    configs::ConfigSources,
+    flatpak::FlatpakScope,
+    package_tuning::PackageTuning,
+    repositories::Repository,
+    shell::set_omz_settings,
+    template::{TEMPLATE_EXTENSION, TemplateVars, is_template},
+};
+use serde::{Deserialize, Serialize};
+use std::{collections::BTreeMap, fs, path::Path};
+
+/// A portable description of a configured machine that glwiz can apply elsewhere.
+///
+/// Profiles are stored as TOML. They are produced by `glwiz snapshot` and applied with
+/// `glwiz run --profile <FILE>`. Every section is optional; whatever a profile leaves out falls
+/// back to glwiz’s defaults (the interactive package prompt and the bundled configuration files).
+///
+/// # Fields
+/// * `packages` - The packages to install.
+/// * `shell` - The login shell and Oh My Zsh settings.
+/// * `files` - The contents of the configuration files glwiz manages.
+/// * `vars` - Values available to templated configuration files, in addition to the system
+///   variables (see `template::system_vars`).
+///
+/// # Example
+/// ```
+/// use glwiz::functionality::profile::Profile;
+/// let profile = Profile::from_toml(r#"
+/// [packages]
+/// native = ["zsh", "git"]
+///
+/// [shell]
+/// omz_theme = "fox"
+/// "#).unwrap();
+/// assert_eq!(profile.packages.native, vec!["zsh", "git"]);
+/// assert_eq!(profile.shell.omz_theme.as_deref(), Some("fox"));
+/// assert!(profile.files.zshrc.is_none());
+/// ```
+///
+/// # See Also
+/// - `snapshot::snapshot_system`: Captures a profile from the running system.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Profile {
+    pub packages: ProfilePackages,
+    pub shell: ProfileShell,
+    pub files: ProfileFiles,
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub vars: BTreeMap<String, toml::Value>,
+}
+
+/// The packages section of a profile.
+///
+/// Each app is installed either natively or as a Flatpak: a name listed in `flatpak` is left
+/// out of the native packages, including the default list and a custom one.
+///
+/// # Fields
+/// * `native` - Packages installed with the distribution’s package manager.
+/// * `flatpak` - Apps installed from Flathub, by app ID (e.g., `"org.gimp.GIMP"`) or by a
+///   package name mapped to one (e.g., `"firefox"`, see `flatpak::resolve_apps`).
+/// * `flatpak_scope` - The Flatpak installation the apps go to, `"system"` or `"user"`.
+/// * `aur` - Packages built from the Arch User Repository, on Arch only.
+/// * `aur_helper` - The AUR helper that builds them, `"paru"` or `"yay"`.
+/// * `repositories` - Optional and third-party repositories to enable before installing, such
+///   as `"multilib"`, `"non-free"` or `"rpmfusion-free"` (see `repositories::Repository`).
+/// * `tuning` - Package manager settings applied before installing (see
+///   `package_tuning::PackageTuning`).
+/// * `debloat` - Preinstalled packages to remove, in addition to the distribution’s default
+///   list with `--debloat` (see `debloat::default_debloat`).
+///
+/// # Example
+/// ```
+/// use glwiz::functionality::{flatpak::FlatpakScope, profile::Profile};
+/// let profile = Profile::from_toml(r#"
+/// [packages]
+/// native = ["zsh"]
+/// flatpak = ["firefox"]
+/// flatpak_scope = "user"
+/// "#).unwrap();
+/// assert_eq!(profile.packages.flatpak, vec!["firefox"]);
+/// assert_eq!(profile.packages.flatpak_scope, FlatpakScope::User);
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ProfilePackages {
+    pub native: Vec<String>,
+    pub flatpak: Vec<String>,
+    pub flatpak_scope: FlatpakScope,
+    pub aur: Vec<String>,
+    pub aur_helper: AurHelper,
+    pub repositories: Vec<Repository>,
+    pub tuning: PackageTuning,
+    pub debloat: Vec<String>,
+}
+
+/// The shell section of a profile.
+///
+/// # Fields
+/// * `login_shell` - The login shell to set for the user and root (e.g., `"/usr/bin/zsh"`).
+/// * `omz_theme` - The Oh My Zsh theme set in `.zshrc`.
+/// * `omz_plugins` - The Oh My Zsh plugins enabled in `.zshrc`.
+/// * `custom_plugins` - The plugins cloned into the Oh My Zsh custom plugins directory.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ProfileShell {
+    pub login_shell: Option<String>,
+    pub omz_theme: Option<String>,
+    pub omz_plugins: Vec<String>,
+    pub custom_plugins: Vec<ZshPlugin>,
+}
+
+/// A Zsh plugin installed from a Git repository.
+///
+/// # Fields
+/// * `name` - The plugin directory name (e.g., `"zsh-autosuggestions"`).
+/// * `repo` - The Git repository URL.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ZshPlugin {
+    pub name: String,
+    pub repo: String,
+}
+
+/// The files section of a profile, holding configuration file contents.
+///
+/// # Fields
+/// * `zshrc` - The contents of `~/.zshrc`.
+/// * `vimrc` - The contents of `~/.vimrc`.
+/// * `iptables` - The iptables rules (as written by `iptables-save`).
+/// * `zram` - The zram-generator configuration.
+/// * `templated` - Whether the contents are templates, rendered with `template::render` before
+///   they are installed.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ProfileFiles {
+    pub zshrc: Option<String>,
+    pub vimrc: Option<String>,
+    pub iptables: Option<String>,
+    pub zram: Option<String>,
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub templated: bool,
+}
+
+impl Profile {
+    /// Parses a profile from TOML.
+    ///
+    /// # Arguments
+    /// * `contents` - The TOML document.
+    ///
+    /// # Returns
+    /// * `Ok(Profile)` - The parsed profile.
+    /// * `Err(String)` - An error message describing the invalid TOML.
+    pub fn from_toml(contents: &str) -> Result<Self, String> {
+        toml::from_str(contents).map_err(|e| format!("Invalid profile: {}", e))
+    }
+
+    /// Reads and parses a profile file.
+    ///
+    /// # Arguments
+    /// * `path` - The path of the profile (e.g., `"profile.toml"`).
+    ///
+    /// # Returns
+    /// * `Ok(Profile)` - The parsed profile.
+    /// * `Err(String)` - An error message if the file cannot be read or is not a valid profile.
+    pub fn load(path: &str) -> Result<Self, String> {
+        let contents =
+            fs::read_to_string(path).map_err(|e| format!("Failed to read {}: {}", path, e))?;
+        Self::from_toml(&contents).map_err(|e| format!("{}: {}", path, e))
+    }
+
+    /// Serializes the profile to TOML.
+    ///
+    /// # Returns
+    /// * `Ok(String)` - The TOML document.
+    /// * `Err(String)` - An error message if serialization fails.
+    ///
+    /// # Example
+    /// ```
+    /// use glwiz::functionality::profile::Profile;
+    /// let mut profile = Profile::default();
+    /// profile.packages.native = vec!["vim".to_string()];
+    /// let toml = profile.to_toml().unwrap();
+    /// assert_eq!(Profile::from_toml(&toml).unwrap(), profile);
+    /// ```
+    pub fn to_toml(&self) -> Result<String, String> {
+        toml::to_string_pretty(self).map_err(|e| format!("Failed to serialize profile: {}", e))
+    }
+
+    /// Adds the profile’s `[vars]` to a set of template variables, overriding existing values.
+    ///
+    /// Strings are used as they are; numbers and booleans are converted to their TOML text.
+    ///
+    /// # Arguments
+    /// * `vars` - The variables to extend.
+    ///
+    /// # Example
+    /// ```
+    /// use glwiz::functionality::{profile::Profile, template::TemplateVars};
+    /// let profile = Profile::from_toml("[vars]\nssh_source = \"10.0.0.0/24\"\nzram_mb = 4096\n").unwrap();
+    /// let mut vars = TemplateVars::new();
+    /// profile.add_template_vars(&mut vars);
+    /// assert_eq!(vars.get("ssh_source"), Some("10.0.0.0/24"));
+    /// assert_eq!(vars.get("zram_mb"), Some("4096"));
+    /// ```
+    pub fn add_template_vars(&self, vars: &mut TemplateVars) {
+        for (name, value) in &self.vars {
+            match value {
+                toml::Value::String(s) => vars.set(name, s),
+                other => vars.set(name, &other.to_string()),
+            }
+        }
+    }
+
+    /// Writes the configuration files carried by the profile to `dir` and returns the sources
+    /// to install from.
+    ///
+    /// Files the profile does not carry keep their bundled defaults. If the profile sets an Oh My
+    /// Zsh theme or plugin list, it is applied to the `.zshrc` being installed. Templated files are
+    /// staged with the `.tmpl` extension so that they are rendered when installed.
+    ///
+    /// # Arguments
+    /// * `dir` - The staging directory, created if missing.
+    ///
+    /// # Returns
+    /// * `Ok(ConfigSources)` - The sources to pass to the setup tasks.
+    /// * `Err(String)` - An error message if a file cannot be read or written.
+    pub fn stage_files(&self, dir: &Path) -> Result<ConfigSources, String> {
+        let mut sources = ConfigSources::default();
+        let shell = &self.shell;
+
+        let zshrc = match &self.files.zshrc {
+            Some(zshrc) => Some(zshrc.clone()),
+            None if shell.omz_theme.is_some() || !shell.omz_plugins.is_empty() => Some(
+                fs::read_to_string(&sources.zshrc)
+                    .map_err(|e| format!("Failed to read {}: {}", sources.zshrc, e))?,
+            ),
+            None => None,
+        }
+        .map(|zshrc| set_omz_settings(&zshrc, shell.omz_theme.as_deref(), &shell.omz_plugins));
+
+        // A `.zshrc` read from the bundled source stays a template if that source is one.
+        let zshrc_templated = match &self.files.zshrc {
+            Some(_) => self.files.templated,
+            None => is_template(&sources.zshrc),
+        };
+        let templated = self.files.templated;
+        let staged = [
+            (
+                zshrc.as_ref(),
+                ".zshrc",
+                zshrc_templated,
+                &mut sources.zshrc,
+            ),
+            (
+                self.files.vimrc.as_ref(),
+                ".vimrc",
+                templated,
+                &mut sources.vimrc,
+            ),
+            (
+                self.files.iptables.as_ref(),
+                "iptables.rules",
+                templated,
+                &mut sources.iptables,
+            ),
+            (
+                self.files.zram.as_ref(),
+                "zram-generator.conf",
+                templated,
+                &mut sources.zram,
+            ),
+        ];
+
+        for (contents, name, templated, source) in staged {
+            if let Some(contents) = contents {
+                fs::create_dir_all(dir)
+                    .map_err(|e| format!("Failed to create {}: {}", dir.display(), e))?;
+                let path = if templated {
+                    dir.join(format!("{}{}", name, TEMPLATE_EXTENSION))
+                } else {
+                    dir.join(name)
+                };
+                fs::write(&path, contents)
+                    .map_err(|e| format!("Failed to write {}: {}", path.display(), e))?;
+                *source = path.to_string_lossy().into_owned();
+            }
+        }
+
+        Ok(sources)
+    }
+}
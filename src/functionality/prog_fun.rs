@@ -18,6 +18,7 @@
  * along with this program.  If not, see <https://www.gnu.org/licenses/>.
  */
 
+use super::bundle::{BUNDLES, Bundle, parse_bundles, print_bundles};
 use colored::Colorize;
 use std::io::{self, Write};
 use std::process::exit;
@@ -164,24 +165,34 @@ pub fn print_setup_status_failed() {
     );
 }
 
-/// Prompts the user to choose between default or custom software lists.
+/// The software list chosen at the installation prompt.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SoftwareChoice {
+    /// The list from `default_sw_package`.
+    Default,
+    /// A list typed by the user (see `set_sw_list`).
+    Custom,
+    /// A combination of package bundles (see `select_bundles`).
+    Bundles,
+}
+
+/// Prompts the user to choose between default, custom or bundled software lists.
 ///
 /// This function interactively asks the user to select a software installation mode in the
-/// "glwiz" project. Entering `0` selects a custom list, while any other number selects the
-/// default list. It loops until valid input is provided, ensuring robust user interaction.
+/// "glwiz" project. Entering `0` selects a custom list, `2` a combination of package bundles,
+/// and any other number the default list. It loops until valid input is provided, ensuring
+/// robust user interaction.
 ///
 /// # Returns
-/// * `true` - The user selected a custom software list.
-/// * `false` - The user selected the default software list.
+/// The `SoftwareChoice` the user made.
 ///
 /// # Example
 /// ```
-/// use glwiz::functionality::prog_fun::check_sw_install_type;
-/// let use_custom = check_sw_install_type();
-/// if use_custom {
-///     println!("User chose custom software list");
-/// } else {
-///     println!("User chose default software list");
+/// use glwiz::functionality::prog_fun::{check_sw_install_type, SoftwareChoice};
+/// match check_sw_install_type() {
+///     SoftwareChoice::Custom => println!("User chose custom software list"),
+///     SoftwareChoice::Bundles => println!("User chose package bundles"),
+///     SoftwareChoice::Default => println!("User chose default software list"),
 /// }
 /// ```
 ///
@@ -189,28 +200,69 @@ pub fn print_setup_status_failed() {
 /// - `read_input`: Used to capture user input.
 /// - `default_sw_package`: Provides the default list if selected.
 /// - `set_sw_list`: Collects the custom list if selected.
-pub fn check_sw_install_type() -> bool {
+/// - `select_bundles`: Collects the bundles if selected.
+pub fn check_sw_install_type() -> SoftwareChoice {
     loop {
         println!(
             "{}",
-            "Enter 0 for a custom software list or any other number for default:".yellow()
+            "Enter 0 for a custom software list, 2 for package bundles or any other number for default:"
+                .yellow()
         );
 
         let input = read_input();
         match input.trim().parse::<i8>() {
             Ok(0) => {
                 println!("{}", "Selected custom software list.".green());
-                return true;
+                return SoftwareChoice::Custom;
+            }
+            Ok(2) => {
+                println!("{}", "Selected package bundles.".green());
+                return SoftwareChoice::Bundles;
             }
             Ok(_) => {
                 println!("{}", "Selected default software list.".green());
-                return false;
+                return SoftwareChoice::Default;
             }
             Err(_) => println!("{}", "Invalid input. Please enter a number.".red()),
         }
     }
 }
 
+/// Prompts the user to combine package bundles.
+///
+/// This function shows every bundle with its contents and asks for the names of the bundles
+/// to install, separated by commas or spaces. It loops until at least one bundle is given and
+/// all names are known.
+///
+/// # Returns
+/// The selected bundles, in the order they were named.
+///
+/// # Example
+/// ```no_run
+/// // Requires an interactive terminal.
+/// use glwiz::functionality::prog_fun::select_bundles;
+/// let bundles = select_bundles();
+/// println!("Selected {} bundles", bundles.len());
+/// ```
+///
+/// # See Also
+/// - `bundle::parse_bundles`: Parses the names typed by the user.
+/// - `check_sw_install_type`: Determines when to call this function.
+pub fn select_bundles() -> Vec<&'static Bundle> {
+    print_bundles(&BUNDLES.iter().collect::<Vec<_>>());
+    loop {
+        println!(
+            "{}",
+            "Enter the bundles to install (comma or space separated):".yellow()
+        );
+        match parse_bundles(&read_input()) {
+            Ok(bundles) if !bundles.is_empty() => return bundles,
+            Ok(_) => println!("{}", "Please enter at least one bundle.".red()),
+            Err(e) => println!("{}", e.red()),
+        }
+    }
+}
+
 /// Reads a line of input from standard input (stdin).
 ///
 /// This function captures a single line of user input in the "glwiz" project, used for
@@ -267,5 +319,5 @@ pub fn read_input() -> String {
 pub fn set_sw_list() -> Vec<String> {
     println!("Enter software packages to install (space-separated):");
     let input = read_input();
-    input.trim().split_whitespace().map(String::from).collect()
+    input.split_whitespace().map(String::from).collect()
 }
@@ -0,0 +1,214 @@
+/*
+ * GLWiz - The ultimate post-installation setup assistant for GNU/Linux popular distros,
+ * streamlining your configuration process with ease and precision.
+ * 
+ * Copyright (C) 2025  Andrew Kushyk
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published
+ * by the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use super::commands::{run_user_command, sh_quote};
+use colored::Colorize;
+use std::{
+    io::{self, IsTerminal, Read, Write},
+    process::{Command, Stdio},
+    thread,
+};
+
+// The directory, relative to the remote user's home, that receives the glwiz binary and the
+// bundled configs. The binary is placed in `bin/` so that the `../configs` paths used by the
+// setup tasks resolve the same way they do locally.
+const REMOTE_DIR: &str = ".cache/glwiz-remote";
+
+/// Parses an inventory file listing the hosts to configure.
+///
+/// The inventory contains one SSH destination per line (e.g., `user@host` or an alias from
+/// `~/.ssh/config`). Blank lines are ignored, and everything after a `#` is treated as a comment.
+///
+/// # Arguments
+/// * `contents` - The contents of the inventory file.
+///
+/// # Returns
+/// A `Vec<String>` with the hosts in the order they appear.
+///
+/// # Example
+/// ```
+/// use glwiz::functionality::remote::parse_inventory;
+/// let hosts = parse_inventory("# lab machines\nadmin@lab1\n\nadmin@lab2  # GPU box\n");
+/// assert_eq!(hosts, vec!["admin@lab1", "admin@lab2"]);
+/// ```
+pub fn parse_inventory(contents: &str) -> Vec<String> {
+    contents
+        .lines()
+        .map(|line| line.split('#').next().unwrap_or("").trim())
+        .filter(|line| !line.is_empty())
+        .map(String::from)
+        .collect()
+}
+
+/// Runs the glwiz setup on a remote host over SSH.
+///
+/// This function copies the running glwiz binary and the bundled `../configs` directory to
+/// `~/.cache/glwiz-remote` on the host with `scp`, then runs the same setup there through `ssh`.
+/// Every argument is shell-quoted before it reaches the remote shell. When stdin is a terminal,
+/// `ssh -t` allocates a TTY on the host so that `sudo` password, overwrite and package prompts
+/// can still be answered. The remote output is streamed as it arrives, prefixed with the host
+/// name at the start of each line, so prompts without a trailing newline are shown too. The
+/// remote host must be able to execute the local binary (same architecture and a compatible
+/// libc).
+///
+/// # Arguments
+/// * `host` - The SSH destination (e.g., `"admin@lab1"`).
+/// * `setup_args` - Arguments passed to the remote `glwiz run` (e.g., `&["--allow-root"]`).
+///
+/// # Returns
+/// * `0` - The remote setup completed successfully.
+/// * `1` - Copying the assets failed, or the remote setup reported a failure.
+///
+/// # Example
+/// ```no_run
+/// // Requires SSH access to the host.
+/// use glwiz::functionality::remote::remote_setup;
+/// let result = remote_setup("admin@lab1", &[]);
+/// assert_eq!(result, 0);
+/// ```
+///
+/// # See Also
+/// - `parse_inventory`: Reads the host list used for several hosts.
+/// - `gnu_linux_remote_setup`: Runs this function for every host and prints a combined report.
+pub fn remote_setup(host: &str, setup_args: &[&str]) -> i8 {
+    let binary = match std::env::current_exe() {
+        Ok(path) => path.to_string_lossy().into_owned(),
+        Err(e) => {
+            eprintln!("{} Failed to locate glwiz binary: {}", "error:".red(), e);
+            return 1;
+        }
+    };
+
+    let prepare = [
+        (
+            "ssh",
+            vec![host.to_string(), format!("mkdir -p {}/bin", REMOTE_DIR)],
+        ),
+        (
+            "scp",
+            vec![binary, format!("{}:{}/bin/glwiz", host, REMOTE_DIR)],
+        ),
+        (
+            "scp",
+            vec![
+                "-r".to_string(),
+                "../configs".to_string(),
+                format!("{}:{}/", host, REMOTE_DIR),
+            ],
+        ),
+    ];
+
+    for (cmd, args) in prepare.iter() {
+        let args: Vec<&str> = args.iter().map(|a| a.as_str()).collect();
+        if let Err(e) = run_user_command(cmd, &args) {
+            eprintln!(
+                "[{}] {} Failed to copy glwiz assets: {}",
+                host,
+                "error:".red(),
+                e
+            );
+            return 1;
+        }
+    }
+
+    let mut remote_cmd = format!("cd {}/bin && ./glwiz run", REMOTE_DIR);
+    for arg in setup_args {
+        remote_cmd.push(' ');
+        remote_cmd.push_str(&sh_quote(arg));
+    }
+
+    let mut ssh_args = Vec::new();
+    if io::stdin().is_terminal() {
+        ssh_args.push("-t");
+    }
+    ssh_args.extend([host, remote_cmd.as_str()]);
+    match stream_command(host, "ssh", &ssh_args) {
+        Ok(true) => 0,
+        Ok(false) => 1,
+        Err(e) => {
+            eprintln!("[{}] {} {}", host, "error:".red(), e);
+            1
+        }
+    }
+}
+
+// Runs a command and forwards its stdout and stderr as they arrive, prefixed with `[label]` at
+// the start of each line.
+//
+// Returns `Ok(true)` if the command exited successfully, `Ok(false)` if it exited with a
+// non-zero status, and `Err(String)` if it could not be spawned or waited for.
+fn stream_command(label: &str, command: &str, args: &[&str]) -> Result<bool, String> {
+    let mut child = Command::new(command)
+        .args(args)
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| format!("Failed to spawn `{}`: {}", command, e))?;
+
+    let stdout = child
+        .stdout
+        .take()
+        .map(|out| forward_output(label, out, false));
+    let stderr = child
+        .stderr
+        .take()
+        .map(|err| forward_output(label, err, true));
+    for handle in [stdout, stderr].into_iter().flatten() {
+        let _ = handle.join();
+    }
+
+    child
+        .wait()
+        .map(|status| status.success())
+        .map_err(|e| format!("Failed to wait for `{}`: {}", command, e))
+}
+
+// Forwards every chunk read from `reader` to stdout (or stderr) without waiting for a newline,
+// so that prompts are shown as soon as they are printed. `[label]` is written before the first
+// byte of each line.
+fn forward_output<R: Read + Send + 'static>(
+    label: &str,
+    mut reader: R,
+    to_stderr: bool,
+) -> thread::JoinHandle<()> {
+    let prefix = format!("[{}] ", label).cyan().to_string();
+    thread::spawn(move || {
+        let mut buf = [0u8; 4096];
+        let mut at_line_start = true;
+        while let Ok(n) = reader.read(&mut buf) {
+            if n == 0 {
+                break;
+            }
+            let mut out: Box<dyn Write> = if to_stderr {
+                Box::new(io::stderr().lock())
+            } else {
+                Box::new(io::stdout().lock())
+            };
+            for chunk in buf[..n].split_inclusive(|&b| b == b'\n') {
+                if at_line_start {
+                    let _ = out.write_all(prefix.as_bytes());
+                }
+                let _ = out.write_all(chunk);
+                at_line_start = chunk.ends_with(b"\n");
+            }
+            let _ = out.flush();
+        }
+    })
+}
@@ -0,0 +1,580 @@
+/*
+ * GLWiz - The ultimate post-installation setup assistant for GNU/Linux popular distros,
+ * streamlining your configuration process with ease and precision.
+ * 
+ * Copyright (C) 2025  Andrew Kushyk
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published
+ * by the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+use super::{
+    atomic::write_file_as_root,
+    distro::{Distro, DistroFamily},
+    package_manager::{PackageManager, for_family},
+    target::Target,
+};
+use colored::Colorize;
+use serde::{Deserialize, Serialize};
+use std::fs;
+
+/// The pacman configuration, holding one section per repository.
+pub const PACMAN_CONF_PATH: &str = "/etc/pacman.conf";
+
+/// The mirror list the official Arch repositories include.
+pub const PACMAN_MIRRORLIST_INCLUDE: &str = "Include = /etc/pacman.d/mirrorlist";
+
+/// The deb822 sources of the Debian archive, as written by the Debian 12 installer.
+pub const DEBIAN_SOURCES_PATH: &str = "/etc/apt/sources.list.d/debian.sources";
+
+/// The one-line-style APT sources list used by older installations.
+pub const SOURCES_LIST_PATH: &str = "/etc/apt/sources.list";
+
+/// The RPM Fusion mirror redirector serving the release packages.
+pub const RPMFUSION_URL: &str = "https://mirrors.rpmfusion.org";
+
+/// The directory holding the RPM signing keys installed by release packages.
+pub const RPM_GPG_KEY_DIR: &str = "/etc/pki/rpm-gpg";
+
+/// An optional or third-party repository that a profile can enable.
+///
+/// # Example
+/// ```
+/// use glwiz::functionality::{distro::DistroFamily, repositories::Repository};
+/// assert_eq!(Repository::NonFreeFirmware.name(), "non-free-firmware");
+/// assert_eq!(Repository::Multilib.family(), DistroFamily::Arch);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Repository {
+    /// RPM Fusion free, with software Fedora does not ship for patent reasons (Fedora).
+    RpmfusionFree,
+    /// RPM Fusion nonfree, with non-free software; requires RPM Fusion free (Fedora).
+    RpmfusionNonfree,
+    /// The 32-bit libraries for x86_64 (Arch).
+    Multilib,
+    /// Free software depending on non-free software (Debian).
+    Contrib,
+    /// Non-free software (Debian).
+    NonFree,
+    /// Non-free firmware (Debian 12 and later).
+    NonFreeFirmware,
+}
+
+impl Repository {
+    /// Returns the repository name used in profiles.
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::RpmfusionFree => "rpmfusion-free",
+            Self::RpmfusionNonfree => "rpmfusion-nonfree",
+            Self::Multilib => "multilib",
+            Self::Contrib => "contrib",
+            Self::NonFree => "non-free",
+            Self::NonFreeFirmware => "non-free-firmware",
+        }
+    }
+
+    /// Returns the distribution family the repository belongs to.
+    pub fn family(self) -> DistroFamily {
+        match self {
+            Self::RpmfusionFree | Self::RpmfusionNonfree => DistroFamily::Fedora,
+            Self::Multilib => DistroFamily::Arch,
+            Self::Contrib | Self::NonFree | Self::NonFreeFirmware => DistroFamily::Debian,
+        }
+    }
+}
+
+/// Enables a repository section in a pacman configuration.
+///
+/// A commented-out section (such as the `#[multilib]` block of the stock `pacman.conf`) is
+/// uncommented together with its settings; a missing section is appended with the official
+/// mirror list.
+///
+/// # Arguments
+/// * `conf` - The contents of `pacman.conf`.
+/// * `name` - The repository name (e.g., `"multilib"`).
+///
+/// # Returns
+/// * `Some(String)` - The updated configuration.
+/// * `None` - The repository is already enabled.
+///
+/// # Example
+/// ```
+/// use glwiz::functionality::repositories::enable_pacman_repo;
+/// let conf = "[extra]\nInclude = /etc/pacman.d/mirrorlist\n\n\
+///             #[multilib-testing]\n#Include = /etc/pacman.d/mirrorlist\n\n\
+///             #[multilib]\n#Include = /etc/pacman.d/mirrorlist\n";
+/// let enabled = enable_pacman_repo(conf, "multilib").unwrap();
+/// assert!(enabled.ends_with("[multilib]\nInclude = /etc/pacman.d/mirrorlist\n"));
+/// assert!(enabled.contains("#[multilib-testing]\n#Include"));
+/// assert_eq!(enable_pacman_repo(&enabled, "multilib"), None);
+/// ```
+pub fn enable_pacman_repo(conf: &str, name: &str) -> Option<String> {
+    let header = format!("[{}]", name);
+    if conf.lines().any(|line| line.trim() == header) {
+        return None;
+    }
+
+    let mut lines: Vec<String> = Vec::new();
+    let mut in_section = false;
+    let mut found = false;
+    for line in conf.lines() {
+        let uncommented = line.trim().trim_start_matches('#').trim();
+        if uncommented.starts_with('[') || line.trim().is_empty() {
+            in_section = false;
+        }
+        if !found && uncommented == header {
+            in_section = true;
+            found = true;
+            lines.push(header.clone());
+        } else if in_section && is_pacman_setting(uncommented) {
+            lines.push(uncommented.to_string());
+        } else {
+            lines.push(line.to_string());
+        }
+    }
+    let mut updated = lines.join("\n");
+    if !found {
+        if !updated.is_empty() {
+            updated.push_str("\n\n");
+        }
+        updated.push_str(&format!("{}\n{}", header, PACMAN_MIRRORLIST_INCLUDE));
+    }
+    updated.push('\n');
+    Some(updated)
+}
+
+// Returns `true` for a repository setting such as `Include = /etc/pacman.d/mirrorlist`.
+fn is_pacman_setting(line: &str) -> bool {
+    line.split_once('=')
+        .is_some_and(|(key, _)| matches!(key.trim(), "Include" | "Server" | "SigLevel" | "Usage"))
+}
+
+// Returns the value of a deb822 field on a line, if the line holds that field.
+fn deb822_field<'a>(line: &'a str, name: &str) -> Option<&'a str> {
+    if line.starts_with('#') || line.starts_with(char::is_whitespace) {
+        return None;
+    }
+    let (key, value) = line.split_once(':')?;
+    key.trim()
+        .eq_ignore_ascii_case(name)
+        .then_some(value.trim())
+}
+
+// Returns `true` for a URI of the Debian archive or one of its mirrors.
+fn is_debian_uri(uri: &str) -> bool {
+    uri.contains("debian.org") || uri.trim_end_matches('/').ends_with("/debian")
+}
+
+/// Adds a component to the Debian archive entries of deb822 APT sources.
+///
+/// Only stanzas whose URIs point to the Debian archive or a Debian mirror are changed, so
+/// third-party sources keep their components.
+///
+/// # Arguments
+/// * `sources` - The contents of a `.sources` file.
+/// * `component` - The component to add (e.g., `"contrib"`).
+///
+/// # Returns
+/// * `Some(String)` - The updated sources.
+/// * `None` - No Debian stanza lacks the component.
+///
+/// # Example
+/// ```
+/// use glwiz::functionality::repositories::add_deb822_component;
+/// let sources = "Types: deb\nURIs: http://deb.debian.org/debian\nSuites: bookworm\n\
+///                Components: main\n\n\
+///                Types: deb\nURIs: https://packages.example.com/apt\nSuites: stable\n\
+///                Components: main\n";
+/// let updated = add_deb822_component(sources, "contrib").unwrap();
+/// assert!(updated.contains("bookworm\nComponents: main contrib\n"));
+/// assert!(updated.ends_with("stable\nComponents: main\n"));
+/// assert_eq!(add_deb822_component(&updated, "contrib"), None);
+/// ```
+pub fn add_deb822_component(sources: &str, component: &str) -> Option<String> {
+    let lines: Vec<&str> = sources.lines().collect();
+    let mut updated: Vec<String> = Vec::new();
+    let mut changed = false;
+    let mut start = 0;
+    while start < lines.len() {
+        let end = lines[start..]
+            .iter()
+            .position(|line| line.trim().is_empty())
+            .map_or(lines.len(), |offset| start + offset + 1);
+        let stanza = &lines[start..end];
+        let is_debian = stanza
+            .iter()
+            .filter_map(|line| deb822_field(line, "URIs"))
+            .any(|uris| uris.split_whitespace().any(is_debian_uri));
+        for line in stanza {
+            match deb822_field(line, "Components") {
+                Some(value) if is_debian && !value.split_whitespace().any(|c| c == component) => {
+                    updated.push(format!("Components: {} {}", value, component));
+                    changed = true;
+                }
+                _ => updated.push(line.to_string()),
+            }
+        }
+        start = end;
+    }
+    changed.then(|| updated.join("\n") + "\n")
+}
+
+/// Converts the Debian archive entries of a one-line-style `sources.list` to deb822 stanzas.
+///
+/// Each active `deb` or `deb-src` line for the Debian archive becomes a stanza; its `arch`,
+/// `signed-by` and `trusted` options are carried over. The converted lines are commented out
+/// in the returned list, and lines with other options or for other archives are kept as they
+/// are.
+///
+/// # Arguments
+/// * `list` - The contents of `sources.list`.
+///
+/// # Returns
+/// * `Some((stanzas, list))` - The deb822 stanzas and the list with the converted lines
+///   commented out.
+/// * `None` - No line was converted.
+///
+/// # Example
+/// ```
+/// use glwiz::functionality::repositories::sources_list_to_deb822;
+/// let list = "deb http://deb.debian.org/debian bookworm main\n\
+///             deb [arch=amd64] https://packages.example.com/apt stable main\n";
+/// let (stanzas, list) = sources_list_to_deb822(list).unwrap();
+/// assert_eq!(
+///     stanzas,
+///     "Types: deb\nURIs: http://deb.debian.org/debian\nSuites: bookworm\nComponents: main\n"
+/// );
+/// assert!(list.starts_with("# Moved to debian.sources by glwiz: deb http"));
+/// assert!(list.ends_with("deb [arch=amd64] https://packages.example.com/apt stable main\n"));
+/// ```
+pub fn sources_list_to_deb822(list: &str) -> Option<(String, String)> {
+    let mut stanzas: Vec<String> = Vec::new();
+    let mut remaining: Vec<String> = Vec::new();
+    for line in list.lines() {
+        match one_line_to_deb822(line) {
+            Some(stanza) => {
+                stanzas.push(stanza);
+                remaining.push(format!("# Moved to debian.sources by glwiz: {}", line));
+            }
+            None => remaining.push(line.to_string()),
+        }
+    }
+    if stanzas.is_empty() {
+        None
+    } else {
+        Some((stanzas.join("\n"), remaining.join("\n") + "\n"))
+    }
+}
+
+// Converts one `deb [options] uri suite components...` line for the Debian archive.
+fn one_line_to_deb822(line: &str) -> Option<String> {
+    let line = line.trim();
+    let (kind, rest) = line.split_once(char::is_whitespace)?;
+    if kind != "deb" && kind != "deb-src" {
+        return None;
+    }
+    let rest = rest.trim_start();
+    let (options, rest) = match rest.strip_prefix('[') {
+        Some(bracketed) => bracketed.split_once(']')?,
+        None => ("", rest),
+    };
+    let mut fields = Vec::new();
+    for option in options.split_whitespace() {
+        let (key, value) = option.split_once('=')?;
+        let field = match key {
+            "arch" => "Architectures",
+            "signed-by" => "Signed-By",
+            "trusted" => "Trusted",
+            _ => return None,
+        };
+        fields.push(format!("{}: {}", field, value.replace(',', " ")));
+    }
+    let mut words = rest.split_whitespace();
+    let uri = words.next().filter(|uri| is_debian_uri(uri))?;
+    let suite = words.next()?;
+    let components: Vec<&str> = words.collect();
+    if components.is_empty() {
+        return None;
+    }
+    let mut stanza = format!(
+        "Types: {}\nURIs: {}\nSuites: {}\nComponents: {}\n",
+        kind,
+        uri,
+        suite,
+        components.join(" ")
+    );
+    for field in fields {
+        stanza.push_str(&field);
+        stanza.push('\n');
+    }
+    Some(stanza)
+}
+
+/// Returns the URL of an RPM Fusion release package.
+///
+/// # Arguments
+/// * `section` - `"free"` or `"nonfree"`.
+/// * `distro` - The distribution; only Fedora is supported.
+///
+/// # Returns
+/// * `Some(String)` - The URL of the `.noarch.rpm` release package.
+/// * `None` - RPM Fusion has no release package for the distribution or its version is unknown.
+///
+/// # Example
+/// ```
+/// use glwiz::functionality::{distro::Distro, repositories::rpmfusion_release_url};
+/// let fedora = Distro::from_os_release("ID=fedora\nVERSION_ID=40\n").unwrap();
+/// assert_eq!(
+///     rpmfusion_release_url("free", &fedora).unwrap(),
+///     "https://mirrors.rpmfusion.org/free/fedora/rpmfusion-free-release-40.noarch.rpm"
+/// );
+/// let nobara = Distro::from_os_release("ID=nobara\nVERSION_ID=40\n").unwrap();
+/// assert_eq!(rpmfusion_release_url("free", &nobara), None);
+/// ```
+pub fn rpmfusion_release_url(section: &str, distro: &Distro) -> Option<String> {
+    let version = distro.version_id.as_deref()?;
+    if distro.id != "fedora" {
+        return None;
+    }
+    Some(format!(
+        "{}/{}/fedora/rpmfusion-{}-release-{}.noarch.rpm",
+        RPMFUSION_URL, section, section, version
+    ))
+}
+
+// Enables an RPM Fusion section by installing its release package and importing the signing
+// keys it ships. Returns `false` if the section was already enabled.
+fn enable_rpmfusion(
+    section: &str,
+    distro: &Distro,
+    manager: &dyn PackageManager,
+    target: &Target,
+) -> Result<bool, String> {
+    let release = format!("rpmfusion-{}-release", section);
+    if manager.is_installed(&release)? {
+        return Ok(false);
+    }
+    let url = rpmfusion_release_url(section, distro)
+        .ok_or_else(|| format!("RPM Fusion is not available for {}", distro))?;
+    manager.install(&[&url])?;
+
+    let prefix = format!("RPM-GPG-KEY-rpmfusion-{}-", section);
+    let keys = fs::read_dir(target.path(RPM_GPG_KEY_DIR))
+        .map_err(|e| format!("Failed to read {}: {}", RPM_GPG_KEY_DIR, e))?;
+    for key in keys.filter_map(|key| key.ok()) {
+        let name = key.file_name().to_string_lossy().into_owned();
+        if name.starts_with(&prefix) {
+            target.run_command(
+                "rpm",
+                &["--import", &format!("{}/{}", RPM_GPG_KEY_DIR, name)],
+            )?;
+        }
+    }
+    Ok(true)
+}
+
+// Enables the multilib repository in the target’s pacman.conf. Returns `false` if it was
+// already enabled.
+fn enable_multilib(target: &Target) -> Result<bool, String> {
+    if std::env::consts::ARCH != "x86_64" {
+        return Err("multilib is only available on x86_64".to_string());
+    }
+    let path = target.path(PACMAN_CONF_PATH);
+    let conf = fs::read_to_string(&path).map_err(|e| format!("Failed to read {}: {}", path, e))?;
+    match enable_pacman_repo(&conf, Repository::Multilib.name()) {
+        Some(updated) => write_file_as_root(&path, &updated).map(|()| true),
+        None => Ok(false),
+    }
+}
+
+// Adds Debian archive components to the deb822 sources, converting the Debian entries of a
+// one-line sources.list first. Returns whether each component was newly enabled.
+fn enable_debian_components(components: &[&str], target: &Target) -> Result<Vec<bool>, String> {
+    let sources_path = target.path(DEBIAN_SOURCES_PATH);
+    let list_path = target.path(SOURCES_LIST_PATH);
+    let existing = fs::read_to_string(&sources_path).unwrap_or_default();
+    let converted = fs::read_to_string(&list_path)
+        .ok()
+        .and_then(|list| sources_list_to_deb822(&list));
+
+    let mut sources = existing.clone();
+    if let Some((stanzas, _)) = &converted {
+        if !sources.is_empty() {
+            sources.push('\n');
+        }
+        sources.push_str(stanzas);
+    }
+    if !sources
+        .lines()
+        .filter_map(|line| deb822_field(line, "URIs"))
+        .any(|uris| uris.split_whitespace().any(is_debian_uri))
+    {
+        return Err("no Debian archive sources found".to_string());
+    }
+
+    let mut enabled = Vec::new();
+    for component in components {
+        match add_deb822_component(&sources, component) {
+            Some(updated) => {
+                sources = updated;
+                enabled.push(true);
+            }
+            None => enabled.push(false),
+        }
+    }
+    if sources != existing {
+        write_file_as_root(&sources_path, &sources)?;
+    }
+    if let Some((_, list)) = converted {
+        write_file_as_root(&list_path, &list)?;
+    }
+    Ok(enabled)
+}
+
+/// Enables optional and third-party repositories.
+///
+/// This function enables the repositories a profile opts into, for the distribution being
+/// configured:
+/// - `multilib` on Arch, by uncommenting (or adding) its section in `/etc/pacman.conf`.
+/// - `contrib`, `non-free` and `non-free-firmware` on Debian, by adding the components to the
+///   Debian archive entries of `/etc/apt/sources.list.d/debian.sources`. Debian entries of a
+///   one-line `/etc/apt/sources.list` are converted to that deb822 file first and commented
+///   out.
+/// - `rpmfusion-free` and `rpmfusion-nonfree` on Fedora, by installing the RPM Fusion release
+///   packages and importing the signing keys they ship.
+///   nonfree requires free, which is then enabled as well.
+///
+/// Files are written atomically and backed up, so `glwiz undo` restores them. Once a
+/// repository was enabled, the package index is refreshed; on Arch this is a full `pacman -Syu`,
+/// since Arch does not support partial upgrades. Repositories of other distributions are
+/// skipped with a warning, so that one profile can serve several distributions. The status of
+/// every repository is printed.
+///
+/// # Arguments
+/// * `repositories` - The repositories to enable.
+/// * `distro` - The distribution being configured.
+/// * `target` - The system whose repositories are enabled.
+///
+/// # Returns
+/// * `0` - Every repository for this distribution is enabled.
+/// * `1` - A repository could not be enabled, or the index refresh failed.
+///
+/// # Example
+/// ```no_run
+/// // Requires a Debian system and sudo privileges.
+/// use glwiz::functionality::{
+///     distro::Distro,
+///     repositories::{repositories_setup, Repository},
+///     target::Target,
+/// };
+/// let target = Target::host();
+/// let distro = Distro::detect(&target).unwrap();
+/// let result = repositories_setup(&[Repository::Contrib, Repository::NonFree], &distro, &target);
+/// assert_eq!(result, 0);
+/// ```
+///
+/// # See Also
+/// - `profile::ProfilePackages`: Lists the repositories to enable.
+/// - `software::software_setup`: Installs packages from the enabled repositories.
+pub fn repositories_setup(repositories: &[Repository], distro: &Distro, target: &Target) -> i8 {
+    let Some(family) = distro.family else {
+        eprintln!("{} Unsupported distribution: {}", "error:".red(), distro);
+        return 1;
+    };
+
+    let mut wanted: Vec<Repository> = Vec::new();
+    for repository in repositories {
+        if repository.family() != family {
+            eprintln!(
+                "{} {} is not available on {}, skipping.",
+                "warning:".yellow(),
+                repository.name(),
+                distro
+            );
+        } else if !wanted.contains(repository) {
+            wanted.push(*repository);
+        }
+    }
+    if wanted.contains(&Repository::RpmfusionNonfree)
+        && !wanted.contains(&Repository::RpmfusionFree)
+    {
+        println!("rpmfusion-nonfree requires rpmfusion-free, which is enabled as well.");
+        wanted.insert(0, Repository::RpmfusionFree);
+    }
+    if wanted.is_empty() {
+        return 0;
+    }
+
+    let manager = for_family(family, target);
+    let mut results: Vec<(Repository, Result<bool, String>)> = Vec::new();
+    let components: Vec<Repository> = wanted
+        .iter()
+        .copied()
+        .filter(|repository| repository.family() == DistroFamily::Debian)
+        .collect();
+    if !components.is_empty() {
+        let names: Vec<&str> = components
+            .iter()
+            .map(|repository| repository.name())
+            .collect();
+        if distro.id != "debian" {
+            let error = format!("only the Debian archive has {}", names.join(", "));
+            results.extend(components.iter().map(|c| (*c, Err(error.clone()))));
+        } else {
+            match enable_debian_components(&names, target) {
+                Ok(enabled) => {
+                    results.extend(components.iter().copied().zip(enabled.into_iter().map(Ok)))
+                }
+                Err(e) => results.extend(components.iter().map(|c| (*c, Err(e.clone())))),
+            }
+        }
+    }
+    for repository in &wanted {
+        let result = match repository {
+            Repository::Multilib => enable_multilib(target),
+            Repository::RpmfusionFree => enable_rpmfusion("free", distro, manager.as_ref(), target),
+            Repository::RpmfusionNonfree => {
+                enable_rpmfusion("nonfree", distro, manager.as_ref(), target)
+            }
+            _ => continue,
+        };
+        results.push((*repository, result));
+    }
+
+    let mut status = 0;
+    for (repository, result) in &results {
+        match result {
+            Ok(true) => println!("{}: {}", repository.name(), "enabled".green()),
+            Ok(false) => println!("{}: {}", repository.name(), "already enabled".green()),
+            Err(e) => {
+                eprintln!(
+                    "{} Cannot enable {}: {}",
+                    "error:".red(),
+                    repository.name(),
+                    e
+                );
+                status = 1;
+            }
+        }
+    }
+
+    // On Arch this is a full upgrade (see `PackageManager::refresh_index`).
+    if results.iter().any(|(_, result)| matches!(result, Ok(true))) {
+        println!("Refreshing the package index with {}...", manager.name());
+        if let Err(e) = manager.refresh_index() {
+            eprintln!("{} Cannot refresh the package index: {}", "error:".red(), e);
+            status = 1;
+        }
+    }
+    status
+}
@@ -0,0 +1,174 @@
+/*
+ * GLWiz - The ultimate post-installation setup assistant for GNU/Linux popular distros,
+ * streamlining your configuration process with ease and precision.
+ * 
+ * Copyright (C) 2025  Andrew Kushyk
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published
+ * by the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use super::{distro::DistroFamily, target::Target};
+use std::{fmt, fs, path::Path};
+
+/// The locations of the OpenRC service runner, one of which exists on OpenRC systems.
+pub const OPENRC_RUN_PATHS: [&str; 2] = ["/sbin/openrc-run", "/usr/sbin/openrc-run"];
+
+/// The directory holding the runit service definitions on Void Linux.
+pub const RUNIT_SERVICES_DIR: &str = "/etc/sv";
+
+/// The runit service directory of a running Void Linux system.
+pub const RUNIT_RUNSVDIR: &str = "/var/service";
+
+/// The runit service directory of the default runlevel, used for a mounted root where
+/// `/var/service` does not point anywhere yet.
+pub const RUNIT_DEFAULT_RUNSVDIR: &str = "/etc/runit/runsvdir/default";
+
+/// The link to the Portage profile a Gentoo system is built with.
+pub const PORTAGE_PROFILE_PATH: &str = "/etc/portage/make.profile";
+
+/// The init systems glwiz can enable services with.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InitSystem {
+    /// systemd (`systemctl enable`).
+    Systemd,
+    /// OpenRC (`rc-update add`), as used by Alpine Linux and Gentoo.
+    OpenRc,
+    /// runit (service symlinks in `/var/service`), as used by Void Linux.
+    Runit,
+}
+
+impl InitSystem {
+    /// Detects the init system of a target.
+    ///
+    /// A running host booted with systemd is recognized by `/run/systemd/system`. Otherwise the
+    /// target is checked for OpenRC’s service runner and runit’s default runlevel directory,
+    /// and if neither is found, the distribution family’s default is used. On Gentoo, where
+    /// both OpenRC and systemd are common, the Portage profile decides (see
+    /// `from_portage_profile`).
+    ///
+    /// # Arguments
+    /// * `target` - The system whose init system is detected.
+    /// * `family` - The distribution family, if known.
+    ///
+    /// # Example
+    /// ```
+    /// use glwiz::functionality::{distro::DistroFamily, service::InitSystem, target::Target};
+    /// let root = std::env::temp_dir();
+    /// let target = Target::new(root.to_str().unwrap()).unwrap();
+    /// assert_eq!(InitSystem::detect(&target, Some(DistroFamily::Alpine)), InitSystem::OpenRc);
+    /// assert_eq!(InitSystem::detect(&target, Some(DistroFamily::Void)), InitSystem::Runit);
+    /// assert_eq!(InitSystem::detect(&target, Some(DistroFamily::Arch)), InitSystem::Systemd);
+    /// ```
+    pub fn detect(target: &Target, family: Option<DistroFamily>) -> Self {
+        let profile = (family == Some(DistroFamily::Gentoo))
+            .then(|| fs::read_link(target.path(PORTAGE_PROFILE_PATH)).ok())
+            .flatten();
+        if !target.is_chroot() && Path::new("/run/systemd/system").is_dir() {
+            Self::Systemd
+        } else if let Some(profile) = profile {
+            Self::from_portage_profile(&profile.to_string_lossy())
+        } else if OPENRC_RUN_PATHS
+            .iter()
+            .any(|path| Path::new(&target.path(path)).exists())
+        {
+            Self::OpenRc
+        } else if Path::new(&target.path(RUNIT_DEFAULT_RUNSVDIR)).is_dir() {
+            Self::Runit
+        } else {
+            family.map_or(Self::Systemd, |f| f.default_init_system())
+        }
+    }
+
+    /// Returns the init system a Gentoo system is built for, given the target of its
+    /// `/etc/portage/make.profile` link. systemd profiles contain a `systemd` component; all
+    /// others use OpenRC.
+    ///
+    /// # Example
+    /// ```
+    /// use glwiz::functionality::service::InitSystem;
+    /// assert_eq!(
+    ///     InitSystem::from_portage_profile("../../var/db/repos/gentoo/profiles/default/linux/amd64/23.0/systemd"),
+    ///     InitSystem::Systemd
+    /// );
+    /// assert_eq!(
+    ///     InitSystem::from_portage_profile("/var/db/repos/gentoo/profiles/default/linux/amd64/23.0/desktop"),
+    ///     InitSystem::OpenRc
+    /// );
+    /// ```
+    pub fn from_portage_profile(profile: &str) -> Self {
+        if profile.split('/').any(|component| component == "systemd") {
+            Self::Systemd
+        } else {
+            Self::OpenRc
+        }
+    }
+
+    /// Returns the init system’s name (`"systemd"`, `"openrc"` or `"runit"`).
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Systemd => "systemd",
+            Self::OpenRc => "openrc",
+            Self::Runit => "runit",
+        }
+    }
+
+    /// Enables a service so that it starts at boot.
+    ///
+    /// OpenRC services are added to the `default` runlevel. runit services are linked from
+    /// `/etc/sv` into `/var/service`, or into the default runlevel directory for a mounted root.
+    /// The command runs inside the target, so services can be enabled in a mounted root before
+    /// it is booted.
+    ///
+    /// # Arguments
+    /// * `service` - The service name (e.g., `"iptables"`).
+    /// * `target` - The system the service is enabled on.
+    ///
+    /// # Returns
+    /// * `Ok(())` - The service is enabled.
+    /// * `Err(String)` - An error message if the command failed.
+    ///
+    /// # Example
+    /// ```no_run
+    /// // Requires an OpenRC system and root privileges.
+    /// use glwiz::functionality::{service::InitSystem, target::Target};
+    /// InitSystem::OpenRc.enable("iptables", &Target::host()).unwrap();
+    /// ```
+    pub fn enable(&self, service: &str, target: &Target) -> Result<(), String> {
+        match self {
+            Self::Systemd => target.run_command("systemctl", &["enable", service]),
+            Self::OpenRc => target.run_command("rc-update", &["add", service]),
+            Self::Runit => {
+                let runsvdir = if target.is_chroot() {
+                    RUNIT_DEFAULT_RUNSVDIR
+                } else {
+                    RUNIT_RUNSVDIR
+                };
+                target.run_command(
+                    "ln",
+                    &[
+                        "-sfn",
+                        &format!("{}/{}", RUNIT_SERVICES_DIR, service),
+                        &format!("{}/{}", runsvdir, service),
+                    ],
+                )
+            }
+        }
+    }
+}
+
+impl fmt::Display for InitSystem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
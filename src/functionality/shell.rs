@@ -18,58 +18,344 @@
  * along with this program.  If not, see <https://www.gnu.org/licenses/>.
  */
 
+use super::atomic::write_file_as_root;
 use super::commands::{run_sudo_command, run_user_command};
+use super::target::Target;
 use colored::Colorize;
 use std::process::{Command, Stdio};
 
-/// Changes the default shell to Zsh for a specified user.
+/// The login shell glwiz sets for the user and for root, unless the target’s `/etc/shells`
+/// lists Zsh elsewhere (see `zsh_path`).
+pub const ZSH_PATH: &str = "/usr/bin/zsh";
+
+/// The official Oh My Zsh installation script.
+pub const OMZ_INSTALL_URL: &str =
+    "https://raw.githubusercontent.com/ohmyzsh/ohmyzsh/master/tools/install.sh";
+
+/// The Git repository of Oh My Zsh, cloned by the installation script.
+pub const OMZ_REPO: &str = "https://github.com/ohmyzsh/ohmyzsh.git";
+
+/// The Git repository of the Zsh Autosuggestions plugin.
+pub const ZSH_AUTOSUGGESTIONS_REPO: &str = "https://github.com/zsh-users/zsh-autosuggestions";
+
+/// The Git repository of the Zsh Syntax Highlighting plugin.
+pub const ZSH_SYNTAX_HIGHLIGHTING_REPO: &str =
+    "https://github.com/zsh-users/zsh-syntax-highlighting.git";
+
+/// Changes the default shell for a specified user, normally to Zsh (`ZSH_PATH`).
 ///
-/// This function sets Zsh as the default shell for a user by executing the `chsh` command with
+/// This function sets the default shell for a user by executing the `chsh` command with
 /// `sudo` privileges. It is part of the "glwiz" project’s post-installation setup to provide
 /// an enhanced shell experience. The function logs success or failure and is used for both
-/// regular users and the root user.
+/// regular users and the root user. For a mounted root, `chsh` would change the host’s account,
+/// so the login shell is rewritten directly in the target’s `/etc/passwd` instead.
 ///
 /// # Arguments
-/// * `name` - The username for which to set Zsh as the default shell (e.g., `"user"`, `"root"`).
+/// * `name` - The username for which to set the default shell (e.g., `"user"`, `"root"`).
+/// * `shell` - The login shell to set (e.g., `ZSH_PATH`, or the shell recorded in a profile).
+/// * `target` - The system whose account database is modified.
 ///
 /// # Returns
-/// * `0` - The shell was successfully changed to Zsh.
+/// * `0` - The shell was successfully changed.
 /// * `1` - An error occurred, such as a failed `chsh` command or invalid username.
 ///
 /// # Errors
 /// Returns `1` if:
 /// - The `chsh` command fails due to permissions, invalid shell path, or non-existent user.
 /// - The `sudo` execution encounters an error (e.g., `sudo` not installed).
+/// - The user has no entry in the target’s `/etc/passwd`.
 ///
 /// # Example
 /// ```
-/// use glwiz::functionality::shell::change_def_shell;
-/// let result = change_def_shell("user");
+/// use glwiz::functionality::{shell::{change_def_shell, ZSH_PATH}, target::Target};
+/// let result = change_def_shell("user", ZSH_PATH, &Target::host());
 /// assert_eq!(result, 0); // Zsh set successfully for user
 /// ```
 ///
 /// # See Also
 /// - `commands::run_sudo_command`: Used to execute `chsh` with `sudo`.
 /// - `configs::user_config_setup`: Configures Zsh settings after shell change.
-pub fn change_def_shell(name: &str) -> i8 {
-    match run_sudo_command("chsh", &["-s", "/usr/bin/zsh", name]) {
+/// - `set_passwd_shell`: Rewrites the login shell for a mounted root.
+pub fn change_def_shell(name: &str, shell: &str, target: &Target) -> i8 {
+    let result = if target.is_chroot() {
+        let passwd_path = target.path("/etc/passwd");
+        std::fs::read_to_string(&passwd_path)
+            .map_err(|e| format!("Failed to read {}: {}", passwd_path, e))
+            .and_then(|passwd| set_passwd_shell(&passwd, name, shell))
+            .and_then(|passwd| write_file_as_root(&passwd_path, &passwd))
+    } else {
+        run_sudo_command("chsh", &["-s", shell, name])
+    };
+
+    match result {
         Ok(_) => {
-            println!("Zsh set for {}.", name.green());
+            println!("{} set for {}.", shell, name.green());
             0
         }
         Err(e) => {
-            eprintln!("{} Failed to set Zsh for {}: {}", "error:".red(), name, e);
+            eprintln!(
+                "{} Failed to set {} for {}: {}",
+                "error:".red(),
+                shell,
+                name,
+                e
+            );
             1
         }
     }
 }
 
+/// Replaces a user’s login shell in the contents of an `/etc/passwd` file.
+///
+/// This function rewrites the seventh field of the entry for `name`, leaving every other line
+/// untouched. It is used by `change_def_shell` when configuring a mounted root, where running
+/// `chsh` on the host is not an option.
+///
+/// # Arguments
+/// * `passwd` - The contents of an `/etc/passwd` file.
+/// * `name` - The username whose login shell is replaced (e.g., `"root"`).
+/// * `shell` - The new login shell (e.g., `"/usr/bin/zsh"`).
+///
+/// # Returns
+/// * `Ok(String)` - The updated file contents.
+/// * `Err(String)` - An error message if `name` has no valid entry.
+///
+/// # Example
+/// ```
+/// use glwiz::functionality::shell::set_passwd_shell;
+/// let passwd = "root:x:0:0::/root:/bin/bash\nuser:x:1000:1000::/home/user:/bin/bash\n";
+/// let updated = set_passwd_shell(passwd, "user", "/usr/bin/zsh").unwrap();
+/// assert_eq!(
+///     updated,
+///     "root:x:0:0::/root:/bin/bash\nuser:x:1000:1000::/home/user:/usr/bin/zsh\n"
+/// );
+/// assert!(set_passwd_shell(passwd, "nobody", "/usr/bin/zsh").is_err());
+/// ```
+pub fn set_passwd_shell(passwd: &str, name: &str, shell: &str) -> Result<String, String> {
+    let mut found = false;
+    let lines: Vec<String> = passwd
+        .lines()
+        .map(|line| {
+            let mut fields: Vec<&str> = line.split(':').collect();
+            if fields.len() == 7 && fields[0] == name {
+                found = true;
+                fields[6] = shell;
+                fields.join(":")
+            } else {
+                line.to_string()
+            }
+        })
+        .collect();
+
+    if !found {
+        return Err(format!("User {} not found in /etc/passwd", name));
+    }
+
+    let mut updated = lines.join("\n");
+    if passwd.ends_with('\n') {
+        updated.push('\n');
+    }
+    Ok(updated)
+}
+
+/// Returns a user’s login shell from the contents of an `/etc/passwd` file.
+///
+/// # Arguments
+/// * `passwd` - The contents of an `/etc/passwd` file.
+/// * `name` - The username to look up (e.g., `"user"`).
+///
+/// # Returns
+/// * `Some(String)` - The login shell of the user.
+/// * `None` - The user has no valid entry.
+///
+/// # Example
+/// ```
+/// use glwiz::functionality::shell::passwd_shell;
+/// let passwd = "user:x:1000:1000::/home/user:/usr/bin/zsh\n";
+/// assert_eq!(passwd_shell(passwd, "user"), Some("/usr/bin/zsh".to_string()));
+/// assert_eq!(passwd_shell(passwd, "root"), None);
+/// ```
+pub fn passwd_shell(passwd: &str, name: &str) -> Option<String> {
+    passwd.lines().find_map(|line| {
+        let fields: Vec<&str> = line.split(':').collect();
+        (fields.len() == 7 && fields[0] == name).then(|| fields[6].to_string())
+    })
+}
+
+/// Returns the path of a shell from the contents of an `/etc/shells` file.
+///
+/// # Arguments
+/// * `shells` - The contents of an `/etc/shells` file.
+/// * `name` - The shell’s executable name (e.g., `"zsh"`).
+///
+/// # Returns
+/// * `Some(String)` - The first listed path ending in `/name`.
+/// * `None` - The shell is not listed.
+///
+/// # Example
+/// ```
+/// use glwiz::functionality::shell::listed_shell;
+/// let shells = "# /etc/shells\n/bin/bash\n/bin/zsh\n/usr/bin/zsh\n";
+/// assert_eq!(listed_shell(shells, "zsh"), Some("/bin/zsh".to_string()));
+/// assert_eq!(listed_shell(shells, "fish"), None);
+/// ```
+pub fn listed_shell(shells: &str, name: &str) -> Option<String> {
+    shells
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.starts_with('#'))
+        .find(|line| line.rsplit('/').next() == Some(name) && line.starts_with('/'))
+        .map(String::from)
+}
+
+/// Returns the path Zsh is registered under in the target’s `/etc/shells`.
+///
+/// `chsh` only accepts shells listed in `/etc/shells`, and distributions differ in the path
+/// they register: openSUSE Leap lists `/bin/zsh`, while most others list `/usr/bin/zsh`. The
+/// file is read after the packages are installed, so it includes Zsh once it is installed.
+///
+/// # Arguments
+/// * `target` - The system whose `/etc/shells` is read.
+///
+/// # Returns
+/// The listed Zsh path, or `ZSH_PATH` if the file cannot be read or does not list Zsh.
+///
+/// # Example
+/// ```
+/// use glwiz::functionality::{shell::zsh_path, target::Target};
+/// assert!(zsh_path(&Target::host()).ends_with("/zsh"));
+/// ```
+pub fn zsh_path(target: &Target) -> String {
+    std::fs::read_to_string(target.path("/etc/shells"))
+        .ok()
+        .and_then(|shells| listed_shell(&shells, "zsh"))
+        .unwrap_or_else(|| ZSH_PATH.to_string())
+}
+
+/// Reads the Oh My Zsh theme and enabled plugins from the contents of a `.zshrc` file.
+///
+/// Only uncommented `ZSH_THEME=` and `plugins=(...)` assignments are considered; the plugin
+/// list may span several lines.
+///
+/// # Arguments
+/// * `zshrc` - The contents of a `.zshrc` file.
+///
+/// # Returns
+/// A tuple of the theme, if set, and the enabled plugins.
+///
+/// # Example
+/// ```
+/// use glwiz::functionality::shell::omz_settings;
+/// let zshrc = "# ZSH_THEME=\"robbyrussell\"\nZSH_THEME=\"fox\"\nplugins=(git\n  zsh-autosuggestions)\n";
+/// let (theme, plugins) = omz_settings(zshrc);
+/// assert_eq!(theme.as_deref(), Some("fox"));
+/// assert_eq!(plugins, vec!["git", "zsh-autosuggestions"]);
+/// ```
+pub fn omz_settings(zshrc: &str) -> (Option<String>, Vec<String>) {
+    let mut theme = None;
+    let mut plugins = Vec::new();
+    let mut in_plugins = false;
+
+    for line in zshrc.lines() {
+        let line = line.trim();
+        if in_plugins {
+            let (list, closed) = match line.split_once(')') {
+                Some((list, _)) => (list, true),
+                None => (line, false),
+            };
+            plugins.extend(list.split_whitespace().map(String::from));
+            in_plugins = !closed;
+        } else if let Some(value) = line.strip_prefix("ZSH_THEME=") {
+            theme = Some(value.trim_matches(|c| c == '"' || c == '\'').to_string());
+        } else if let Some(rest) = line.strip_prefix("plugins=(") {
+            plugins.clear();
+            let (list, closed) = match rest.split_once(')') {
+                Some((list, _)) => (list, true),
+                None => (rest, false),
+            };
+            plugins.extend(list.split_whitespace().map(String::from));
+            in_plugins = !closed;
+        }
+    }
+
+    (theme, plugins)
+}
+
+/// Sets the Oh My Zsh theme and enabled plugins in the contents of a `.zshrc` file.
+///
+/// The existing uncommented `ZSH_THEME=` and `plugins=(...)` assignments are replaced, and
+/// missing ones are appended. Passing `None` or an empty plugin list leaves that setting as is.
+///
+/// # Arguments
+/// * `zshrc` - The contents of a `.zshrc` file.
+/// * `theme` - The theme to set (e.g., `Some("fox")`).
+/// * `plugins` - The plugins to enable.
+///
+/// # Returns
+/// The updated file contents.
+///
+/// # Example
+/// ```
+/// use glwiz::functionality::shell::{omz_settings, set_omz_settings};
+/// let zshrc = "ZSH_THEME=\"fox\"\nplugins=(git)\n";
+/// let updated = set_omz_settings(zshrc, Some("agnoster"), &["git".to_string(), "z".to_string()]);
+/// assert_eq!(updated, "ZSH_THEME=\"agnoster\"\nplugins=(git z)\n");
+/// assert_eq!(omz_settings(&updated).1, vec!["git", "z"]);
+/// ```
+pub fn set_omz_settings(zshrc: &str, theme: Option<&str>, plugins: &[String]) -> String {
+    let mut lines: Vec<String> = Vec::new();
+    let mut theme_set = theme.is_none();
+    let mut plugins_set = plugins.is_empty();
+    let mut skipping_plugins = false;
+
+    for line in zshrc.lines() {
+        let trimmed = line.trim();
+        if skipping_plugins {
+            skipping_plugins = !trimmed.contains(')');
+            continue;
+        }
+        match theme {
+            Some(theme) if trimmed.starts_with("ZSH_THEME=") => {
+                lines.push(format!("ZSH_THEME=\"{}\"", theme));
+                theme_set = true;
+                continue;
+            }
+            _ => {}
+        }
+        if !plugins.is_empty() && trimmed.starts_with("plugins=(") {
+            lines.push(format!("plugins=({})", plugins.join(" ")));
+            plugins_set = true;
+            skipping_plugins = !trimmed.contains(')');
+            continue;
+        }
+        lines.push(line.to_string());
+    }
+
+    if !theme_set {
+        lines.push(format!("ZSH_THEME=\"{}\"", theme.unwrap_or_default()));
+    }
+    if !plugins_set {
+        lines.push(format!("plugins=({})", plugins.join(" ")));
+    }
+
+    let mut updated = lines.join("\n");
+    if zshrc.ends_with('\n') || zshrc.is_empty() {
+        updated.push('\n');
+    }
+    updated
+}
+
 /// Installs Oh My Zsh to enhance the Zsh shell experience.
 ///
 /// This function downloads and installs Oh My Zsh by piping the official installation script from
 /// a remote URL through `curl` to `bash`. It checks if Oh My Zsh is already installed to avoid
 /// redundant operations, ensuring idempotency. The function is part of the "glwiz" project’s
 /// post-installation setup to provide a customizable and feature-rich shell environment.
+/// The installer is told where to install through `ZSH` and not to change the shell or start
+/// Zsh itself, since glwiz handles the shell change separately.
+///
+/// # Arguments
+/// * `home_dir` - The home directory to install into (e.g., `"/home/user"` or `"/mnt/home/user"`).
 ///
 /// # Returns
 /// * `0` - Oh My Zsh was successfully installed or already present.
@@ -85,25 +371,23 @@ pub fn change_def_shell(name: &str) -> i8 {
 /// ```should_panic
 /// // Requires network access and curl/bash.
 /// use glwiz::functionality::shell::install_omz;
-/// let result = install_omz();
+/// let result = install_omz("/home/user");
 /// assert_eq!(result, 0);
 /// ```
 ///
 /// # See Also
 /// - `install_zsh_autosuggestions`: Installs a complementary Zsh plugin.
 /// - `install_zsh_syntax_highlighting`: Installs another Zsh plugin.
-pub fn install_omz() -> i8 {
-    if std::path::Path::new("~/.oh-my-zsh").exists() {
+pub fn install_omz(home_dir: &str) -> i8 {
+    let omz_dir = format!("{}/.oh-my-zsh", home_dir);
+    if std::path::Path::new(&omz_dir).exists() {
         println!("Oh My Zsh already installed.");
         return 0;
     }
 
     let mut curl_cmd = Command::new("curl");
     curl_cmd
-        .args(&[
-            "-fsSL",
-            "https://raw.githubusercontent.com/ohmyzsh/ohmyzsh/master/tools/install.sh",
-        ])
+        .args(["-fsSL", OMZ_INSTALL_URL])
         .stdout(Stdio::piped());
 
     let curl_process = match curl_cmd.spawn() {
@@ -123,7 +407,11 @@ pub fn install_omz() -> i8 {
     };
 
     let mut bash_cmd = Command::new("bash");
-    bash_cmd.stdin(Stdio::from(curl_stdout));
+    bash_cmd
+        .env("ZSH", &omz_dir)
+        .env("RUNZSH", "no")
+        .env("CHSH", "no")
+        .stdin(Stdio::from(curl_stdout));
     match bash_cmd.output() {
         Ok(output) if output.status.success() => {
             println!("Oh My Zsh {}.", "installed".green());
@@ -145,22 +433,30 @@ pub fn install_omz() -> i8 {
     }
 }
 
-// Installs a Zsh plugin by cloning a Git repository.
-//
-// This private helper function clones a specified Zsh plugin repository into the Oh My Zsh
-// custom plugins directory. It checks if the plugin is already installed to avoid redundant
-// cloning, ensuring idempotency. The function is used by `install_zsh_autosuggestions` and
-// `install_zsh_syntax_highlighting` to install specific plugins.
-//
-// Arguments:
-// * `home_dir` - The user’s home directory (e.g., "/home/user").
-// * `plugin_name` - The name of the plugin (e.g., "zsh-autosuggestions").
-// * `repo_url` - The Git repository URL for the plugin.
-//
-// Returns:
-// * `0` - The plugin was successfully installed or already present.
-// * `1` - An error occurred during the Git clone operation.
-fn install_zsh_plugin(home_dir: &str, plugin_name: &str, repo_url: &str) -> i8 {
+/// Installs a Zsh plugin by cloning a Git repository.
+///
+/// This function clones a specified Zsh plugin repository into the Oh My Zsh custom plugins
+/// directory. It checks if the plugin is already installed to avoid redundant cloning, ensuring
+/// idempotency. The function is used by `install_zsh_autosuggestions` and
+/// `install_zsh_syntax_highlighting`, and for the custom plugins listed in a profile.
+///
+/// # Arguments
+/// * `home_dir` - The user’s home directory (e.g., `"/home/user"`).
+/// * `plugin_name` - The name of the plugin (e.g., `"zsh-autosuggestions"`).
+/// * `repo_url` - The Git repository URL for the plugin.
+///
+/// # Returns
+/// * `0` - The plugin was successfully installed or already present.
+/// * `1` - An error occurred during the Git clone operation.
+///
+/// # Example
+/// ```no_run
+/// // Requires git and network access.
+/// use glwiz::functionality::shell::install_zsh_plugin;
+/// let result = install_zsh_plugin("/home/user", "zsh-completions", "https://github.com/zsh-users/zsh-completions");
+/// assert_eq!(result, 0);
+/// ```
+pub fn install_zsh_plugin(home_dir: &str, plugin_name: &str, repo_url: &str) -> i8 {
     let path = format!("{}/.oh-my-zsh/custom/plugins/{}", home_dir, plugin_name);
     if std::path::Path::new(&path).exists() {
         println!("{} already installed.", plugin_name);
@@ -213,11 +509,7 @@ fn install_zsh_plugin(home_dir: &str, plugin_name: &str, repo_url: &str) -> i8 {
 /// - `install_zsh_plugin`: The helper function performing the installation.
 /// - `install_omz`: Installs Oh My Zsh, required for this plugin.
 pub fn install_zsh_autosuggestions(home_dir: &str) -> i8 {
-    install_zsh_plugin(
-        home_dir,
-        "zsh-autosuggestions",
-        "https://github.com/zsh-users/zsh-autosuggestions",
-    )
+    install_zsh_plugin(home_dir, "zsh-autosuggestions", ZSH_AUTOSUGGESTIONS_REPO)
 }
 
 /// Installs the Zsh Syntax Highlighting plugin for improved shell readability.
@@ -252,6 +544,6 @@ pub fn install_zsh_syntax_highlighting(home_dir: &str) -> i8 {
     install_zsh_plugin(
         home_dir,
         "zsh-syntax-highlighting",
-        "https://github.com/zsh-users/zsh-syntax-highlighting.git",
+        ZSH_SYNTAX_HIGHLIGHTING_REPO,
     )
 }
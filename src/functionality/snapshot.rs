@@ -0,0 +1,312 @@
+/*
+ * GLWiz - The ultimate post-installation setup assistant for GNU/Linux popular distros,
+ * streamlining your configuration process with ease and precision.
+ * 
+ * Copyright (C) 2025  Andrew Kushyk
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published
+ * by the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use super::{
+    commands::{capture_sudo_command, capture_user_command, command_exists},
+    distro::Distro,
+    flatpak::{FLATPAK_COMMAND, FlatpakScope, parse_app_list},
+    iptables::rules_path,
+    package_manager::{xbps::split_pkgver, zypper::parse_table},
+    profile::{Profile, ZshPlugin},
+    service::InitSystem,
+    shell::{omz_settings, passwd_shell},
+    target::Target,
+    zram::{ZRAM_CONFIG_PATH, config_path},
+};
+use colored::Colorize;
+use std::{collections::HashSet, fs};
+
+/// Captures the configuration of the running system as a `Profile`.
+///
+/// This function records the explicitly installed packages (with those from the AUR apart on
+/// Arch) and Flatpak apps, the user’s login shell, the Oh My Zsh theme and plugins, the custom
+/// Zsh plugins with their Git remotes, the user’s `.zshrc` and `.vimrc`, the active iptables
+/// rules, and the zram-generator configuration of systemd hosts (the zram-init and zramen
+/// configurations of OpenRC and runit hosts are left out with a warning). A profile holds one Flatpak scope: it is the
+/// user installation if every app is installed there, and otherwise the system installation,
+/// which then also receives the user’s apps (with a warning). Anything that cannot be read is left out of
+/// the profile with a warning on stderr, so the snapshot of a
+/// partially configured machine still applies cleanly elsewhere. All progress messages go to
+/// stderr, keeping stdout free for the TOML document.
+///
+/// # Arguments
+/// * `user_name` - The user whose login shell is recorded (e.g., `"user"`).
+/// * `home_dir` - The user’s home directory (e.g., `"/home/user"`).
+///
+/// # Returns
+/// A `Profile` describing the system.
+///
+/// # Example
+/// ```no_run
+/// // Requires sudo privileges to read the iptables rules.
+/// use glwiz::functionality::snapshot::snapshot_system;
+/// let profile = snapshot_system("user", "/home/user");
+/// println!("{}", profile.to_toml().unwrap());
+/// ```
+///
+/// # See Also
+/// - `profile::Profile`: The captured profile, applied with `glwiz run --profile`.
+pub fn snapshot_system(user_name: &str, home_dir: &str) -> Profile {
+    let mut profile = Profile::default();
+
+    let distro = Distro::detect(&Target::host());
+    match &distro {
+        Some(distro) => match installed_packages(distro.family_name()) {
+            Ok(packages) => profile.packages.native = packages,
+            Err(e) => warn("packages", &e),
+        },
+        None => warn("packages", "unknown distribution"),
+    }
+    // Foreign packages on Arch come from the AUR; they are rebuilt there rather than looked up
+    // in the repositories. `pacman -Qqem` exits with a failure status when there are none.
+    if distro.as_ref().is_some_and(|d| d.family_name() == "arch") {
+        let aur: Vec<String> = capture_user_command("pacman", &["-Qqem"])
+            .map(|output| output.lines().map(String::from).collect())
+            .unwrap_or_default();
+        profile
+            .packages
+            .native
+            .retain(|package| !aur.contains(package));
+        profile.packages.aur = aur;
+    }
+    if command_exists(FLATPAK_COMMAND) {
+        let list = |scope: FlatpakScope| {
+            capture_user_command(
+                FLATPAK_COMMAND,
+                &["list", "--app", scope.as_arg(), "--columns=application"],
+            )
+            .map(|output| parse_app_list(&output))
+        };
+        match (list(FlatpakScope::System), list(FlatpakScope::User)) {
+            (Ok(system), Ok(user)) if system.is_empty() && !user.is_empty() => {
+                profile.packages.flatpak = user;
+                profile.packages.flatpak_scope = FlatpakScope::User;
+            }
+            (Ok(mut system), Ok(user)) => {
+                if !user.is_empty() {
+                    eprintln!(
+                        "{} Recording the user’s Flatpak apps for the system installation: {}",
+                        "warning:".yellow(),
+                        user.join(" ")
+                    );
+                }
+                for app in user {
+                    if !system.contains(&app) {
+                        system.push(app);
+                    }
+                }
+                profile.packages.flatpak = system;
+            }
+            (Err(e), _) | (_, Err(e)) => warn("Flatpak apps", &e),
+        }
+    }
+
+    match fs::read_to_string("/etc/passwd") {
+        Ok(passwd) => profile.shell.login_shell = passwd_shell(&passwd, user_name),
+        Err(e) => warn("login shell", &e.to_string()),
+    }
+
+    let zshrc = read_optional(&format!("{}/.zshrc", home_dir));
+    if let Some(zshrc) = &zshrc {
+        let (theme, plugins) = omz_settings(zshrc);
+        profile.shell.omz_theme = theme;
+        profile.shell.omz_plugins = plugins;
+    }
+    profile.shell.custom_plugins = custom_plugins(home_dir);
+
+    profile.files.zshrc = zshrc;
+    profile.files.vimrc = read_optional(&format!("{}/.vimrc", home_dir));
+    let family = distro.and_then(|d| d.family);
+    profile.files.iptables = match capture_sudo_command("iptables-save", &[]) {
+        Ok(rules) => Some(rules),
+        Err(e) => {
+            warn("iptables rules", &e);
+            read_optional(rules_path(family))
+        }
+    };
+    // Profiles carry a zram-generator configuration, which `zram_swap_setup` translates for
+    // the zram-init and zramen services; their own files cannot be translated back.
+    profile.files.zram = match InitSystem::detect(&Target::host(), family) {
+        InitSystem::Systemd => read_optional(ZRAM_CONFIG_PATH),
+        init => {
+            let path = config_path(init);
+            if fs::metadata(path).is_ok() {
+                warn(
+                    "zram configuration",
+                    &format!("{} is not a zram-generator configuration", path),
+                );
+            }
+            None
+        }
+    };
+
+    profile
+}
+
+/// Extracts package names from the output of `dnf history userinstalled`.
+///
+/// The command prints one NEVRA (`name-[epoch:]version-release.arch`) per line under a
+/// header; the version, release and architecture are stripped.
+///
+/// # Arguments
+/// * `output` - The command output.
+///
+/// # Returns
+/// A `Vec<String>` with the package names.
+///
+/// # Example
+/// ```
+/// use glwiz::functionality::snapshot::parse_dnf_userinstalled;
+/// let output = "Packages installed by user\nvim-enhanced-2:9.1.0-1.fc40.x86_64\nzsh-5.9-12.fc40.x86_64\n";
+/// assert_eq!(parse_dnf_userinstalled(output), vec!["vim-enhanced", "zsh"]);
+/// ```
+pub fn parse_dnf_userinstalled(output: &str) -> Vec<String> {
+    output
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.contains(' '))
+        .filter_map(|nevra| {
+            let mut parts = nevra.rsplitn(3, '-');
+            let _release_arch = parts.next()?;
+            let _version = parts.next()?;
+            parts.next().map(String::from)
+        })
+        .collect()
+}
+
+/// Extracts package names from `/etc/apk/world`.
+///
+/// Each line holds a dependency, which may carry a version constraint (`<`, `>`, `=`, `~`) or a
+/// repository tag (`@edge`); both are stripped.
+///
+/// # Arguments
+/// * `world` - The contents of `/etc/apk/world`.
+///
+/// # Returns
+/// A `Vec<String>` with the package names.
+///
+/// # Example
+/// ```
+/// use glwiz::functionality::snapshot::parse_apk_world;
+/// let world = "alpine-base\nneovim@edge\nzsh>=5.9\nvim~9.1\n";
+/// assert_eq!(parse_apk_world(world), vec!["alpine-base", "neovim", "zsh", "vim"]);
+/// ```
+pub fn parse_apk_world(world: &str) -> Vec<String> {
+    world
+        .lines()
+        .map(|line| {
+            let end = line.find(['<', '>', '=', '~', '@']).unwrap_or(line.len());
+            line[..end].trim()
+        })
+        .filter(|name| !name.is_empty())
+        .map(String::from)
+        .collect()
+}
+
+// Lists the packages explicitly installed with the distribution’s package manager.
+fn installed_packages(distro: &str) -> Result<Vec<String>, String> {
+    let output = match distro {
+        "arch" => capture_user_command("pacman", &["-Qqe"])?,
+        "debian" => capture_user_command("apt-mark", &["showmanual"])?,
+        "alpine" => {
+            return fs::read_to_string("/etc/apk/world")
+                .map(|world| parse_apk_world(&world))
+                .map_err(|e| e.to_string());
+        }
+        "gentoo" => fs::read_to_string("/var/lib/portage/world").map_err(|e| e.to_string())?,
+        "void" => {
+            return capture_user_command("xbps-query", &["-m"]).map(|output| {
+                output
+                    .lines()
+                    .filter_map(split_pkgver)
+                    .map(|package| package.name)
+                    .collect()
+            });
+        }
+        "fedora" => {
+            return capture_user_command("dnf", &["history", "userinstalled"])
+                .map(|output| parse_dnf_userinstalled(&output))
+                .or_else(|_| {
+                    capture_user_command(
+                        "dnf",
+                        &["repoquery", "--userinstalled", "--qf", "%{name}\\n"],
+                    )
+                    .map(|output| output.lines().map(String::from).collect())
+                });
+        }
+        "suse" => {
+            let output =
+                capture_user_command("zypper", &["--quiet", "packages", "--userinstalled"])?;
+            // A package is listed once per installed version or architecture.
+            let mut seen = HashSet::new();
+            return Ok(parse_table(&output)
+                .into_iter()
+                .filter_map(|row| row.get("Name").cloned())
+                .filter(|name| seen.insert(name.clone()))
+                .collect());
+        }
+        _ => return Err(format!("unsupported distribution {}", distro)),
+    };
+    Ok(output
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .map(String::from)
+        .collect())
+}
+
+// Lists the plugins in the Oh My Zsh custom plugins directory that are Git checkouts,
+// skipping the bundled `example` plugin.
+fn custom_plugins(home_dir: &str) -> Vec<ZshPlugin> {
+    let dir = format!("{}/.oh-my-zsh/custom/plugins", home_dir);
+    let Ok(entries) = fs::read_dir(&dir) else {
+        return Vec::new();
+    };
+
+    let mut plugins: Vec<ZshPlugin> = entries
+        .filter_map(Result::ok)
+        .filter(|entry| entry.path().is_dir())
+        .filter_map(|entry| {
+            let name = entry.file_name().to_string_lossy().into_owned();
+            if name == "example" {
+                return None;
+            }
+            let path = entry.path().to_string_lossy().into_owned();
+            capture_user_command("git", &["-C", &path, "remote", "get-url", "origin"])
+                .ok()
+                .map(|repo| ZshPlugin {
+                    name,
+                    repo: repo.trim().to_string(),
+                })
+        })
+        .collect();
+    plugins.sort_by(|a, b| a.name.cmp(&b.name));
+    plugins
+}
+
+// Reads a file, returning `None` if it does not exist or cannot be read.
+fn read_optional(path: &str) -> Option<String> {
+    fs::read_to_string(path).ok()
+}
+
+// Reports a part of the system that could not be captured.
+fn warn(what: &str, reason: &str) {
+    eprintln!("{} Skipping {}: {}", "warning:".yellow(), what, reason);
+}
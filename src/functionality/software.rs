@@ -18,82 +18,624 @@
  * along with this program.  If not, see <https://www.gnu.org/licenses/>.
  */
 
+use super::{
+    distro::DistroFamily,
+    package_manager::{PackageManager, apk, apt, dnf, for_family, pacman, portage, xbps, zypper},
+    package_map::PackageMap,
+    target::Target,
+};
 use colored::Colorize;
-use std::process::Command;
+use std::{collections::HashSet, fmt, fs, path::Path};
+
+/// Release files identifying the supported distributions, checked in order. These are only
+/// used for systems without an os-release file (see `distro::Distro::detect`).
+pub const DISTRO_RELEASE_FILES: [(&str, &str); 6] = [
+    ("/etc/arch-release", "arch"),
+    ("/etc/debian_version", "debian"),
+    ("/etc/fedora-release", "fedora"),
+    ("/etc/SUSE-brand", "suse"),
+    ("/etc/alpine-release", "alpine"),
+    ("/etc/gentoo-release", "gentoo"),
+];
+
+/// Files whose presence means the running system needs a reboot, written by Debian and Ubuntu
+/// packages.
+pub const REBOOT_REQUIRED_FILES: [&str; 2] = ["/run/reboot-required", "/var/run/reboot-required"];
+
+/// Directories holding the module trees of the installed kernels, one per kernel release.
+pub const KERNEL_MODULE_DIRS: [&str; 2] = ["/usr/lib/modules", "/lib/modules"];
+
+/// The file reporting the release of the running kernel.
+pub const KERNEL_RELEASE_PATH: &str = "/proc/sys/kernel/osrelease";
+
+/// Returns the package manager command and arguments that install packages on a distribution.
+///
+/// # Arguments
+/// * `distro` - The distribution or family identifier (e.g., `"arch"`, `"ubuntu"`, `"suse"`).
+///
+/// # Returns
+/// * `Some((command, args))` - The package manager and its non-interactive install arguments.
+/// * `None` - The distribution is not supported.
+///
+/// # Example
+/// ```
+/// use glwiz::functionality::software::install_command;
+/// assert_eq!(install_command("fedora"), Some(("dnf", vec!["install", "-y"])));
+/// assert_eq!(
+///     install_command("suse"),
+///     Some(("zypper", vec!["--non-interactive", "install"]))
+/// );
+/// assert_eq!(install_command("unknown"), None);
+/// ```
+pub fn install_command(distro: &str) -> Option<(&'static str, Vec<&'static str>)> {
+    match DistroFamily::from_id(distro)? {
+        DistroFamily::Arch => Some((pacman::COMMAND, pacman::INSTALL_ARGS.to_vec())),
+        DistroFamily::Debian => Some((apt::COMMAND, apt::INSTALL_ARGS.to_vec())),
+        DistroFamily::Fedora => Some((dnf::COMMAND, dnf::INSTALL_ARGS.to_vec())),
+        DistroFamily::Suse => Some((zypper::COMMAND, zypper::INSTALL_ARGS.to_vec())),
+        DistroFamily::Alpine => Some((apk::COMMAND, apk::INSTALL_ARGS.to_vec())),
+        DistroFamily::Void => Some((xbps::COMMAND, xbps::INSTALL_ARGS.to_vec())),
+        DistroFamily::Gentoo => Some((portage::COMMAND, portage::INSTALL_ARGS.to_vec())),
+    }
+}
+
+/// Returns the package manager command and arguments that refresh the package index on a
+/// distribution before installing.
+///
+/// # Arguments
+/// * `distro` - The distribution or family identifier (e.g., `"debian"`, `"fedora"`).
+///
+/// # Returns
+/// * `Some((command, args))` - The package manager and its refresh arguments.
+/// * `None` - The distribution is not supported, or its index is not refreshed on its own
+///   (Arch, which does not support partial upgrades, see `PackageManager::upgrade`).
+///
+/// # Example
+/// ```
+/// use glwiz::functionality::software::refresh_command;
+/// assert_eq!(refresh_command("ubuntu"), Some(("apt", vec!["update"])));
+/// assert_eq!(refresh_command("arch"), None);
+/// ```
+pub fn refresh_command(distro: &str) -> Option<(&'static str, Vec<&'static str>)> {
+    match DistroFamily::from_id(distro)? {
+        DistroFamily::Arch => None,
+        DistroFamily::Debian => Some((apt::COMMAND, apt::REFRESH_ARGS.to_vec())),
+        DistroFamily::Fedora => Some((dnf::COMMAND, dnf::REFRESH_ARGS.to_vec())),
+        DistroFamily::Suse => Some((zypper::COMMAND, zypper::REFRESH_ARGS.to_vec())),
+        DistroFamily::Alpine => Some((apk::COMMAND, apk::REFRESH_ARGS.to_vec())),
+        DistroFamily::Void => Some((xbps::COMMAND, xbps::REFRESH_ARGS.to_vec())),
+        DistroFamily::Gentoo => Some((portage::COMMAND, portage::REFRESH_ARGS.to_vec())),
+    }
+}
+
+/// Lists the kernels installed on a target system, by their module directories.
+///
+/// # Arguments
+/// * `target` - The system to inspect.
+///
+/// # Returns
+/// The sorted kernel release names (e.g., `["6.9.7-arch1-1"]`); empty if none can be read.
+///
+/// # Example
+/// ```
+/// use glwiz::functionality::{software::installed_kernels, target::Target};
+/// let kernels = installed_kernels(&Target::host());
+/// println!("Installed kernels: {:?}", kernels);
+/// ```
+pub fn installed_kernels(target: &Target) -> Vec<String> {
+    let mut kernels: Vec<String> = KERNEL_MODULE_DIRS
+        .iter()
+        .filter_map(|dir| fs::read_dir(target.path(dir)).ok())
+        .flatten()
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| entry.path().is_dir())
+        .map(|entry| entry.file_name().to_string_lossy().into_owned())
+        .collect();
+    kernels.sort();
+    kernels.dedup();
+    kernels
+}
+
+/// Tells whether a kernel change requires a reboot.
+///
+/// A reboot is needed when the running kernel’s modules are gone (e.g., Arch and Alpine
+/// replace the kernel in place) or when a kernel was installed next to it (e.g., Debian and
+/// Fedora keep the previous ones).
+///
+/// # Arguments
+/// * `running` - The release of the running kernel (as printed by `uname -r`).
+/// * `before` - The installed kernels before the packages changed (see `installed_kernels`).
+/// * `after` - The installed kernels afterwards.
+///
+/// # Returns
+/// * `Some(reason)` - Why a reboot is needed.
+/// * `None` - The kernels did not change, or they cannot be told apart.
+///
+/// # Example
+/// ```
+/// use glwiz::functionality::software::kernel_reboot_reason;
+/// let before = vec!["6.9.6-arch1-1".to_string()];
+/// let after = vec!["6.9.7-arch1-1".to_string()];
+/// assert_eq!(
+///     kernel_reboot_reason("6.9.6-arch1-1", &before, &after),
+///     Some("the running kernel 6.9.6-arch1-1 is no longer installed".to_string())
+/// );
+/// let after = vec!["6.1.0-21-amd64".to_string(), "6.1.0-22-amd64".to_string()];
+/// assert_eq!(
+///     kernel_reboot_reason("6.1.0-21-amd64", &after[..1], &after),
+///     Some("kernel 6.1.0-22-amd64 was installed".to_string())
+/// );
+/// assert_eq!(kernel_reboot_reason("6.1.0-21-amd64", &after, &after), None);
+/// ```
+pub fn kernel_reboot_reason(running: &str, before: &[String], after: &[String]) -> Option<String> {
+    if after.is_empty() {
+        return None;
+    }
+    if !after.iter().any(|kernel| kernel == running) {
+        return Some(format!(
+            "the running kernel {} is no longer installed",
+            running
+        ));
+    }
+    let new: Vec<&str> = after
+        .iter()
+        .filter(|kernel| !before.contains(kernel))
+        .map(String::as_str)
+        .collect();
+    if new.is_empty() {
+        None
+    } else {
+        Some(format!("kernel {} was installed", new.join(", ")))
+    }
+}
+
+/// Tells whether the running system needs a reboot after its packages changed.
+///
+/// A reboot is needed when a package asked for one through `/run/reboot-required` (Debian and
+/// Ubuntu) or when the kernel changed (see `kernel_reboot_reason`). A mounted root is never
+/// reported, since it is not the running system.
+///
+/// # Arguments
+/// * `target` - The system whose packages changed.
+/// * `kernels_before` - The kernels installed before the change (see `installed_kernels`).
+///
+/// # Returns
+/// * `Some(reason)` - Why a reboot is needed.
+/// * `None` - No reboot is needed, or the target is a mounted root.
+///
+/// # Example
+/// ```
+/// use glwiz::functionality::{
+///     software::{installed_kernels, reboot_reason},
+///     target::Target,
+/// };
+/// let target = Target::host();
+/// let kernels = installed_kernels(&target);
+/// if let Some(reason) = reboot_reason(&target, &kernels) {
+///     println!("Reboot needed: {}", reason);
+/// }
+/// ```
+pub fn reboot_reason(target: &Target, kernels_before: &[String]) -> Option<String> {
+    if target.is_chroot() {
+        return None;
+    }
+    if let Some(file) = REBOOT_REQUIRED_FILES
+        .iter()
+        .find(|file| Path::new(file).exists())
+    {
+        return Some(format!("{} exists", file));
+    }
+    let running = fs::read_to_string(KERNEL_RELEASE_PATH).ok()?;
+    kernel_reboot_reason(running.trim(), kernels_before, &installed_kernels(target))
+}
+
+// Prints whether the system needs a reboot, once its packages have changed.
+fn report_reboot(target: &Target, kernels_before: &[String]) {
+    match reboot_reason(target, kernels_before) {
+        Some(reason) => println!("{} A reboot is needed: {}.", "warning:".yellow(), reason),
+        None if !target.is_chroot() => println!("Reboot {}.", "not needed".green()),
+        None => {}
+    }
+}
+
+/// The number of close matches suggested for a package no repository provides.
+pub const MAX_SUGGESTIONS: usize = 3;
+
+// The length of the name prefix searched for suggestions, so that misspellings later in the
+// name still find the package.
+const SUGGESTION_PREFIX_LEN: usize = 3;
+
+/// The outcome of installing one package with `software_setup`.
+///
+/// # Example
+/// ```
+/// use glwiz::functionality::software::PackageStatus;
+/// let status = PackageStatus::NotFound(vec!["firefox".to_string()]);
+/// assert!(!status.is_ok());
+/// assert_eq!(status.to_string(), "not found (did you mean firefox?)");
+/// assert_eq!(PackageStatus::AlreadyPresent.to_string(), "already present");
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PackageStatus {
+    /// The package was installed by this run.
+    Installed,
+    /// The package was installed before this run.
+    AlreadyPresent,
+    /// No configured repository provides the package; holds close matches that do exist.
+    NotFound(Vec<String>),
+    /// The package manager failed to install the package.
+    Failed,
+}
+
+impl PackageStatus {
+    /// Returns `true` if the package is installed after the run.
+    pub fn is_ok(&self) -> bool {
+        matches!(self, Self::Installed | Self::AlreadyPresent)
+    }
+}
+
+impl fmt::Display for PackageStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Installed => write!(f, "installed"),
+            Self::AlreadyPresent => write!(f, "already present"),
+            Self::NotFound(suggestions) if suggestions.is_empty() => write!(f, "not found"),
+            Self::NotFound(suggestions) => {
+                write!(f, "not found (did you mean {}?)", suggestions.join(", "))
+            }
+            Self::Failed => write!(f, "failed"),
+        }
+    }
+}
+
+/// The result of `software_setup`: a status for every package it was asked to install.
+///
+/// # Fields
+/// * `status` - `0` if every package is installed afterwards, `1` otherwise.
+/// * `packages` - Each package, under its package manager name, with its status.
+///
+/// # Example
+/// ```
+/// use glwiz::functionality::software::{PackageStatus, SoftwareReport};
+/// let report = SoftwareReport::new(vec![
+///     ("zsh".to_string(), PackageStatus::AlreadyPresent),
+///     ("fierfox".to_string(), PackageStatus::NotFound(vec!["firefox".to_string()])),
+/// ]);
+/// assert_eq!(report.status, 1);
+/// assert_eq!(
+///     report.details(),
+///     ["zsh: already present", "fierfox: not found (did you mean firefox?)"]
+/// );
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SoftwareReport {
+    pub status: i8,
+    pub packages: Vec<(String, PackageStatus)>,
+}
+
+impl SoftwareReport {
+    /// Creates a report from the package statuses, failing if any package is not installed.
+    pub fn new(packages: Vec<(String, PackageStatus)>) -> Self {
+        let status = if packages.iter().all(|(_, status)| status.is_ok()) {
+            0
+        } else {
+            1
+        };
+        Self { status, packages }
+    }
+
+    /// Creates a failed report for a setup that stopped before any package was looked at.
+    pub fn failed() -> Self {
+        Self {
+            status: 1,
+            packages: Vec::new(),
+        }
+    }
+
+    /// Returns one `name: status` line per package, as stored in the task result.
+    pub fn details(&self) -> Vec<String> {
+        self.packages
+            .iter()
+            .map(|(package, status)| format!("{}: {}", package, status))
+            .collect()
+    }
+
+    /// Prints the status of every package.
+    pub fn print(&self) {
+        println!("Package status:");
+        for (package, status) in &self.packages {
+            let text = status.to_string();
+            let text = if status.is_ok() {
+                text.green()
+            } else {
+                text.red()
+            };
+            println!("  {}: {}", package, text);
+        }
+    }
+}
+
+/// Suggests close matches for a package name that no repository provides.
+///
+/// Candidates are ranked by their edit distance to `name`; only those within a third of the
+/// name’s length (at least one edit) are kept, up to `MAX_SUGGESTIONS`.
+///
+/// # Arguments
+/// * `name` - The unknown package name (e.g., `"fierfox"`).
+/// * `candidates` - Package names found in the repositories (e.g., by `PackageManager::search`).
+///
+/// # Returns
+/// The closest candidates, best first; empty if none is close.
+///
+/// # Example
+/// ```
+/// use glwiz::functionality::software::suggest_packages;
+/// let candidates = ["firefox", "firefox-esr", "fish", "fire"].map(String::from);
+/// assert_eq!(suggest_packages("fierfox", &candidates), ["firefox"]);
+/// assert!(suggest_packages("vlc", &candidates).is_empty());
+/// ```
+pub fn suggest_packages(name: &str, candidates: &[String]) -> Vec<String> {
+    let limit = (name.chars().count() / 3).max(1);
+    let mut ranked: Vec<(usize, &String)> = candidates
+        .iter()
+        .filter(|candidate| candidate.as_str() != name)
+        .map(|candidate| (edit_distance(name, candidate), candidate))
+        .filter(|(distance, _)| *distance <= limit)
+        .collect();
+    ranked.sort();
+    ranked.dedup();
+    ranked
+        .into_iter()
+        .take(MAX_SUGGESTIONS)
+        .map(|(_, candidate)| candidate.clone())
+        .collect()
+}
+
+// Returns the Levenshtein distance between two strings, counted in characters.
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut current = vec![i + 1];
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(ca != *cb);
+            current.push(substitution.min(previous[j + 1] + 1).min(current[j] + 1));
+        }
+        previous = current;
+    }
+    previous[b.len()]
+}
+
+// Searches the repositories for the names a misspelled package may have meant.
+fn suggestion_candidates(manager: &dyn PackageManager, name: &str) -> Vec<String> {
+    let prefix: String = name.chars().take(SUGGESTION_PREFIX_LEN).collect();
+    let mut queries = vec![name];
+    if prefix != name {
+        queries.push(&prefix);
+    }
+    queries
+        .into_iter()
+        .flat_map(|query| manager.search(query).unwrap_or_default())
+        .map(|package| package.name)
+        .collect()
+}
 
 /// Installs software packages using the distribution’s package manager.
 ///
 /// This function installs a list of software packages on a GNU/Linux system by selecting the
-/// appropriate package manager based on the detected distribution (Arch, Debian, or Fedora).
-/// It uses `sudo` to execute commands like `pacman`, `apt`, or `dnf` with distribution-specific
-/// arguments for non-interactive installation. The function is a core component of the
+/// appropriate package manager based on the detected distribution (Arch, Debian, Fedora,
+/// openSUSE, Alpine, Void or Gentoo). It uses `sudo` to execute commands like `pacman`, `apt`,
+/// `dnf`, `zypper`, `apk`, `xbps-install` or `emerge` with distribution-specific arguments for
+/// non-interactive installation. The listed names are first resolved through the package name
+/// table (see `package_map::PackageMap`), reporting which were mapped to the distribution’s
+/// names and which are unknown, and then translated to the package manager’s own (e.g.,
+/// `category/name` atoms on Gentoo, see `PackageManager::package_name`). Packages the
+/// distribution’s base system lacks but the setup relies on (see
+/// `distro::DistroFamily::required_packages`) are added to the list.
+/// The function is a core component of the
 /// "glwiz" project’s post-installation setup, enabling automated software installation
-/// for user-specified or default package lists. It logs the command being run and reports
-/// success or failure with detailed error messages.
+/// for user-specified or default package lists. Packages that are already installed are
+/// skipped, so only the missing ones are passed to the package manager. It logs the command
+/// being run and reports success or failure with detailed error messages. For a mounted root,
+/// the package manager runs inside the target through its chroot tool.
+///
+/// Each missing package is first looked up in the repositories (see
+/// `PackageManager::is_available`), so that one misspelled name does not make the package
+/// manager reject the whole transaction; unknown names get close matches suggested (see
+/// `suggest_packages`). The available packages are installed together, and one at a time if
+/// that fails. The status of every package (installed, already present, not found, failed) is
+/// printed and returned for the task result.
+///
+/// Before installing, the package index is refreshed (e.g., `apt update`), so that a fresh
+/// system with stale lists can install. With `upgrade`, the whole system is upgraded first
+/// instead (e.g., `pacman -Syu`, `apt full-upgrade`, `dnf upgrade`). On Arch the index is only
+/// synchronized as part of an upgrade, since Arch does not support partial upgrades, and Gentoo
+/// mirrors allow one synchronization a day, so both are only refreshed when their index is
+/// stale (see `PackageManager::index_is_stale`); on Arch that upgrades the system. Once
+/// packages changed, the function reports whether a reboot is needed (see `reboot_reason`).
 ///
 /// # Arguments
 /// * `packages` - A slice of package names to install (e.g., `&["firefox", "vim"]`).
 /// * `distro` - The Linux distribution identifier (e.g., `"arch"`, `"debian"`, `"fedora"`).
+/// * `target` - The system the packages are installed into.
+/// * `upgrade` - Upgrades the whole system before installing.
 ///
 /// # Returns
-/// * `0` - All packages were successfully installed.
-/// * `1` - An error occurred, such as an unsupported distribution, failed command, or package installation error.
+/// A `SoftwareReport` with the status of each package. Its `status` is:
+/// * `0` - All packages are installed.
+/// * `1` - An error occurred, such as an unsupported distribution, failed command, unknown
+///   package or package installation error.
 ///
 /// # Errors
-/// Returns `1` if:
-/// - The `distro` is not supported (i.e., not `"arch"`, `"debian"`, or `"fedora"`).
+/// The report’s status is `1` if:
+/// - The `distro` is not supported (i.e., not an Arch, Debian, Fedora, openSUSE, Alpine, Void or
+///   Gentoo family).
+/// - The system upgrade fails.
+/// - A package is not found in the repositories.
 /// - The package manager command fails to execute (e.g., `sudo` or the package manager is not installed).
-/// - The installation command exits with a non-zero status, indicating issues like unavailable packages or network errors.
+/// - Installing a package exits with a non-zero status, indicating issues like conflicts or network errors.
 ///
 /// # Example
 /// ```
-/// use glwiz::functionality::software::software_setup;
+/// use glwiz::functionality::{software::software_setup, target::Target};
 /// let packages = &["firefox", "vim"];
-/// let result = software_setup(packages, "debian");
-/// assert_eq!(result, 0); // Packages installed successfully
+/// let report = software_setup(packages, "debian", &Target::host(), false);
+/// assert_eq!(report.status, 0); // Packages installed successfully
 /// ```
 ///
 /// # See Also
 /// - `prog_fun::default_sw_package`: Provides the default package list.
 /// - `prog_fun::set_sw_list`: Collects custom package lists from user input.
-/// - `commands::run_sudo_command`: Used to execute package manager commands with `sudo`.
-pub fn software_setup(packages: &[&str], distro: &str) -> i8 {
-    let (cmd, args) = match distro {
-        "arch" => ("pacman", vec!["-Sy", "--noconfirm"]),
-        "debian" => ("apt", vec!["install", "-y"]),
-        "fedora" => ("dnf", vec!["install", "-y"]),
-        _ => {
-            eprintln!("{} Unsupported distribution: {}", "error:".red(), distro);
-            return 1;
-        }
+/// - `package_manager::PackageManager`: Checks for and installs the packages.
+pub fn software_setup(
+    packages: &[&str],
+    distro: &str,
+    target: &Target,
+    upgrade: bool,
+) -> SoftwareReport {
+    let Some(family) = DistroFamily::from_id(distro) else {
+        eprintln!("{} Unsupported distribution: {}", "error:".red(), distro);
+        return SoftwareReport::failed();
     };
+    let manager = for_family(family, target);
+    let kernels = installed_kernels(target);
 
-    let mut command = Command::new("sudo");
-    command.arg(cmd).args(&args).args(packages);
-    println!(
-        "Running: {} {} {}",
-        cmd,
-        args.join(" "),
-        packages.join(" ").green()
-    );
-
-    match command.output() {
-        Ok(output) if output.status.success() => {
-            println!("Software {}.", "installed".green());
-            0
+    if upgrade {
+        println!("Upgrading the system with {}...", manager.name());
+        if let Err(e) = manager.upgrade() {
+            eprintln!("{} System upgrade failed: {}", "error:".red(), e);
+            return SoftwareReport::failed();
         }
-        Ok(output) => {
+        println!("System {}.", "upgraded".green());
+    }
+
+    // A failed query is not fatal: the package manager skips installed packages itself.
+    let installed: HashSet<String> = match manager.list_installed() {
+        Ok(list) => list.into_iter().map(|package| package.name).collect(),
+        Err(e) => {
             eprintln!(
-                "{} Software installation failed:\nstdout: {}\nstderr: {}",
-                "error:".red(),
-                String::from_utf8_lossy(&output.stdout),
-                String::from_utf8_lossy(&output.stderr)
+                "{} Cannot list installed packages: {}",
+                "warning:".yellow(),
+                e
             );
-            1
+            HashSet::new()
         }
-        Err(e) => {
-            eprintln!("{} Failed to run {}: {}", "error:".red(), cmd, e);
-            1
+    };
+    let resolution = PackageMap::load().resolve(packages, family.as_str());
+    resolution.report(family.as_str());
+    let mut wanted: Vec<String> = Vec::new();
+    let listed = resolution.packages.iter().map(String::as_str);
+    for package in listed.chain(family.required_packages().iter().copied()) {
+        let name = manager.package_name(package);
+        if !wanted.contains(&name) {
+            wanted.push(name);
+        }
+    }
+    let (present, missing): (Vec<&str>, Vec<&str>) = wanted
+        .iter()
+        .map(String::as_str)
+        .partition(|package| installed.contains(*package));
+    let mut statuses: Vec<(String, PackageStatus)> = present
+        .iter()
+        .map(|package| (package.to_string(), PackageStatus::AlreadyPresent))
+        .collect();
+    if missing.is_empty() {
+        println!("Software {}.", "already installed".green());
+        if upgrade {
+            report_reboot(target, &kernels);
+        }
+        return SoftwareReport::new(statuses);
+    }
+
+    // A failed refresh is not fatal: the install reports unavailable packages itself.
+    let mut upgraded = upgrade;
+    if !upgrade && manager.refreshes_before_install() {
+        if let Err(e) = manager.refresh_index() {
+            eprintln!(
+                "{} Cannot refresh the package index: {}",
+                "warning:".yellow(),
+                e
+            );
         }
+    } else if !upgrade && manager.index_is_stale() {
+        // On Arch refreshing is a full upgrade; Portage only synchronizes the repository.
+        println!(
+            "The package index is out of date, refreshing it with {}...",
+            manager.name()
+        );
+        if let Err(e) = manager.refresh_index() {
+            eprintln!("{} Cannot refresh the package index: {}", "error:".red(), e);
+            return SoftwareReport::failed();
+        }
+        println!("Package index {}.", "refreshed".green());
+        upgraded = true;
+    }
+
+    // A failed lookup is not fatal either: the package is then left to the install to judge.
+    let (available, unknown): (Vec<&str>, Vec<&str>) = missing
+        .iter()
+        .partition(|package| manager.is_available(package).unwrap_or(true));
+    for package in unknown {
+        let candidates = suggestion_candidates(manager.as_ref(), package);
+        let suggestions = suggest_packages(package, &candidates);
+        statuses.push((package.to_string(), PackageStatus::NotFound(suggestions)));
+    }
+
+    let mut changed = upgraded;
+    if !available.is_empty() {
+        let (cmd, args) = install_command(family.as_str()).unwrap_or_default();
+        println!(
+            "Running: {} {} {}",
+            cmd,
+            args.join(" "),
+            available.join(" ").green()
+        );
+        match manager.install(&available) {
+            Ok(()) => statuses.extend(
+                available
+                    .iter()
+                    .map(|package| (package.to_string(), PackageStatus::Installed)),
+            ),
+            Err(e) if available.len() == 1 => {
+                eprintln!("{} Cannot install {}: {}", "error:".red(), available[0], e);
+                statuses.push((available[0].to_string(), PackageStatus::Failed));
+            }
+            // One package failing makes most package managers reject the whole transaction,
+            // so the others are installed one at a time.
+            Err(e) => {
+                eprintln!(
+                    "{} Installing the packages together failed: {}",
+                    "warning:".yellow(),
+                    e
+                );
+                println!("Installing the packages one by one...");
+                for package in &available {
+                    let status = match manager.install(&[package]) {
+                        Ok(()) => PackageStatus::Installed,
+                        Err(e) => {
+                            eprintln!("{} Cannot install {}: {}", "error:".red(), package, e);
+                            PackageStatus::Failed
+                        }
+                    };
+                    statuses.push((package.to_string(), status));
+                }
+            }
+        }
+        changed |= statuses
+            .iter()
+            .any(|(_, status)| *status == PackageStatus::Installed);
+    }
+
+    statuses.sort_by_key(|(package, _)| wanted.iter().position(|name| name == package));
+    let report = SoftwareReport::new(statuses);
+    report.print();
+    if report.status == 0 {
+        println!("Software {}.", "installed".green());
+    } else {
+        eprintln!("{} Some packages were not installed.", "error:".red());
+    }
+    if changed {
+        report_reboot(target, &kernels);
     }
+    report
 }
@@ -0,0 +1,225 @@
+/*
+ * GLWiz - The ultimate post-installation setup assistant for GNU/Linux popular distros,
+ * streamlining your configuration process with ease and precision.
+ * 
+ * Copyright (C) 2025  Andrew Kushyk
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published
+ * by the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use super::commands::{
+    capture_sudo_command, capture_sudo_output, capture_user_command, run_chroot_command,
+    run_sudo_command, stream_chroot_command, stream_sudo_command,
+};
+use std::path::Path;
+
+/// Describes the system that glwiz configures.
+///
+/// By default glwiz configures the running host. When a root directory such as `/mnt` is given
+/// (for example right after `pacstrap` or `debootstrap`), every system path is resolved below
+/// that directory and commands that must run inside the installed system are executed through
+/// the distribution’s chroot tool instead of on the host.
+///
+/// # Example
+/// ```
+/// use glwiz::functionality::target::Target;
+/// let host = Target::host();
+/// assert_eq!(host.path("/etc/iptables/iptables.rules"), "/etc/iptables/iptables.rules");
+/// assert!(!host.is_chroot());
+/// ```
+///
+/// # See Also
+/// - `commands::run_chroot_command`: Executes commands inside a mounted root.
+#[derive(Debug, Clone)]
+pub struct Target {
+    root: Option<String>,
+}
+
+impl Default for Target {
+    fn default() -> Self {
+        Self::host()
+    }
+}
+
+impl Target {
+    /// Creates a `Target` describing the running host.
+    ///
+    /// # Returns
+    /// A `Target` whose paths are used unchanged.
+    pub fn host() -> Self {
+        Self { root: None }
+    }
+
+    /// Creates a `Target` for a system mounted at `root`.
+    ///
+    /// A root of `/` is treated as the running host. Trailing slashes are ignored.
+    ///
+    /// # Arguments
+    /// * `root` - The directory the target system is mounted at (e.g., `"/mnt"`).
+    ///
+    /// # Returns
+    /// * `Ok(Target)` - The root exists and is a directory.
+    /// * `Err(String)` - An error message if the root is empty or not a directory.
+    ///
+    /// # Example
+    /// ```
+    /// use glwiz::functionality::target::Target;
+    /// let target = Target::new("/tmp/").unwrap();
+    /// assert!(target.is_chroot());
+    /// assert_eq!(target.get_root(), "/tmp");
+    /// assert_eq!(target.path("/root/.zshrc"), "/tmp/root/.zshrc");
+    /// assert!(Target::new("/does/not/exist").is_err());
+    /// ```
+    pub fn new(root: &str) -> Result<Self, String> {
+        if root.is_empty() {
+            return Err("Target root cannot be empty".to_string());
+        }
+        if !Path::new(root).is_dir() {
+            return Err(format!("Target root {} is not a directory", root));
+        }
+        let trimmed = root.trim_end_matches('/');
+        if trimmed.is_empty() {
+            Ok(Self::host())
+        } else {
+            Ok(Self {
+                root: Some(trimmed.to_string()),
+            })
+        }
+    }
+
+    /// Returns the directory the target system is mounted at (`/` for the host).
+    pub fn get_root(&self) -> &str {
+        self.root.as_deref().unwrap_or("/")
+    }
+
+    /// Returns `true` if the target is a mounted root rather than the running host.
+    pub fn is_chroot(&self) -> bool {
+        self.root.is_some()
+    }
+
+    /// Resolves an absolute system path inside the target.
+    ///
+    /// # Arguments
+    /// * `path` - An absolute path as seen from the target system (e.g., `"/etc/passwd"`).
+    ///
+    /// # Returns
+    /// The path prefixed with the target root, or unchanged for the host.
+    pub fn path(&self, path: &str) -> String {
+        match &self.root {
+            Some(root) => format!("{}/{}", root, path.trim_start_matches('/')),
+            None => path.to_string(),
+        }
+    }
+
+    /// Executes a command with root privileges inside the target system.
+    ///
+    /// On the host this is equivalent to `commands::run_sudo_command`. For a mounted root the
+    /// command runs through `commands::run_chroot_command`.
+    ///
+    /// # Arguments
+    /// * `command` - The command to execute (e.g., `"pacman"`).
+    /// * `args` - A slice of arguments to pass to the command.
+    ///
+    /// # Returns
+    /// * `Ok(())` - The command executed successfully.
+    /// * `Err(String)` - An error message describing the failure.
+    pub fn run_command(&self, command: &str, args: &[&str]) -> Result<(), String> {
+        match &self.root {
+            Some(root) => run_chroot_command(root, command, args),
+            None => run_sudo_command(command, args),
+        }
+    }
+
+    /// Executes a command with root privileges inside the target system, streaming its output.
+    ///
+    /// This is the counterpart of `run_command` for long operations whose progress should be
+    /// visible, such as compiling packages.
+    ///
+    /// # Arguments
+    /// * `command` - The command to execute (e.g., `"emerge"`).
+    /// * `args` - A slice of arguments to pass to the command.
+    ///
+    /// # Returns
+    /// * `Ok(())` - The command executed successfully.
+    /// * `Err(String)` - An error message describing the failure.
+    pub fn stream_command(&self, command: &str, args: &[&str]) -> Result<(), String> {
+        match &self.root {
+            Some(root) => stream_chroot_command(root, command, args),
+            None => stream_sudo_command(command, args),
+        }
+    }
+
+    /// Executes a read-only command inside the target system and returns its standard output.
+    ///
+    /// On the host the command runs as the current user. For a mounted root it runs through
+    /// `sudo chroot`, without preparing the API filesystems, which queries such as listing
+    /// installed packages do not need.
+    ///
+    /// # Arguments
+    /// * `command` - The command to execute (e.g., `"pacman"`).
+    /// * `args` - A slice of arguments to pass to the command (e.g., `&["-Q"]`).
+    ///
+    /// # Returns
+    /// * `Ok(String)` - The command’s stdout.
+    /// * `Err(String)` - An error message describing the failure.
+    ///
+    /// # Example
+    /// ```
+    /// use glwiz::functionality::target::Target;
+    /// let output = Target::host().capture_command("echo", &["hello"]).unwrap();
+    /// assert_eq!(output.trim(), "hello");
+    /// ```
+    pub fn capture_command(&self, command: &str, args: &[&str]) -> Result<String, String> {
+        match &self.root {
+            Some(root) => {
+                let mut chroot_args = vec![root.as_str(), command];
+                chroot_args.extend_from_slice(args);
+                capture_sudo_command("chroot", &chroot_args)
+            }
+            None => capture_user_command(command, args),
+        }
+    }
+
+    /// Previews a transaction inside the target system and returns its standard output.
+    ///
+    /// The command runs with root privileges, which package managers require even for a dry
+    /// run, and its exit status is ignored, since declining a previewed transaction (e.g., with
+    /// `--assumeno`) ends with a failure status.
+    ///
+    /// # Arguments
+    /// * `command` - The command to execute (e.g., `"dnf"`).
+    /// * `args` - A slice of arguments to pass to the command (e.g., `&["remove", "--assumeno"]`).
+    ///
+    /// # Returns
+    /// * `Ok(String)` - The command’s stdout.
+    /// * `Err(String)` - An error message if the command could not be executed.
+    ///
+    /// # Example
+    /// ```no_run
+    /// // Requires a Fedora system and sudo privileges.
+    /// use glwiz::functionality::target::Target;
+    /// let preview = Target::host().preview_command("dnf", &["autoremove", "--assumeno"]).unwrap();
+    /// println!("{}", preview);
+    /// ```
+    pub fn preview_command(&self, command: &str, args: &[&str]) -> Result<String, String> {
+        match &self.root {
+            Some(root) => {
+                let mut chroot_args = vec![root.as_str(), command];
+                chroot_args.extend_from_slice(args);
+                capture_sudo_output("chroot", &chroot_args)
+            }
+            None => capture_sudo_output(command, args),
+        }
+    }
+}
@@ -18,6 +18,7 @@
  * along with this program.  If not, see <https://www.gnu.org/licenses/>.
  */
 
+use super::{interrupt::interrupted, journal};
 use colored::Colorize;
 
 /// Represents the outcome of a single setup task in the glwiz.
@@ -31,6 +32,7 @@ use colored::Colorize;
 /// # Fields
 /// * `status` - An `i8` status code: `0` for success, non-zero for failure (typically `1`).
 /// * `message` - A `String` describing the task (e.g., "Software installation").
+/// * `details` - Per-item outcomes reported with a failed task (e.g., `"vlc: not found"`).
 ///
 /// # Example
 /// ```
@@ -38,6 +40,7 @@ use colored::Colorize;
 /// let task = TaskResult {
 ///     status: 0,
 ///     message: String::from("Software installation"),
+///     details: Vec::new(),
 /// };
 /// assert_eq!(task.status, 0);
 /// assert_eq!(task.message, "Software installation");
@@ -50,13 +53,14 @@ use colored::Colorize;
 pub struct TaskResult {
     pub status: i8,
     pub message: String,
+    pub details: Vec<String>,
 }
 
 /// Validates a collection of task results to determine setup success.
 ///
 /// This function evaluates a vector of `TaskResult` instances to check if all setup tasks in the
 /// "glwiz" project completed successfully. If any task failed (non-zero status), it logs the
-/// errors with descriptive messages and returns `false`. Otherwise, it returns `true`, indicating
+/// errors with descriptive messages, followed by their details, and returns `false`. Otherwise, it returns `true`, indicating
 /// a successful setup. The function is used to provide comprehensive error reporting and ensure
 /// the integrity of the post-installation configuration process.
 ///
@@ -71,8 +75,8 @@ pub struct TaskResult {
 /// ```
 /// use glwiz::functionality::task::{TaskResult, validate_task_statuses};
 /// let tasks = vec![
-///     TaskResult { status: 0, message: String::from("Task 1") },
-///     TaskResult { status: 1, message: String::from("Task 2") },
+///     TaskResult { status: 0, message: String::from("Task 1"), details: Vec::new() },
+///     TaskResult { status: 1, message: String::from("Task 2"), details: Vec::new() },
 /// ];
 /// let result = validate_task_statuses(tasks);
 /// assert_eq!(result, false); // Failed due to Task 2
@@ -91,7 +95,88 @@ pub fn validate_task_statuses(tasks: Vec<TaskResult>) -> bool {
         eprintln!("{} Setup failed with errors:", "error:".red());
         for task in errors {
             eprintln!("- {}: status {}", task.message, task.status);
+            for detail in &task.details {
+                eprintln!("    {}", detail);
+            }
         }
         false
     }
 }
+
+/// Runs a resumable setup task and records its result.
+///
+/// The task is not started once an interrupt has been received. When an interrupted run is
+/// being resumed, a task that already completed is reported as done without running it again.
+/// Successful tasks are recorded in the run journal so that a later `--resume` skips them.
+///
+/// # Arguments
+/// * `tasks` - The results collected so far; the task’s result is appended unless it is skipped
+///   because of an interrupt.
+/// * `message` - The task name used in the report and the run journal.
+/// * `task` - The task to run, returning `0` on success.
+///
+/// # Example
+/// ```
+/// use glwiz::functionality::task::run_task;
+/// let mut tasks = Vec::new();
+/// run_task(&mut tasks, "Example task", || 0);
+/// assert_eq!(tasks[0].status, 0);
+/// assert_eq!(tasks[0].message, "Example task");
+/// ```
+///
+/// # See Also
+/// - `journal`: Records completed tasks for `--resume`.
+/// - `interrupt::interrupted`: Stops the run after Ctrl-C.
+pub fn run_task(tasks: &mut Vec<TaskResult>, message: &str, task: impl FnOnce() -> i8) {
+    run_task_with_details(tasks, message, || (task(), Vec::new()));
+}
+
+/// Runs a resumable setup task that reports per-item details, and records its result.
+///
+/// This behaves like `run_task`, but the task also returns the details stored in its
+/// `TaskResult` (e.g., the status of each package it installed).
+///
+/// # Arguments
+/// * `tasks` - The results collected so far.
+/// * `message` - The task name used in the report and the run journal.
+/// * `task` - The task to run, returning `0` on success along with its details.
+///
+/// # Example
+/// ```
+/// use glwiz::functionality::task::run_task_with_details;
+/// let mut tasks = Vec::new();
+/// run_task_with_details(&mut tasks, "Example task", || (1, vec!["vlc: not found".to_string()]));
+/// assert_eq!(tasks[0].status, 1);
+/// assert_eq!(tasks[0].details, ["vlc: not found"]);
+/// ```
+///
+/// # See Also
+/// - `run_task`: The variant for tasks without details.
+pub fn run_task_with_details(
+    tasks: &mut Vec<TaskResult>,
+    message: &str,
+    task: impl FnOnce() -> (i8, Vec<String>),
+) {
+    if interrupted() {
+        return;
+    }
+    if journal::is_completed(message) {
+        println!("{} {}.", message, "already done".green());
+        tasks.push(TaskResult {
+            status: 0,
+            message: message.to_string(),
+            details: Vec::new(),
+        });
+        return;
+    }
+
+    let (status, details) = task();
+    if status == 0 && !interrupted() {
+        journal::complete_task(message);
+    }
+    tasks.push(TaskResult {
+        status,
+        message: message.to_string(),
+        details,
+    });
+}
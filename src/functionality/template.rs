@@ -0,0 +1,346 @@
+/*
+ * GLWiz - The ultimate post-installation setup assistant for GNU/Linux popular distros,
+ * streamlining your configuration process with ease and precision.
+ * 
+ * Copyright (C) 2025  Andrew Kushyk
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published
+ * by the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use super::{distro::Distro, target::Target};
+use std::{collections::BTreeMap, fs, path::Path};
+
+/// The file extension marking a configuration source as a template.
+pub const TEMPLATE_EXTENSION: &str = ".tmpl";
+
+/// The variables available to configuration templates.
+///
+/// Variables are plain strings referenced by name. `system_vars` fills in the values describing
+/// the machine being configured, and a profile can add its own under `[vars]`.
+///
+/// # Example
+/// ```
+/// use glwiz::functionality::template::TemplateVars;
+/// let mut vars = TemplateVars::new();
+/// vars.set("ssh_source", "10.0.0.0/24");
+/// assert_eq!(vars.get("ssh_source"), Some("10.0.0.0/24"));
+/// assert_eq!(vars.get("missing"), None);
+/// ```
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct TemplateVars {
+    vars: BTreeMap<String, String>,
+}
+
+impl TemplateVars {
+    /// Creates an empty set of variables.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets a variable, replacing any previous value.
+    ///
+    /// # Arguments
+    /// * `name` - The variable name (e.g., `"hostname"`).
+    /// * `value` - The value substituted for `{{ name }}`.
+    pub fn set(&mut self, name: &str, value: &str) {
+        self.vars.insert(name.to_string(), value.to_string());
+    }
+
+    /// Returns the value of a variable, if set.
+    pub fn get(&self, name: &str) -> Option<&str> {
+        self.vars.get(name).map(|v| v.as_str())
+    }
+}
+
+/// Collects the variables describing the system being configured.
+///
+/// The following variables are set:
+/// - `user`, `home` - The user being configured and their home directory.
+/// - `hostname` - The target’s `/etc/hostname`, or the running host’s name.
+/// - `distro` - The distribution family (e.g., `"arch"`, `"debian"` or `"alpine"`).
+/// - `distro_id`, `distro_version`, `distro_codename` - The specific distribution from
+///   os-release (e.g., `"linuxmint"`, `"22"`, `"wilma"`).
+/// - `ram_mb`, `ram_gb` - The installed memory, from `/proc/meminfo`.
+/// - `cpus` - The number of available CPUs.
+///
+/// Values that cannot be determined are left unset, so templates can test for them.
+///
+/// # Arguments
+/// * `user` - The username (e.g., `"user"`).
+/// * `home` - The user’s home directory (e.g., `"/home/user"`).
+/// * `distro` - The distribution, if known.
+/// * `target` - The system being configured.
+///
+/// # Returns
+/// The collected `TemplateVars`.
+///
+/// # Example
+/// ```
+/// use glwiz::functionality::{distro::Distro, target::Target, template::system_vars};
+/// let distro = Distro::from_id("manjaro").unwrap();
+/// let vars = system_vars("user", "/home/user", Some(&distro), &Target::host());
+/// assert_eq!(vars.get("distro"), Some("arch"));
+/// assert_eq!(vars.get("distro_id"), Some("manjaro"));
+/// assert!(vars.get("cpus").is_some());
+/// ```
+pub fn system_vars(
+    user: &str,
+    home: &str,
+    distro: Option<&Distro>,
+    target: &Target,
+) -> TemplateVars {
+    let mut vars = TemplateVars::new();
+    vars.set("user", user);
+    vars.set("home", home);
+    if let Some(distro) = distro {
+        vars.set("distro", distro.family_name());
+        vars.set("distro_id", &distro.id);
+        if let Some(version) = &distro.version_id {
+            vars.set("distro_version", version);
+        }
+        if let Some(codename) = &distro.codename {
+            vars.set("distro_codename", codename);
+        }
+    }
+
+    let hostname = fs::read_to_string(target.path("/etc/hostname"))
+        .ok()
+        .filter(|h| !h.trim().is_empty())
+        .or_else(|| fs::read_to_string("/proc/sys/kernel/hostname").ok());
+    if let Some(hostname) = hostname {
+        vars.set("hostname", hostname.trim());
+    }
+
+    if let Some(kb) = fs::read_to_string("/proc/meminfo")
+        .ok()
+        .and_then(|meminfo| mem_total_kb(&meminfo))
+    {
+        vars.set("ram_mb", &(kb / 1024).to_string());
+        vars.set("ram_gb", &((kb + 512 * 1024) / (1024 * 1024)).to_string());
+    }
+
+    if let Ok(cpus) = std::thread::available_parallelism() {
+        vars.set("cpus", &cpus.to_string());
+    }
+
+    vars
+}
+
+/// Renders a configuration template.
+///
+/// Templates use the following syntax:
+/// - `{{ name }}` - Replaced with the value of the variable `name`. An unset variable is an
+///   error, so a typo cannot silently produce a broken configuration.
+/// - `{{ if COND }}` … `{{ else }}` … `{{ end }}` - Keeps the first or second branch depending
+///   on the condition; the `else` branch is optional and blocks can be nested.
+///
+/// A condition is a variable name, which holds if it is set to anything other than an empty
+/// string, `"0"`, `"false"` or `"no"`, optionally negated with `not`; or a comparison `name OP
+/// value` with `==`, `!=`, `<`, `<=`, `>` or `>=`. The value may be quoted, and `<`, `<=`, `>`
+/// and `>=` compare numerically. A tag alone on its line does not leave an empty line behind.
+///
+/// # Arguments
+/// * `template` - The template text.
+/// * `vars` - The variables available to the template.
+///
+/// # Returns
+/// * `Ok(String)` - The rendered text.
+/// * `Err(String)` - An error message naming the line of an unknown variable, an unterminated
+///   tag, a malformed condition, or an unbalanced `if`/`else`/`end`.
+///
+/// # Example
+/// ```
+/// use glwiz::functionality::template::{render, TemplateVars};
+/// let mut vars = TemplateVars::new();
+/// vars.set("ram_mb", "2048");
+/// vars.set("hostname", "lab1");
+/// let template = "# {{ hostname }}\n{{ if ram_mb < 4096 }}\nzram-size = ram\n{{ else }}\nzram-size = ram / 2\n{{ end }}\n";
+/// assert_eq!(render(template, &vars).unwrap(), "# lab1\nzram-size = ram\n");
+/// assert!(render("{{ missing }}", &vars).is_err());
+/// ```
+pub fn render(template: &str, vars: &TemplateVars) -> Result<String, String> {
+    // Each open block records whether its enclosing output was active and whether the
+    // current branch is taken.
+    let mut blocks: Vec<(bool, bool)> = Vec::new();
+    let mut active = true;
+    let mut output = String::new();
+
+    for (index, line) in template.split_inclusive('\n').enumerate() {
+        let line_no = index + 1;
+        let mut rest = line;
+        let mut rendered = String::new();
+        let mut only_tags = true;
+
+        while let Some(start) = rest.find("{{") {
+            let text = &rest[..start];
+            only_tags &= text.trim().is_empty();
+            if active {
+                rendered.push_str(text);
+            }
+            let end = rest[start..]
+                .find("}}")
+                .ok_or(format!("line {}: unterminated `{{{{`", line_no))?;
+            let tag = rest[start + 2..start + end].trim();
+            rest = &rest[start + end + 2..];
+
+            if let Some(cond) = tag.strip_prefix("if ") {
+                let taken = active && eval_condition(cond.trim(), vars, line_no)?;
+                blocks.push((active, taken));
+                active = taken;
+            } else if tag == "else" {
+                let block = blocks
+                    .last_mut()
+                    .ok_or(format!("line {}: `else` without `if`", line_no))?;
+                block.1 = block.0 && !block.1;
+                active = block.1;
+            } else if tag == "end" {
+                let (outer, _) = blocks
+                    .pop()
+                    .ok_or(format!("line {}: `end` without `if`", line_no))?;
+                active = outer;
+            } else {
+                only_tags = false;
+                if active {
+                    let value = vars.get(tag).ok_or(format!(
+                        "line {}: unknown template variable `{}`",
+                        line_no, tag
+                    ))?;
+                    rendered.push_str(value);
+                }
+            }
+        }
+
+        let is_tag_line = only_tags && rest.trim().is_empty() && rest.len() < line.len();
+        if is_tag_line {
+            continue;
+        }
+        if active {
+            rendered.push_str(rest);
+        } else if !rendered.is_empty() && line.ends_with('\n') {
+            // The line opened a block that is not taken; keep what preceded the tag.
+            rendered.push('\n');
+        }
+        output.push_str(&rendered);
+    }
+
+    if !blocks.is_empty() {
+        return Err(format!("{} unterminated `if` block(s)", blocks.len()));
+    }
+    Ok(output)
+}
+
+/// Returns `true` if a configuration source is a template (its name ends in `.tmpl`).
+///
+/// # Example
+/// ```
+/// use glwiz::functionality::template::is_template;
+/// assert!(is_template("../configs/.zshrc.tmpl"));
+/// assert!(!is_template("../configs/.zshrc"));
+/// ```
+pub fn is_template(path: &str) -> bool {
+    path.ends_with(TEMPLATE_EXTENSION)
+}
+
+/// Reads a configuration source, rendering it first if it is a template.
+///
+/// # Arguments
+/// * `path` - The source file (e.g., `"../configs/iptables.rules.tmpl"`).
+/// * `vars` - The variables available to the template.
+///
+/// # Returns
+/// * `Ok(String)` - The file contents, rendered if the file is a template.
+/// * `Err(String)` - An error message if the file cannot be read or the template is invalid.
+///
+/// # See Also
+/// - `render`: The template syntax.
+pub fn read_source(path: &str, vars: &TemplateVars) -> Result<String, String> {
+    let contents =
+        fs::read_to_string(path).map_err(|e| format!("Failed to read {}: {}", path, e))?;
+    if is_template(path) {
+        render(&contents, vars).map_err(|e| format!("{}: {}", path, e))
+    } else {
+        Ok(contents)
+    }
+}
+
+/// Returns the template variant of a bundled source if it exists, or the source itself.
+///
+/// This lets a `.tmpl` file placed next to a bundled configuration (e.g.,
+/// `../configs/iptables.rules.tmpl`) take its place.
+///
+/// # Arguments
+/// * `path` - The bundled source (e.g., `"../configs/iptables.rules"`).
+///
+/// # Returns
+/// The path of the source to use.
+pub fn prefer_template(path: &str) -> String {
+    let template = format!("{}{}", path, TEMPLATE_EXTENSION);
+    if Path::new(&template).is_file() {
+        template
+    } else {
+        path.to_string()
+    }
+}
+
+// Evaluates an `if` condition against the variables.
+fn eval_condition(cond: &str, vars: &TemplateVars, line_no: usize) -> Result<bool, String> {
+    if let Some(negated) = cond.strip_prefix("not ") {
+        return eval_condition(negated.trim(), vars, line_no).map(|b| !b);
+    }
+
+    let op = ["==", "!=", "<=", ">=", "<", ">"]
+        .into_iter()
+        .find(|op| cond.contains(op));
+    let Some(op) = op else {
+        return Ok(vars
+            .get(cond)
+            .is_some_and(|v| !matches!(v, "" | "0" | "false" | "no")));
+    };
+
+    let (name, expected) = cond.split_once(op).unwrap_or_default();
+    let name = name.trim();
+    let expected = expected.trim().trim_matches(|c| c == '"' || c == '\'');
+    if name.is_empty() {
+        return Err(format!("line {}: malformed condition `{}`", line_no, cond));
+    }
+    let actual = vars.get(name).unwrap_or("");
+
+    match op {
+        "==" => Ok(actual == expected),
+        "!=" => Ok(actual != expected),
+        _ => {
+            let (Ok(a), Ok(b)) = (actual.parse::<f64>(), expected.parse::<f64>()) else {
+                return Err(format!(
+                    "line {}: `{}` compares non-numeric values",
+                    line_no, cond
+                ));
+            };
+            Ok(match op {
+                "<" => a < b,
+                "<=" => a <= b,
+                ">" => a > b,
+                _ => a >= b,
+            })
+        }
+    }
+}
+
+// Extracts the `MemTotal` value, in kB, from the contents of `/proc/meminfo`.
+fn mem_total_kb(meminfo: &str) -> Option<u64> {
+    meminfo
+        .lines()
+        .find_map(|line| line.strip_prefix("MemTotal:"))
+        .and_then(|value| value.split_whitespace().next())
+        .and_then(|kb| kb.parse().ok())
+}
@@ -182,3 +182,9 @@ impl UserCfg {
         &self.home_dir
     }
 }
+
+impl Default for UserCfg {
+    fn default() -> Self {
+        Self::new()
+    }
+}
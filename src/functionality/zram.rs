@@ -18,9 +18,61 @@
  * along with this program.  If not, see <https://www.gnu.org/licenses/>.
  */
 
-use super::commands::run_sudo_command;
+use super::atomic::write_file_as_root;
+use super::distro::DistroFamily;
+use super::package_manager::for_family;
+use super::service::InitSystem;
+use super::target::Target;
+use super::template::{TemplateVars, read_source};
 use colored::Colorize;
 
+/// The bundled zram-generator configuration, relative to the glwiz binary.
+pub const ZRAM_CONFIG_SOURCE: &str = "../configs/zram-generator.conf";
+
+/// The system location of the zram-generator configuration.
+pub const ZRAM_CONFIG_PATH: &str = "/etc/systemd/zram-generator.conf";
+
+/// The location of the zram-init service configuration on OpenRC systems.
+pub const ZRAM_INIT_CONFIG_PATH: &str = "/etc/conf.d/zram-init";
+
+/// The OpenRC service, and the package providing it, that sets up zram devices at boot.
+pub const ZRAM_INIT_SERVICE: &str = "zram-init";
+
+/// The location of the zramen service configuration on runit systems.
+pub const ZRAMEN_CONFIG_PATH: &str = "/etc/sv/zramen/conf";
+
+/// The runit service, and the package providing it, that sets up zram swap on Void Linux.
+pub const ZRAMEN_SERVICE: &str = "zramen";
+
+// The size zram-generator uses when `zram-size` is not set.
+const DEFAULT_ZRAM_SIZE: &str = "min(ram / 2, 4096)";
+
+/// Returns the location of the zram configuration for an init system.
+///
+/// # Example
+/// ```
+/// use glwiz::functionality::{service::InitSystem, zram::config_path};
+/// assert_eq!(config_path(InitSystem::OpenRc), "/etc/conf.d/zram-init");
+/// assert_eq!(config_path(InitSystem::Runit), "/etc/sv/zramen/conf");
+/// assert_eq!(config_path(InitSystem::Systemd), "/etc/systemd/zram-generator.conf");
+/// ```
+pub fn config_path(init: InitSystem) -> &'static str {
+    match init {
+        InitSystem::Systemd => ZRAM_CONFIG_PATH,
+        InitSystem::OpenRc => ZRAM_INIT_CONFIG_PATH,
+        InitSystem::Runit => ZRAMEN_CONFIG_PATH,
+    }
+}
+
+// Returns the service that sets up zram on init systems without the zram-generator.
+fn zram_service(init: InitSystem) -> Option<&'static str> {
+    match init {
+        InitSystem::Systemd => None,
+        InitSystem::OpenRc => Some(ZRAM_INIT_SERVICE),
+        InitSystem::Runit => Some(ZRAMEN_SERVICE),
+    }
+}
+
 /// Configures ZRAM swap to optimize system memory usage.
 ///
 /// This function sets up ZRAM (compressed RAM-based swap) by copying a predefined configuration
@@ -28,7 +80,22 @@ use colored::Colorize;
 /// of the "glwiz" project’s post-installation setup to enhance system performance by providing
 /// fast, compressed swap space. The function checks for the source file’s existence and prompts
 /// the user to overwrite the destination if it exists, ensuring idempotent operation. It uses
-/// `sudo` to write to the system directory, guaranteeing proper permissions.
+/// `sudo` to write to the system directory, guaranteeing proper permissions. A source ending in
+/// `.tmpl` is rendered with `vars` first, so the zram size can follow the host’s memory. On
+/// distributions that do not ship the zram-generator by default (e.g., openSUSE), its package
+/// is installed first.
+///
+/// OpenRC systems such as Alpine Linux have no zram-generator. There the configuration is
+/// translated by `zram_init_config` into `/etc/conf.d/zram-init`, and the `zram-init` package
+/// is installed and its service enabled with `rc-update`. On runit systems such as Void Linux,
+/// `zramen_config` produces the configuration of the `zramen` service, which is linked into
+/// `/var/service`.
+///
+/// # Arguments
+/// * `src` - The configuration to install (normally `ZRAM_CONFIG_SOURCE`).
+/// * `vars` - The variables available if the source is a template.
+/// * `target` - The system to configure; the destination is resolved below its root.
+/// * `family` - The distribution family, if known, used to install the zram-generator.
 ///
 /// # Returns
 /// * `0` - The ZRAM configuration was successfully applied or skipped (user chose not to overwrite).
@@ -36,31 +103,75 @@ use colored::Colorize;
 ///
 /// # Errors
 /// Returns `1` if:
-/// - The source file `../configs/zram-generator.conf` does not exist.
-/// - The copy operation fails due to permissions or `sudo` issues.
+/// - The source file (e.g., `../configs/zram-generator.conf`) does not exist.
+/// - The source is a template that fails to render.
+/// - The zram-generator or zram-init package cannot be installed.
+/// - The configuration cannot be translated for zram-init, or its service cannot be enabled.
+/// - The write operation fails due to permissions or `sudo` issues.
 ///
 /// # Example
 /// ```should_panic
 /// // Requires ../configs/zram-generator.conf and sudo privileges.
 /// // Use integration tests for actual validation.
-/// use glwiz::functionality::zram::zram_swap_setup;
-/// let result = zram_swap_setup();
+/// use glwiz::functionality::{target::Target, template::TemplateVars, zram::{zram_swap_setup, ZRAM_CONFIG_SOURCE}};
+/// let result = zram_swap_setup(ZRAM_CONFIG_SOURCE, &TemplateVars::new(), &Target::host(), None);
 /// assert_eq!(result, 0); // Success if config exists and no overwrite
 /// ```
-/// 
+///
 /// # See Also
-/// - `commands::run_sudo_command`: Used to copy the configuration file with `sudo`.
+/// - `atomic::write_file_as_root`: Used to write the configuration file with `sudo`.
 /// - `prog_fun::read_input`: Used to prompt for overwrite confirmation.
-pub fn zram_swap_setup() -> i8 {
-    let src = "../configs/zram-generator.conf";
-    let dest = "/etc/systemd/zram-generator.conf";
+pub fn zram_swap_setup(
+    src: &str,
+    vars: &TemplateVars,
+    target: &Target,
+    family: Option<DistroFamily>,
+) -> i8 {
+    let init = InitSystem::detect(target, family);
+    let dest = target.path(config_path(init));
 
     if !std::path::Path::new(src).exists() {
         eprintln!("{} Source file {} not found.", "error:".red(), src);
         return 1;
     }
 
-    if std::path::Path::new(dest).exists() {
+    let config = match read_source(src, vars) {
+        Ok(c) => c,
+        Err(e) => {
+            eprintln!("{} {}", "error:".red(), e);
+            return 1;
+        }
+    };
+    let ram_mb = vars
+        .get("ram_mb")
+        .and_then(|mb| mb.parse().ok())
+        .unwrap_or(0);
+    let config = match init {
+        InitSystem::Systemd => Ok(config),
+        InitSystem::OpenRc => zram_init_config(&config, ram_mb),
+        InitSystem::Runit => zramen_config(&config, ram_mb),
+    };
+    let config = match config {
+        Ok(c) => c,
+        Err(e) => {
+            eprintln!("{} {}", "error:".red(), e);
+            return 1;
+        }
+    };
+
+    let package = zram_service(init).or_else(|| family.and_then(|f| f.zram_package()));
+    if let Some((family, package)) = family.zip(package) {
+        let manager = for_family(family, target);
+        if !manager.is_installed(package).unwrap_or(false) {
+            println!("Installing {}", package.green());
+            if let Err(e) = manager.install(&[package]) {
+                eprintln!("{} Failed to install {}: {}", "error:".red(), package, e);
+                return 1;
+            }
+        }
+    }
+
+    if std::path::Path::new(&dest).exists() {
         println!("{} exists. Overwrite? (y/n)", dest);
         let input = super::prog_fun::read_input().trim().to_lowercase();
         if input != "y" {
@@ -69,7 +180,11 @@ pub fn zram_swap_setup() -> i8 {
         }
     }
 
-    match run_sudo_command("cp", &[src, dest]) {
+    let result = write_file_as_root(&dest, &config).and_then(|()| match zram_service(init) {
+        Some(service) => init.enable(service, target),
+        None => Ok(()),
+    });
+    match result {
         Ok(_) => {
             println!("ZRAM {}.", "configured".green());
             0
@@ -80,3 +195,253 @@ pub fn zram_swap_setup() -> i8 {
         }
     }
 }
+
+/// Translates a zram-generator configuration into the zram-init service configuration.
+///
+/// The `[zram0]` section’s `zram-size` (default `min(ram / 2, 4096)`) is evaluated for the given
+/// memory size, supporting numbers, `ram`, `+`, `-`, `*`, `/`, parentheses, `min` and `max`,
+/// and its `compression-algorithm` is kept. The result configures a single swap device.
+///
+/// # Arguments
+/// * `generator_config` - The contents of a `zram-generator.conf` file.
+/// * `ram_mb` - The installed memory in MiB.
+///
+/// # Returns
+/// * `Ok(String)` - The contents of `/etc/conf.d/zram-init`.
+/// * `Err(String)` - An error message if the memory size is unknown or `zram-size` is invalid.
+///
+/// # Example
+/// ```
+/// use glwiz::functionality::zram::zram_init_config;
+/// let config = zram_init_config("[zram0]\nzram-size = ram / 2\ncompression-algorithm = zstd\n", 8192).unwrap();
+/// assert!(config.contains("size0=4096\n"));
+/// assert!(config.contains("algo0=zstd\n"));
+/// assert!(zram_init_config("[zram0]\nzram-size = half\n", 8192).is_err());
+/// ```
+pub fn zram_init_config(generator_config: &str, ram_mb: u64) -> Result<String, String> {
+    let settings = GeneratorSettings::parse(generator_config, ram_mb)?;
+    let mut config = String::from(
+        "# Generated by glwiz from zram-generator.conf\n\
+         load_on_start=yes\n\
+         unload_on_stop=yes\n\
+         num_devices=1\n\
+         type0=swap\n",
+    );
+    config.push_str(&format!("size0={}\n", settings.size_mb));
+    if let Some(algorithm) = &settings.algorithm {
+        config.push_str(&format!("algo0={}\n", algorithm));
+    }
+    config.push_str("labl0=zram_swap\n");
+    Ok(config)
+}
+
+/// Translates a zram-generator configuration into the zramen service configuration.
+///
+/// zramen sizes the device as a percentage of the memory, so `zram-size` is evaluated as for
+/// `zram_init_config` and converted; `compression-algorithm` and `swap-priority` are kept.
+///
+/// # Arguments
+/// * `generator_config` - The contents of a `zram-generator.conf` file.
+/// * `ram_mb` - The installed memory in MiB.
+///
+/// # Returns
+/// * `Ok(String)` - The contents of `/etc/sv/zramen/conf`.
+/// * `Err(String)` - An error message if the memory size is unknown or `zram-size` is invalid.
+///
+/// # Example
+/// ```
+/// use glwiz::functionality::zram::zramen_config;
+/// let config = zramen_config("[zram0]\nzram-size = ram / 2\nswap-priority = 100\n", 8192).unwrap();
+/// assert!(config.contains("export ZRAM_SIZE=50\n"));
+/// assert!(config.contains("export ZRAM_PRIORITY=100\n"));
+/// ```
+pub fn zramen_config(generator_config: &str, ram_mb: u64) -> Result<String, String> {
+    let settings = GeneratorSettings::parse(generator_config, ram_mb)?;
+    let percent = (settings.size_mb * 100).div_ceil(ram_mb).max(1);
+    let mut config = format!(
+        "# Generated by glwiz from zram-generator.conf\nexport ZRAM_SIZE={}\n",
+        percent
+    );
+    if let Some(algorithm) = &settings.algorithm {
+        config.push_str(&format!("export ZRAM_COMP_ALGORITHM={}\n", algorithm));
+    }
+    if let Some(priority) = &settings.priority {
+        config.push_str(&format!("export ZRAM_PRIORITY={}\n", priority));
+    }
+    Ok(config)
+}
+
+/// Translates a zram-generator configuration into the `zramSwap` options of a NixOS module.
+///
+/// NixOS sizes the device as a percentage of the memory, so `zram-size` is converted as for
+/// `zramen_config`; `compression-algorithm` and `swap-priority` are kept.
+///
+/// # Arguments
+/// * `generator_config` - The contents of a `zram-generator.conf` file.
+/// * `ram_mb` - The installed memory in MiB.
+///
+/// # Returns
+/// * `Ok(String)` - The `zramSwap = { ... };` attribute, indented for a module’s top level.
+/// * `Err(String)` - An error message if the memory size is unknown or `zram-size` is invalid.
+///
+/// # Example
+/// ```
+/// use glwiz::functionality::zram::nixos_zram_swap;
+/// let zram = nixos_zram_swap("[zram0]\nzram-size = ram / 2\ncompression-algorithm = zstd\n", 8192).unwrap();
+/// assert!(zram.contains("memoryPercent = 50;"));
+/// assert!(zram.contains("algorithm = \"zstd\";"));
+/// ```
+pub fn nixos_zram_swap(generator_config: &str, ram_mb: u64) -> Result<String, String> {
+    let settings = GeneratorSettings::parse(generator_config, ram_mb)?;
+    let percent = (settings.size_mb * 100).div_ceil(ram_mb).max(1);
+    let mut zram = format!(
+        "  zramSwap = {{\n    enable = true;\n    memoryPercent = {};\n",
+        percent
+    );
+    if let Some(algorithm) = &settings.algorithm {
+        zram.push_str(&format!("    algorithm = \"{}\";\n", algorithm));
+    }
+    if let Some(priority) = &settings.priority {
+        zram.push_str(&format!("    priority = {};\n", priority));
+    }
+    zram.push_str("  };\n");
+    Ok(zram)
+}
+
+// The settings of the `[zram0]` section of a zram-generator configuration.
+struct GeneratorSettings {
+    size_mb: u64,
+    algorithm: Option<String>,
+    priority: Option<String>,
+}
+
+impl GeneratorSettings {
+    // Reads the `[zram0]` section and evaluates its size for `ram_mb` MiB of memory.
+    fn parse(generator_config: &str, ram_mb: u64) -> Result<Self, String> {
+        if ram_mb == 0 {
+            return Err("Cannot determine the memory size for the zram device".to_string());
+        }
+
+        let mut section = String::new();
+        let mut size = DEFAULT_ZRAM_SIZE.to_string();
+        let mut algorithm = None;
+        let mut priority = None;
+        for line in generator_config.lines().map(str::trim) {
+            if line.starts_with('[') {
+                section = line.trim_matches(['[', ']']).to_string();
+            } else if let Some((key, value)) = line.split_once('=') {
+                if section != "zram0" || line.starts_with(['#', ';']) {
+                    continue;
+                }
+                let value = value.trim();
+                match key.trim() {
+                    "zram-size" => size = value.to_string(),
+                    "compression-algorithm" => {
+                        algorithm = value.split_whitespace().next().map(String::from)
+                    }
+                    "swap-priority" => priority = Some(value.to_string()),
+                    _ => {}
+                }
+            }
+        }
+
+        let size_mb = SizeExpr::new(&size, ram_mb as f64)
+            .eval()
+            .filter(|mb| mb.is_finite() && *mb >= 1.0)
+            .ok_or(format!("Invalid zram-size: {}", size))?;
+        Ok(Self {
+            size_mb: size_mb as u64,
+            algorithm,
+            priority,
+        })
+    }
+}
+
+// Evaluates a zram-generator size expression by recursive descent.
+struct SizeExpr<'a> {
+    input: &'a str,
+    ram: f64,
+}
+
+impl<'a> SizeExpr<'a> {
+    fn new(input: &'a str, ram: f64) -> Self {
+        Self {
+            input: input.trim(),
+            ram,
+        }
+    }
+
+    // Evaluates the whole input, which must be a single expression.
+    fn eval(mut self) -> Option<f64> {
+        let value = self.sum()?;
+        self.input.is_empty().then_some(value)
+    }
+
+    // Consumes `token` if the input starts with it.
+    fn eat(&mut self, token: char) -> bool {
+        match self.input.strip_prefix(token) {
+            Some(rest) => {
+                self.input = rest.trim_start();
+                true
+            }
+            None => false,
+        }
+    }
+
+    fn sum(&mut self) -> Option<f64> {
+        let mut value = self.product()?;
+        loop {
+            if self.eat('+') {
+                value += self.product()?;
+            } else if self.eat('-') {
+                value -= self.product()?;
+            } else {
+                return Some(value);
+            }
+        }
+    }
+
+    fn product(&mut self) -> Option<f64> {
+        let mut value = self.atom()?;
+        loop {
+            if self.eat('*') {
+                value *= self.atom()?;
+            } else if self.eat('/') {
+                value /= self.atom()?;
+            } else {
+                return Some(value);
+            }
+        }
+    }
+
+    fn atom(&mut self) -> Option<f64> {
+        if self.eat('(') {
+            let value = self.sum()?;
+            return self.eat(')').then_some(value);
+        }
+        let end = self
+            .input
+            .find(|c: char| !(c.is_ascii_alphanumeric() || c == '.' || c == '_'))
+            .unwrap_or(self.input.len());
+        let (word, rest) = self.input.split_at(end);
+        self.input = rest.trim_start();
+        match word {
+            "ram" => Some(self.ram),
+            "min" | "max" => {
+                if !self.eat('(') {
+                    return None;
+                }
+                let a = self.sum()?;
+                if !self.eat(',') {
+                    return None;
+                }
+                let b = self.sum()?;
+                if !self.eat(')') {
+                    return None;
+                }
+                Some(if word == "min" { a.min(b) } else { a.max(b) })
+            }
+            _ => word.parse().ok(),
+        }
+    }
+}
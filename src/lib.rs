@@ -27,29 +27,89 @@
 ///
 /// # Example
 /// ```
-/// use glwiz::functionality::zram::zram_swap_setup;
-/// let result = zram_swap_setup();
+/// use glwiz::functionality::{target::Target, template::TemplateVars, zram::{zram_swap_setup, ZRAM_CONFIG_SOURCE}};
+/// let result = zram_swap_setup(ZRAM_CONFIG_SOURCE, &TemplateVars::new(), &Target::host(), None);
 /// assert_eq!(result, 0); // Successful ZRAM configuration
 /// ```
 pub mod functionality;
 
 use colored::Colorize;
 use functionality::{
-    configs::{setup_root_config, user_config_setup},
+    aur::aur_setup,
+    bundle::{Bundle, bundle_packages, print_bundles},
+    configs::{ConfigSources, setup_root_config, user_config_setup},
+    debloat::{debloat_setup, default_debloat},
+    distro::Distro,
     env::get_env_var,
+    flatpak::flatpak_setup,
+    interrupt::{install_handler, interrupted},
+    iptables::nixos_firewall,
     iptables::{iptables_file_setup, iptables_rules_setup},
+    journal::{self, RunStatus},
+    lock::RunLock,
+    nixos::{NIXOS_ID, NIXOS_OUTPUT_DIR, NixosSettings, nixos_modules_setup},
+    package_map::PackageMap,
+    package_tuning::package_tuning_setup,
+    preflight::{print_report, run_preflight},
+    profile::Profile,
     prog_fun::{
-        check_sw_install_type, default_sw_package, print_license_info, print_setup_status_success,
-        set_sw_list, validate_root_priviliges,
+        SoftwareChoice, check_sw_install_type, default_sw_package, print_license_info,
+        print_setup_status_success, select_bundles, set_sw_list, validate_root_priviliges,
     },
+    remote::remote_setup,
+    repositories::repositories_setup,
     shell::{
-        change_def_shell, install_omz, install_zsh_autosuggestions, install_zsh_syntax_highlighting,
+        change_def_shell, install_omz, install_zsh_autosuggestions, install_zsh_plugin,
+        install_zsh_syntax_highlighting, omz_settings, zsh_path,
     },
+    snapshot::snapshot_system,
     software::software_setup,
-    task::{validate_task_statuses, TaskResult},
+    target::Target,
+    task::{TaskResult, run_task, run_task_with_details, validate_task_statuses},
+    template::{read_source, system_vars},
     user_cfg::UserCfg,
-    zram::zram_swap_setup,
+    zram::{nixos_zram_swap, zram_swap_setup},
 };
+use std::{os::unix::fs::DirBuilderExt, path::PathBuf};
+
+/// Options for `gnu_linux_default_setup`.
+///
+/// # Fields
+/// * `allow_root` - Enables execution with root privileges if `true`.
+/// * `target` - The system to configure (the running host by default).
+/// * `profile` - A profile to apply instead of the interactive package prompt and the bundled
+///   configuration files.
+/// * `resume` - Continues the previous interrupted run, skipping the tasks it completed.
+/// * `wait` - Waits for another running glwiz instance to finish instead of failing.
+/// * `distro` - The distribution to configure for, overriding detection from os-release.
+/// * `home_manager` - On NixOS, also generates a home-manager module.
+/// * `upgrade` - Upgrades the whole system before installing software.
+/// * `bundles` - Package bundles to install instead of prompting for the software list.
+/// * `debloat` - Removes the distribution’s default list of unwanted preinstalled packages.
+///
+/// # Example
+/// ```
+/// use glwiz::SetupOptions;
+/// let options = SetupOptions {
+///     allow_root: true,
+///     ..Default::default()
+/// };
+/// assert!(!options.target.is_chroot());
+/// assert!(options.profile.is_none());
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct SetupOptions {
+    pub allow_root: bool,
+    pub target: Target,
+    pub profile: Option<Profile>,
+    pub resume: bool,
+    pub wait: bool,
+    pub distro: Option<Distro>,
+    pub home_manager: bool,
+    pub upgrade: bool,
+    pub bundles: Vec<&'static Bundle>,
+    pub debloat: bool,
+}
 
 /// Orchestrates a default post-installation setup for a GNU/Linux system.
 ///
@@ -58,6 +118,7 @@ use functionality::{
 /// - Validating root privileges (with an option to allow root execution).
 /// - Setting up user and root environments.
 /// - Configuring iptables firewall rules.
+/// - Removing unwanted preinstalled packages.
 /// - Installing software (default or user-specified packages).
 /// - Setting Zsh as the default shell with plugins (Oh My Zsh, autosuggestions, syntax highlighting).
 /// - Installing Vim configuration.
@@ -67,9 +128,38 @@ use functionality::{
 /// The setup is designed to be idempotent where possible, checking for existing configurations
 /// to avoid redundant operations.
 ///
+/// The setup can target a system mounted at an alternate root (e.g., `/mnt` right after
+/// `pacstrap` or `debootstrap`). In that case every system and home path is resolved below the
+/// root, packages are installed through the distribution’s chroot tool, and login shells are
+/// changed in the target’s `/etc/passwd`.
+///
+/// When a profile is given, its packages are installed without prompting, its login shell and
+/// custom Zsh plugins replace the defaults, and the configuration files it carries are installed
+/// instead of the bundled ones. Anything the profile leaves out keeps the default behavior.
+/// The apps a profile lists as Flatpaks are installed from Flathub instead of natively (see
+/// `flatpak::flatpak_setup`), and on Arch the packages it lists from the AUR are built with an
+/// AUR helper as the current user (see `aur::aur_setup`).
+///
+/// Before any change is made, the preflight checks from `preflight::run_preflight` verify the
+/// required commands, `sudo` rights, disk space, package manager state, destinations, network
+/// access and distribution; if any fails, all problems are reported and the setup stops.
+///
+/// Ctrl-C lets the current task finish and then stops the run. Every file is written atomically
+/// and backed up first, and the run is recorded in the state directory (see `journal`), so an
+/// interrupted run can be continued with `resume` or its file changes reverted with
+/// `gnu_linux_undo`. Only one run can be in progress at a time: an exclusive lock on
+/// `/run/glwiz.lock` and a per-user lock file are held until the setup returns.
+///
+/// Configuration sources ending in `.tmpl` are rendered before they are installed, with the
+/// system variables from `template::system_vars` and the profile’s `[vars]`.
+///
+/// On NixOS the system is not changed at all. Instead, a NixOS module (and, with
+/// `home_manager`, a home-manager module) covering the same setup is written to
+/// `nixos::NIXOS_OUTPUT_DIR`, ready to be imported and applied with `nixos-rebuild`.
+///
 /// # Arguments
-/// * `allow_root` - Enables execution with root privileges if `true`. If `false`, the program
-///   exits if run as root unless explicitly allowed.
+/// * `options` - The setup options: whether root execution is allowed, the system to configure,
+///   and an optional profile to apply.
 ///
 /// # Returns
 /// * `Ok(())` - All tasks completed successfully.
@@ -78,26 +168,42 @@ use functionality::{
 /// # Errors
 /// Returns an error if:
 /// - Environment variables (`USER`, `HOME`) are unset.
+/// - The profile’s configuration files cannot be staged.
 /// - Any task (e.g., software installation, iptables setup) fails.
 /// - Root privileges are required but not allowed.
+/// - Another glwiz run holds the run lock and `wait` is not set.
+/// - A preflight check fails (nothing is changed in that case).
 ///
 /// # Example
 /// ```
-/// let result = glwiz::gnu_linux_default_setup(true);
+/// use glwiz::SetupOptions;
+/// let options = SetupOptions {
+///     allow_root: true,
+///     ..Default::default()
+/// };
+/// let result = glwiz::gnu_linux_default_setup(&options);
 /// match result {
 ///     Ok(()) => println!("Setup completed successfully!"),
 ///     Err(e) => eprintln!("Setup failed: {}", e),
 /// }
 /// ```
-pub fn gnu_linux_default_setup(allow_root: bool) -> Result<(), String> {
+pub fn gnu_linux_default_setup(options: &SetupOptions) -> Result<(), String> {
+    let allow_root = options.allow_root;
+    let target = &options.target;
+    let profile = options.profile.as_ref();
+    let upgrade = options.upgrade;
     let mut tasks = Vec::new();
 
+    // Prevent concurrent runs from racing on the package manager and configuration files
+    let _lock = RunLock::acquire(options.wait)?;
+
     // Print license info
     print_license_info();
 
     tasks.push(TaskResult {
         status: 0,
         message: "License info displayed".to_string(),
+        details: Vec::new(),
     });
 
     // Validate root privileges
@@ -106,128 +212,511 @@ pub fn gnu_linux_default_setup(allow_root: bool) -> Result<(), String> {
     tasks.push(TaskResult {
         status: if is_root || !allow_root { 0 } else { 1 },
         message: "Root privilege validation".to_string(),
+        details: Vec::new(),
     });
 
     // Set up user configuration
     let mut user_cfg = UserCfg::new();
-    let user_name = match get_env_var("USER") {
-        Ok(name) => name,
-        Err(e) => return Err(e),
-    };
-    let home_dir = match get_env_var("HOME") {
-        Ok(dir) => dir,
-        Err(e) => return Err(e),
-    };
+    let user_name = get_env_var("USER")?;
+    let home_dir = get_env_var("HOME")?;
     user_cfg.set_name(&user_name)?;
-    user_cfg.set_home(&home_dir)?;
+    user_cfg.set_home(&target.path(&home_dir))?;
     println!("username: {}", user_cfg.get_name().green());
     println!("home location: {}", user_cfg.get_home().green());
 
     tasks.push(TaskResult {
         status: 0,
         message: "User configuration set".to_string(),
+        details: Vec::new(),
     });
 
+    if target.is_chroot() {
+        println!("target root: {}", target.get_root().green());
+    }
+
     // Detect distribution
-    let distro = detect_distro().unwrap_or_else(|| "unknown".to_string());
+    let distro = options.distro.clone().or_else(|| Distro::detect(target));
+    let family = distro.as_ref().and_then(|d| d.family);
+    let family_name = distro.as_ref().map_or("unknown", |d| d.family_name());
+    if let Some(distro) = &distro {
+        println!("distribution: {}", distro.to_string().green());
+    }
+
+    // NixOS is configured declaratively: generate modules instead of changing the system
+    if distro.as_ref().is_some_and(Distro::is_nixos) {
+        return nixos_setup(options, &user_cfg, distro.as_ref());
+    }
+
+    // Check the system before anything is changed
+    if !preflight(options, user_cfg.get_home()) {
+        return Err("Preflight checks failed.".to_string());
+    }
+
+    // Stage the profile's configuration files
+    if profile.is_some() {
+        println!("{}", "Applying profile".green());
+    }
+    let (sources, _staging_dir) = stage_profile(profile)?;
+
+    // Record the run so that it can be resumed or undone after an interrupt
+    install_handler();
+    journal::begin(options.resume)?;
+
+    // Collect the variables for templated configuration files
+    let mut vars = system_vars(
+        user_cfg.get_name(),
+        user_cfg.get_home(),
+        distro.as_ref(),
+        target,
+    );
+    if let Some(profile) = profile {
+        profile.add_template_vars(&mut vars);
+    }
 
     // Set up iptables
-    tasks.push(TaskResult {
-        status: iptables_file_setup(),
-        message: "iptables file setup".to_string(),
+    run_task(&mut tasks, "iptables file setup", || {
+        iptables_file_setup(&sources.iptables, &vars, target, family)
     });
 
-    tasks.push(TaskResult {
-        status: iptables_rules_setup(),
-        message: "iptables rules setup".to_string(),
+    run_task(&mut tasks, "iptables rules setup", || {
+        iptables_rules_setup(target, family)
     });
 
-    // Install software
-    let sw_result = if check_sw_install_type() {
-        let package_strings = set_sw_list();
-        let package_slices: Vec<&str> = package_strings.iter().map(|s| s.as_str()).collect();
-        software_setup(&package_slices, &distro)
-    } else {
-        software_setup(default_sw_package(), &distro)
+    // Tune the package manager so that the installation benefits from it
+    let tuning = profile
+        .map(|p| p.packages.tuning.clone())
+        .unwrap_or_default();
+    run_task(&mut tasks, "Package manager tuning", || {
+        package_tuning_setup(&tuning, family, target)
+    });
+
+    // Enable the repositories the profile opts into before installing from them
+    let repositories = profile
+        .map(|p| p.packages.repositories.as_slice())
+        .unwrap_or_default();
+    if !repositories.is_empty() {
+        run_task(&mut tasks, "Repository setup", || match &distro {
+            Some(distro) => repositories_setup(repositories, distro, target),
+            None => {
+                eprintln!("{} Unknown distribution.", "error:".red());
+                1
+            }
+        });
+    }
+
+    // Remove unwanted preinstalled packages before installing new ones
+    let mut debloat: Vec<&str> = match (&distro, options.debloat) {
+        (Some(distro), true) => default_debloat(distro).to_vec(),
+        _ => Vec::new(),
     };
+    if let Some(profile) = profile {
+        debloat.extend(profile.packages.debloat.iter().map(|s| s.as_str()));
+    }
+    if options.debloat || !debloat.is_empty() {
+        run_task(&mut tasks, "Package removal", || match &distro {
+            Some(distro) => debloat_setup(&debloat, distro, target),
+            None => {
+                eprintln!("{} Unknown distribution.", "error:".red());
+                1
+            }
+        });
+    }
 
-    tasks.push(TaskResult {
-        status: sw_result,
-        message: "Software installation".to_string(),
+    // Install software, leaving the apps installed as Flatpaks out of the native packages
+    let flatpak_apps: Vec<&str> = profile
+        .map(|p| p.packages.flatpak.iter().map(|s| s.as_str()).collect())
+        .unwrap_or_default();
+    run_task_with_details(&mut tasks, "Software installation", || {
+        let package_strings = chosen_packages(options);
+        let package_slices: Vec<&str> = package_strings.iter().map(|s| s.as_str()).collect();
+        let report = software_setup(
+            &native_packages(&package_slices, &flatpak_apps),
+            family_name,
+            target,
+            upgrade,
+        );
+        (report.status, report.details())
     });
 
+    let aur_packages: Vec<&str> = profile
+        .map(|p| p.packages.aur.iter().map(|s| s.as_str()).collect())
+        .unwrap_or_default();
+    if !aur_packages.is_empty() {
+        let helper = profile.map(|p| p.packages.aur_helper).unwrap_or_default();
+        run_task_with_details(&mut tasks, "AUR installation", || {
+            let report = aur_setup(&aur_packages, helper, family_name, target);
+            (report.status, report.details())
+        });
+    }
+
+    if !flatpak_apps.is_empty() {
+        let scope = profile
+            .map(|p| p.packages.flatpak_scope)
+            .unwrap_or_default();
+        run_task_with_details(&mut tasks, "Flatpak installation", || {
+            let report = flatpak_setup(&flatpak_apps, scope, family_name, target);
+            (report.status, report.details())
+        });
+    }
+
     // Configure shells
-    tasks.push(TaskResult {
-        status: change_def_shell(user_cfg.get_name()),
-        message: format!("Shell change for {}", user_cfg.get_name()),
-    });
+    let shell = profile
+        .and_then(|p| p.shell.login_shell.clone())
+        .unwrap_or_else(|| zsh_path(target));
+    run_task(
+        &mut tasks,
+        &format!("Shell change for {}", user_cfg.get_name()),
+        || change_def_shell(user_cfg.get_name(), &shell, target),
+    );
 
-    tasks.push(TaskResult {
-        status: change_def_shell("root"),
-        message: "Shell change for root".to_string(),
+    run_task(&mut tasks, "Shell change for root", || {
+        change_def_shell("root", &shell, target)
     });
 
     // Set up Zsh
-    tasks.push(TaskResult {
-        status: install_omz(),
-        message: "Oh My Zsh installation".to_string(),
+    run_task(&mut tasks, "Oh My Zsh installation", || {
+        install_omz(user_cfg.get_home())
     });
 
-    tasks.push(TaskResult {
-        status: install_zsh_autosuggestions(user_cfg.get_home()),
-        message: "Zsh autosuggestions installation".to_string(),
-    });
+    match profile.map(|p| &p.shell.custom_plugins) {
+        Some(plugins) if !plugins.is_empty() => {
+            for plugin in plugins {
+                run_task(
+                    &mut tasks,
+                    &format!("Zsh plugin {} installation", plugin.name),
+                    || install_zsh_plugin(user_cfg.get_home(), &plugin.name, &plugin.repo),
+                );
+            }
+        }
+        _ => {
+            run_task(&mut tasks, "Zsh autosuggestions installation", || {
+                install_zsh_autosuggestions(user_cfg.get_home())
+            });
 
-    tasks.push(TaskResult {
-        status: install_zsh_syntax_highlighting(user_cfg.get_home()),
-        message: "Zsh syntax highlighting installation".to_string(),
-    });
+            run_task(&mut tasks, "Zsh syntax highlighting installation", || {
+                install_zsh_syntax_highlighting(user_cfg.get_home())
+            });
+        }
+    }
 
-    tasks.push(TaskResult {
-        status: user_config_setup("../configs/.zshrc", user_cfg.get_home(), "zsh"),
-        message: "Zsh user configuration".to_string(),
+    run_task(&mut tasks, "Zsh user configuration", || {
+        user_config_setup(&sources.zshrc, user_cfg.get_home(), "zsh", &vars)
     });
 
     // Set up Vim
-    tasks.push(TaskResult {
-        status: user_config_setup("../configs/.vimrc", user_cfg.get_home(), "vim"),
-        message: "Vim user configuration".to_string(),
+    run_task(&mut tasks, "Vim user configuration", || {
+        user_config_setup(&sources.vimrc, user_cfg.get_home(), "vim", &vars)
     });
 
     // Configure root
-    tasks.push(TaskResult {
-        status: setup_root_config(user_cfg.get_home()),
-        message: "Root configuration".to_string(),
+    run_task(&mut tasks, "Root configuration", || {
+        setup_root_config(user_cfg.get_home(), target)
     });
 
     // Set up ZRAM
-    tasks.push(TaskResult {
-        status: zram_swap_setup(),
-        message: "ZRAM swap setup".to_string(),
+    run_task(&mut tasks, "ZRAM swap setup", || {
+        zram_swap_setup(&sources.zram, &vars, target, family)
     });
 
+    if interrupted() {
+        journal::finish(RunStatus::Interrupted);
+        return Err(
+            "Setup interrupted. Run `glwiz run --resume` to continue or `glwiz undo` to restore \
+             the changed files."
+                .to_string(),
+        );
+    }
+
     // Validate all tasks
     if validate_task_statuses(tasks) {
+        journal::finish(RunStatus::Completed);
         print_setup_status_success();
         Ok(())
     } else {
+        journal::finish(RunStatus::Failed);
         Err("Setup failed. Check logs for details.".to_string())
     }
 }
 
-// Detects the Linux distribution by checking for specific release files.
+// Writes the NixOS modules for the setup of a NixOS system, from the same package list,
+// profile and configuration sources a regular run would use.
+fn nixos_setup(
+    options: &SetupOptions,
+    user_cfg: &UserCfg,
+    distro: Option<&Distro>,
+) -> Result<(), String> {
+    let profile = options.profile.as_ref();
+    let (sources, _staging_dir) = stage_profile(profile)?;
+    let mut vars = system_vars(
+        user_cfg.get_name(),
+        user_cfg.get_home(),
+        distro,
+        &options.target,
+    );
+    if let Some(profile) = profile {
+        profile.add_template_vars(&mut vars);
+    }
+
+    let packages = chosen_packages(options);
+    let packages: Vec<&str> = packages.iter().map(String::as_str).collect();
+    let resolution = PackageMap::load().resolve(&packages, NIXOS_ID);
+    resolution.report(NIXOS_ID);
+    let custom_plugins = match profile.map(|p| &p.shell.custom_plugins) {
+        Some(plugins) if !plugins.is_empty() => plugins.iter().map(|p| p.name.clone()).collect(),
+        _ => vec![
+            "zsh-autosuggestions".to_string(),
+            "zsh-syntax-highlighting".to_string(),
+        ],
+    };
+    let ram_mb = vars
+        .get("ram_mb")
+        .and_then(|mb| mb.parse().ok())
+        .unwrap_or(0);
+    let (omz_theme, omz_plugins) = omz_settings(&read_source(&sources.zshrc, &vars)?);
+    let settings = NixosSettings {
+        packages: resolution.packages,
+        omz_theme,
+        omz_plugins,
+        custom_plugins,
+        firewall: nixos_firewall(&read_source(&sources.iptables, &vars)?),
+        zram_swap: nixos_zram_swap(&read_source(&sources.zram, &vars)?, ram_mb)?,
+    };
+    let vimrc = read_source(&sources.vimrc, &vars)?;
+
+    let status = nixos_modules_setup(
+        &settings,
+        Some(&vimrc),
+        NIXOS_OUTPUT_DIR,
+        options.home_manager,
+    );
+    if status == 0 {
+        print_setup_status_success();
+        Ok(())
+    } else {
+        Err("NixOS module generation failed.".to_string())
+    }
+}
+
+/// Applies the default setup to one or more remote hosts over SSH.
+///
+/// Each host is configured in turn with `remote_setup`, which copies the glwiz binary and the
+/// bundled configuration assets to the host and runs the same tasks there, streaming the output
+/// prefixed with the host name. Once every host has been processed, a combined report lists the
+/// outcome per host.
+///
+/// # Arguments
+/// * `hosts` - The SSH destinations to configure (e.g., `["admin@lab1", "admin@lab2"]`).
+/// * `setup_args` - Arguments forwarded to the remote `glwiz run` (e.g., `&["--allow-root"]`).
+///
+/// # Returns
+/// * `Ok(())` - The setup succeeded on every host.
+/// * `Err(String)` - A summary naming the number of hosts that failed.
+///
+/// # Example
+/// ```no_run
+/// // Requires SSH access to the hosts.
+/// let hosts = vec!["admin@lab1".to_string(), "admin@lab2".to_string()];
+/// let result = glwiz::gnu_linux_remote_setup(&hosts, &[]);
+/// assert!(result.is_ok());
+/// ```
+pub fn gnu_linux_remote_setup(hosts: &[String], setup_args: &[&str]) -> Result<(), String> {
+    if hosts.is_empty() {
+        return Err("No hosts given.".to_string());
+    }
+
+    let mut tasks = Vec::new();
+    for host in hosts {
+        println!("{} {}", "Configuring".yellow(), host.green());
+        tasks.push(TaskResult {
+            status: remote_setup(host, setup_args),
+            message: format!("Remote setup on {}", host),
+            details: Vec::new(),
+        });
+    }
+
+    println!("\nRemote setup report:");
+    for task in &tasks {
+        let outcome = if task.status == 0 {
+            "ok".green()
+        } else {
+            "failed".red()
+        };
+        println!("- {}: {}", task.message, outcome);
+    }
+
+    let failed = tasks.iter().filter(|t| t.status != 0).count();
+    if validate_task_statuses(tasks) {
+        print_setup_status_success();
+        Ok(())
+    } else {
+        Err(format!(
+            "Setup failed on {} of {} host(s).",
+            failed,
+            hosts.len()
+        ))
+    }
+}
+
+/// Captures the configuration of the running system as a TOML profile.
+///
+/// The profile records the current user’s packages, shell settings and configuration files, and
+/// can be applied to another machine with `gnu_linux_default_setup` (`glwiz run --profile`).
+///
+/// # Returns
+/// * `Ok(String)` - The profile as a TOML document.
+/// * `Err(String)` - An error message if `USER` or `HOME` is unset or serialization fails.
+///
+/// # Example
+/// ```no_run
+/// // Requires sudo privileges to read the iptables rules.
+/// let profile = glwiz::gnu_linux_snapshot().unwrap();
+/// std::fs::write("profile.toml", profile).unwrap();
+/// ```
+pub fn gnu_linux_snapshot() -> Result<String, String> {
+    let user_name = get_env_var("USER")?;
+    let home_dir = get_env_var("HOME")?;
+    snapshot_system(&user_name, &home_dir).to_toml()
+}
+
+/// Restores the files changed by the last recorded setup run.
+///
+/// # Returns
+/// * `Ok(())` - The files were restored.
+/// * `Err(String)` - An error message if another glwiz run is in progress, there is no run to
+///   undo, or a file could not be restored.
+///
+/// # Example
+/// ```no_run
+/// // Requires a recorded run and sudo privileges.
+/// glwiz::gnu_linux_undo().unwrap();
+/// ```
+///
+/// # See Also
+/// - `journal::undo_last_run`: Performs the restoration.
+pub fn gnu_linux_undo() -> Result<(), String> {
+    let _lock = RunLock::acquire(false)?;
+    journal::undo_last_run()
+}
+
+/// Runs the preflight checks of the default setup without changing anything.
+///
+/// # Arguments
+/// * `options` - The setup options; the target and the profile’s package list are checked.
+///
+/// # Returns
+/// * `Ok(())` - Every check passed (warnings are allowed).
+/// * `Err(String)` - An error message if `USER` or `HOME` is unset or a check failed.
+///
+/// # Example
+/// ```no_run
+/// // Requires sudo privileges and network access.
+/// let result = glwiz::gnu_linux_preflight(&glwiz::SetupOptions::default());
+/// assert!(result.is_ok());
+/// ```
+///
+/// # See Also
+/// - `preflight::run_preflight`: The individual checks.
+pub fn gnu_linux_preflight(options: &SetupOptions) -> Result<(), String> {
+    let home_dir = options.target.path(&get_env_var("HOME")?);
+    if preflight(options, &home_dir) {
+        Ok(())
+    } else {
+        Err("Preflight checks failed.".to_string())
+    }
+}
+
+// Runs and prints the preflight checks for the packages the setup is expected to install.
 //
-// Returns `Some(String)` with the distribution name (e.g., "arch", "debian", "fedora")
-// if detected, or `None` if the distribution is unknown. This function is used to
-// tailor software installation commands to the detected distribution.
-fn detect_distro() -> Option<String> {
-    if std::path::Path::new("/etc/arch-release").exists() {
-        Some("arch".to_string())
-    } else if std::path::Path::new("/etc/debian_version").exists() {
-        Some("debian".to_string())
-    } else if std::path::Path::new("/etc/fedora-release").exists() {
-        Some("fedora".to_string())
+// Returns `true` if no check failed.
+fn preflight(options: &SetupOptions, home_dir: &str) -> bool {
+    let packages = match &options.profile {
+        Some(p) if !p.packages.native.is_empty() => p.packages.native.clone(),
+        _ if !options.bundles.is_empty() => bundle_packages(&options.bundles),
+        _ => default_sw_package().iter().map(|p| p.to_string()).collect(),
+    };
+    let packages: Vec<&str> = packages.iter().map(String::as_str).collect();
+    print_report(&run_preflight(
+        &options.target,
+        options.distro.as_ref(),
+        home_dir,
+        &packages,
+    ))
+}
+
+// Leaves the apps installed as Flatpaks out of a native package list. Profiles may list a
+// Flatpak by its app ID, so both lists are compared by logical name (see
+// `PackageMap::logical_name`).
+fn native_packages<'a>(packages: &[&'a str], flatpak_apps: &[&str]) -> Vec<&'a str> {
+    let map = PackageMap::load();
+    let logical = |name: &str| map.logical_name(name).unwrap_or(name).to_string();
+    let flatpak_names: Vec<String> = flatpak_apps.iter().map(|app| logical(app)).collect();
+    packages
+        .iter()
+        .copied()
+        .filter(|package| !flatpak_names.contains(&logical(package)))
+        .collect()
+}
+
+// Returns the native packages to install: the profile’s, those of the bundles given with
+// `--bundles` (rejected together with a profile that lists native packages, see `main.rs`), or
+// else the user’s choice of a custom list, bundles or the default list.
+// Selected bundles are shown with their contents first.
+fn chosen_packages(options: &SetupOptions) -> Vec<String> {
+    if let Some(profile) = &options.profile
+        && !profile.packages.native.is_empty()
+    {
+        return profile.packages.native.clone();
+    }
+    let bundles = if !options.bundles.is_empty() {
+        options.bundles.clone()
     } else {
-        None
+        match check_sw_install_type() {
+            SoftwareChoice::Custom => return set_sw_list(),
+            SoftwareChoice::Default => {
+                return default_sw_package().iter().map(|p| p.to_string()).collect();
+            }
+            SoftwareChoice::Bundles => select_bundles(),
+        }
+    };
+    println!("Installing the bundles:");
+    print_bundles(&bundles);
+    bundle_packages(&bundles)
+}
+
+// A private directory the profile’s configuration files are staged in, removed when dropped
+// so that every return path of the setup cleans it up.
+struct StagingDir(PathBuf);
+
+impl StagingDir {
+    // Creates `glwiz-profile-<pid>` in the temporary directory with mode 0700. A directory left
+    // behind by an earlier run is removed first; one owned by another user makes this fail
+    // instead of being reused.
+    fn create() -> Result<Self, String> {
+        let path = std::env::temp_dir().join(format!("glwiz-profile-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&path);
+        std::fs::DirBuilder::new()
+            .mode(0o700)
+            .create(&path)
+            .map_err(|e| format!("Failed to create {}: {}", path.display(), e))?;
+        Ok(Self(path))
+    }
+}
+
+impl Drop for StagingDir {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_dir_all(&self.0);
+    }
+}
+
+// Stages the profile’s configuration files, if a profile is given. The returned guard must be
+// kept alive for as long as the sources are used.
+fn stage_profile(profile: Option<&Profile>) -> Result<(ConfigSources, Option<StagingDir>), String> {
+    match profile {
+        Some(profile) => {
+            let staging_dir = StagingDir::create()?;
+            let sources = profile.stage_files(&staging_dir.0)?;
+            Ok((sources, Some(staging_dir)))
+        }
+        None => Ok((ConfigSources::default(), None)),
     }
 }
@@ -17,9 +17,23 @@
  * You should have received a copy of the GNU Affero General Public License
  * along with this program.  If not, see <https://www.gnu.org/licenses/>.
  */
- 
-use clap::Parser;
-use glwiz::gnu_linux_default_setup;
+
+use clap::{Parser, Subcommand, ValueEnum};
+use glwiz::{
+    SetupOptions,
+    functionality::{
+        bundle::parse_bundles,
+        distro::Distro,
+        export::{ExportFile, export_ansible, export_sh},
+        plan::default_plan,
+        profile::Profile,
+        prog_fun::default_sw_package,
+        remote::parse_inventory,
+        target::Target,
+    },
+    gnu_linux_default_setup, gnu_linux_preflight, gnu_linux_remote_setup, gnu_linux_snapshot,
+    gnu_linux_undo,
+};
 use std::process;
 
 /// Command-line arguments for the GNU/Linux Config Wizard.
@@ -28,24 +42,287 @@ use std::process;
      author = "Andrew Kushyk",
      version = "2.0.0",
      about = "A post-installation setup assistant for Linux, streamlining configuration with ease and precision.",
-     long_about = None
+     long_about = None,
+     args_conflicts_with_subcommands = true
  )]
 struct Args {
+    #[command(subcommand)]
+    command: Option<Commands>,
+
+    #[command(flatten)]
+    run: RunArgs,
+}
+
+/// Subcommands of the GNU/Linux Config Wizard.
+#[derive(Subcommand, Debug)]
+enum Commands {
+    /// Run the setup (the default when no subcommand is given)
+    Run(RunArgs),
+
+    /// Export the planned setup tasks without running them
+    Export(ExportArgs),
+
+    /// Print a profile of this system's packages, shell and configuration files as TOML
+    Snapshot,
+
+    /// Restore the files changed by the last setup run
+    Undo,
+
+    /// Check that the setup can run, without changing anything
+    Preflight(PreflightArgs),
+}
+
+/// Arguments for running the setup locally or on remote hosts.
+#[derive(clap::Args, Debug)]
+struct RunArgs {
     /// Allow execution with root privileges
     #[arg(long)]
     allow_root: bool,
+
+    /// Configure the system mounted at this directory (e.g. /mnt) instead of the running host
+    #[arg(long, value_name = "PATH")]
+    root: Option<String>,
+
+    /// Configure a remote host over SSH instead of this system (repeatable)
+    #[arg(long, value_name = "USER@HOST")]
+    host: Vec<String>,
+
+    /// Read the remote hosts to configure from a file, one per line
+    #[arg(long, value_name = "FILE")]
+    inventory: Option<String>,
+
+    /// Apply a profile captured with `glwiz snapshot`
+    #[arg(long, value_name = "FILE")]
+    profile: Option<String>,
+
+    /// Continue the previous interrupted run, skipping the tasks it completed
+    #[arg(long)]
+    resume: bool,
+
+    /// Wait for another running glwiz instance to finish instead of failing
+    #[arg(long)]
+    wait: bool,
+
+    /// Configure for this distribution (e.g. ubuntu, manjaro) instead of detecting it
+    #[arg(long, value_name = "ID")]
+    distro: Option<String>,
+
+    /// On NixOS, also generate a home-manager module
+    #[arg(long)]
+    home_manager: bool,
+
+    /// Upgrade the whole system before installing software
+    #[arg(long)]
+    upgrade: bool,
+
+    /// Install these package bundles (e.g. dev,security) instead of prompting for the software list
+    #[arg(long, value_name = "NAMES")]
+    bundles: Option<String>,
+
+    /// Remove the distribution's unwanted preinstalled packages (games, telemetry, office suites)
+    #[arg(long)]
+    debloat: bool,
+}
+
+/// Arguments for the preflight checks.
+#[derive(clap::Args, Debug)]
+struct PreflightArgs {
+    /// Check the system mounted at this directory (e.g. /mnt) instead of the running host
+    #[arg(long, value_name = "PATH")]
+    root: Option<String>,
+
+    /// Check for the packages of a profile captured with `glwiz snapshot`
+    #[arg(long, value_name = "FILE")]
+    profile: Option<String>,
+
+    /// Check for this distribution (e.g. ubuntu, manjaro) instead of detecting it
+    #[arg(long, value_name = "ID")]
+    distro: Option<String>,
+}
+
+/// Arguments for exporting the setup plan.
+#[derive(clap::Args, Debug)]
+struct ExportArgs {
+    /// Output format
+    #[arg(long, value_enum, default_value_t = ExportFormat::Sh)]
+    format: ExportFormat,
+
+    /// Write the export to this file instead of stdout (a directory for ansible)
+    #[arg(long, short, value_name = "PATH")]
+    output: Option<String>,
+}
+
+/// Formats the setup plan can be exported to.
+#[derive(ValueEnum, Clone, Copy, Debug)]
+enum ExportFormat {
+    /// A standalone POSIX shell script
+    Sh,
+    /// An Ansible playbook with the configuration files it deploys
+    Ansible,
 }
 
 fn main() {
     let args = Args::parse();
 
-    match gnu_linux_default_setup(args.allow_root) {
-        Ok(()) => {
-            println!("Setup completed successfully!");
+    let result = match args.command {
+        Some(Commands::Run(run_args)) => run(run_args),
+        Some(Commands::Export(export_args)) => export(export_args),
+        Some(Commands::Snapshot) => gnu_linux_snapshot().map(|profile| print!("{}", profile)),
+        Some(Commands::Preflight(preflight_args)) => preflight(preflight_args),
+        Some(Commands::Undo) => gnu_linux_undo().map_err(|e| format!("Undo failed: {}", e)),
+        None => run(args.run),
+    };
+
+    if let Err(e) = result {
+        eprintln!("{}", e);
+        process::exit(1);
+    }
+}
+
+// Runs the setup on this system, or on the hosts given with `--host` and `--inventory`.
+fn run(args: RunArgs) -> Result<(), String> {
+    setup(args)
+        .map(|()| println!("Setup completed successfully!"))
+        .map_err(|e| format!("Setup failed: {}", e))
+}
+
+fn setup(args: RunArgs) -> Result<(), String> {
+    let bundles = args
+        .bundles
+        .as_deref()
+        .map(parse_bundles)
+        .transpose()?
+        .unwrap_or_default();
+    let mut hosts = args.host;
+    if let Some(path) = args.inventory.as_deref() {
+        let contents = std::fs::read_to_string(path)
+            .map_err(|e| format!("Failed to read inventory {}: {}", path, e))?;
+        hosts.extend(parse_inventory(&contents));
+    }
+
+    if !hosts.is_empty() || args.inventory.is_some() {
+        let mut setup_args = Vec::new();
+        if args.allow_root {
+            setup_args.push("--allow-root");
+        }
+        if let Some(root) = args.root.as_deref() {
+            setup_args.extend(["--root", root]);
+        }
+        if args.resume {
+            setup_args.push("--resume");
+        }
+        if args.wait {
+            setup_args.push("--wait");
+        }
+        if let Some(distro) = args.distro.as_deref() {
+            setup_args.extend(["--distro", distro]);
+        }
+        if args.home_manager {
+            setup_args.push("--home-manager");
         }
-        Err(e) => {
-            eprintln!("Setup failed: {}", e);
-            process::exit(1);
+        if args.upgrade {
+            setup_args.push("--upgrade");
         }
+        if let Some(bundles) = args.bundles.as_deref() {
+            setup_args.extend(["--bundles", bundles]);
+        }
+        if args.debloat {
+            setup_args.push("--debloat");
+        }
+        if args.profile.is_some() {
+            return Err(
+                "--profile is not supported together with --host or --inventory".to_string(),
+            );
+        }
+        return gnu_linux_remote_setup(&hosts, &setup_args);
+    }
+
+    let target = match args.root.as_deref() {
+        Some(root) => Target::new(root)?,
+        None => Target::host(),
+    };
+    let profile = args.profile.as_deref().map(Profile::load).transpose()?;
+    if !bundles.is_empty()
+        && profile
+            .as_ref()
+            .is_some_and(|profile| !profile.packages.native.is_empty())
+    {
+        return Err(
+            "--bundles is not supported together with a profile that lists native packages"
+                .to_string(),
+        );
+    }
+    gnu_linux_default_setup(&SetupOptions {
+        allow_root: args.allow_root,
+        target,
+        profile,
+        resume: args.resume,
+        wait: args.wait,
+        distro: args.distro.as_deref().map(Distro::from_id).transpose()?,
+        home_manager: args.home_manager,
+        upgrade: args.upgrade,
+        bundles,
+        debloat: args.debloat,
+    })
+}
+
+// Runs the preflight checks for this system or the given root.
+fn preflight(args: PreflightArgs) -> Result<(), String> {
+    let target = match args.root.as_deref() {
+        Some(root) => Target::new(root)?,
+        None => Target::host(),
+    };
+    let profile = args.profile.as_deref().map(Profile::load).transpose()?;
+    gnu_linux_preflight(&SetupOptions {
+        target,
+        profile,
+        distro: args.distro.as_deref().map(Distro::from_id).transpose()?,
+        ..Default::default()
+    })
+    .map(|()| println!("Preflight checks passed."))
+}
+
+// Renders the default setup plan and prints it or writes it to the requested location.
+fn export(args: ExportArgs) -> Result<(), String> {
+    let plan = default_plan(default_sw_package());
+    match args.format {
+        ExportFormat::Sh => {
+            let script = export_sh(&plan).map_err(|e| format!("Export failed: {}", e))?;
+            match args.output.as_deref() {
+                Some(path) => write_export(
+                    std::path::Path::new("."),
+                    &ExportFile {
+                        path: path.to_string(),
+                        content: script,
+                    },
+                ),
+                None => {
+                    print!("{}", script);
+                    Ok(())
+                }
+            }
+        }
+        ExportFormat::Ansible => {
+            let dir = args
+                .output
+                .ok_or("Export failed: ansible export requires --output <DIR>")?;
+            let files = export_ansible(&plan).map_err(|e| format!("Export failed: {}", e))?;
+            for file in &files {
+                write_export(std::path::Path::new(&dir), file)?;
+            }
+            println!("Ansible playbook written to {}/playbook.yml", dir);
+            Ok(())
+        }
+    }
+}
+
+// Writes an exported file below `dir`, creating parent directories as needed.
+fn write_export(dir: &std::path::Path, file: &ExportFile) -> Result<(), String> {
+    let path = dir.join(&file.path);
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)
+            .map_err(|e| format!("Export failed: cannot create {}: {}", parent.display(), e))?;
     }
+    std::fs::write(&path, &file.content)
+        .map_err(|e| format!("Export failed: cannot write {}: {}", path.display(), e))
 }
@@ -0,0 +1,199 @@
+/*
+ * GLWiz - The ultimate post-installation setup assistant for GNU/Linux popular distros,
+ * streamlining your configuration process with ease and precision.
+ * 
+ * Copyright (C) 2025  Andrew Kushyk
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published
+ * by the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use glwiz::functionality::{
+    distro::{Distro, DistroFamily},
+    iptables::rules_path,
+    service::InitSystem,
+    target::Target,
+};
+use std::{fs, path::PathBuf};
+
+// Parses a fixture from tests/fixtures/os-release.
+fn fixture(name: &str) -> Distro {
+    let path = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/fixtures/os-release")
+        .join(name);
+    let contents = fs::read_to_string(&path).unwrap();
+    Distro::from_os_release(&contents).unwrap()
+}
+
+#[test]
+fn base_distributions_map_to_their_own_family() {
+    let arch = fixture("arch");
+    assert_eq!(arch.id, "arch");
+    assert_eq!(arch.family, Some(DistroFamily::Arch));
+    assert_eq!(arch.version_id, None);
+
+    let debian = fixture("debian-12");
+    assert_eq!(debian.family, Some(DistroFamily::Debian));
+    assert_eq!(debian.version_id.as_deref(), Some("12"));
+    assert_eq!(debian.codename.as_deref(), Some("bookworm"));
+
+    let fedora = fixture("fedora-40");
+    assert_eq!(fedora.family, Some(DistroFamily::Fedora));
+    assert_eq!(fedora.version_id.as_deref(), Some("40"));
+    assert_eq!(fedora.codename, None);
+    assert_eq!(fedora.name, "Fedora Linux 40 (Workstation Edition)");
+}
+
+#[test]
+fn opensuse_releases_map_to_the_suse_family() {
+    let tumbleweed = fixture("opensuse-tumbleweed");
+    assert_eq!(tumbleweed.id, "opensuse-tumbleweed");
+    assert_eq!(tumbleweed.family, Some(DistroFamily::Suse));
+    assert_eq!(tumbleweed.family_name(), "suse");
+
+    let leap = fixture("opensuse-leap-15.6");
+    assert_eq!(leap.family, Some(DistroFamily::Suse));
+    assert_eq!(leap.version_id.as_deref(), Some("15.6"));
+    assert_eq!(leap.to_string(), "openSUSE Leap 15.6 (suse family)");
+}
+
+#[test]
+fn derivatives_map_to_their_base_family() {
+    let cases = [
+        ("manjaro", "manjaro", DistroFamily::Arch),
+        ("endeavouros", "endeavouros", DistroFamily::Arch),
+        ("ubuntu-24.04", "ubuntu", DistroFamily::Debian),
+        ("linuxmint-22", "linuxmint", DistroFamily::Debian),
+        ("pop-22.04", "pop", DistroFamily::Debian),
+        ("nobara-40", "nobara", DistroFamily::Fedora),
+    ];
+    for (file, id, family) in cases {
+        let distro = fixture(file);
+        assert_eq!(distro.id, id, "{}", file);
+        assert_eq!(distro.family, Some(family), "{}", file);
+    }
+
+    let mint = fixture("linuxmint-22");
+    assert_eq!(mint.id_like, vec!["ubuntu", "debian"]);
+    assert_eq!(mint.codename.as_deref(), Some("wilma"));
+    assert_eq!(mint.to_string(), "Linux Mint 22 (debian family)");
+}
+
+#[test]
+fn unsupported_distributions_have_no_family() {
+    let slackware = fixture("slackware-15.0");
+    assert_eq!(slackware.id, "slackware");
+    assert_eq!(slackware.family, None);
+    assert_eq!(slackware.family_name(), "unknown");
+}
+
+#[test]
+fn enterprise_linux_is_not_treated_as_fedora() {
+    let rocky = fixture("rocky-9");
+    assert_eq!(rocky.id, "rocky");
+    assert_eq!(rocky.id_like, vec!["rhel", "centos", "fedora"]);
+    assert_eq!(rocky.family, None);
+    assert!(Distro::from_id("almalinux").is_err());
+    assert_eq!(fixture("nobara-40").family, Some(DistroFamily::Fedora));
+}
+
+#[test]
+fn alpine_uses_openrc_and_its_own_iptables_path() {
+    let alpine = fixture("alpine-3.20");
+    assert_eq!(alpine.family, Some(DistroFamily::Alpine));
+    assert_eq!(alpine.version_id.as_deref(), Some("3.20.3"));
+    assert_eq!(
+        DistroFamily::Alpine.default_init_system(),
+        InitSystem::OpenRc
+    );
+    assert_eq!(rules_path(alpine.family), "/etc/iptables/rules-save");
+}
+
+#[test]
+fn void_uses_runit() {
+    let void = fixture("void");
+    assert_eq!(void.family, Some(DistroFamily::Void));
+    assert_eq!(void.name, "Void Linux");
+    assert_eq!(void.version_id, None);
+    assert_eq!(DistroFamily::Void.default_init_system(), InitSystem::Runit);
+}
+
+#[test]
+fn gentoo_follows_its_portage_profile() {
+    let gentoo = fixture("gentoo");
+    assert_eq!(gentoo.family, Some(DistroFamily::Gentoo));
+    assert_eq!(gentoo.version_id.as_deref(), Some("2.17"));
+    assert_eq!(rules_path(gentoo.family), "/var/lib/iptables/rules-save");
+
+    let root = std::env::temp_dir().join(format!("glwiz-gentoo-{}", std::process::id()));
+    fs::create_dir_all(root.join("etc/portage")).unwrap();
+    let target = Target::new(root.to_str().unwrap()).unwrap();
+    std::os::unix::fs::symlink(
+        "../../var/db/repos/gentoo/profiles/default/linux/amd64/23.0/systemd",
+        root.join("etc/portage/make.profile"),
+    )
+    .unwrap();
+    let init = InitSystem::detect(&target, gentoo.family);
+    fs::remove_dir_all(&root).unwrap();
+    assert_eq!(init, InitSystem::Systemd);
+    assert_eq!(
+        InitSystem::detect(&target, gentoo.family),
+        InitSystem::OpenRc
+    );
+}
+
+#[test]
+fn nixos_is_recognized_without_a_package_manager_family() {
+    let nixos = fixture("nixos-24.05");
+    assert!(nixos.is_nixos());
+    assert_eq!(nixos.family, None);
+    assert_eq!(nixos.codename.as_deref(), Some("uakari"));
+    assert!(Distro::from_id("NixOS").unwrap().is_nixos());
+    assert!(!fixture("arch").is_nixos());
+}
+
+#[test]
+fn os_release_without_id_is_rejected() {
+    assert!(Distro::from_os_release("NAME=\"Nameless\"\n").is_err());
+}
+
+#[test]
+fn distro_override_accepts_ids_and_families() {
+    assert_eq!(
+        Distro::from_id("ubuntu").unwrap().family,
+        Some(DistroFamily::Debian)
+    );
+    assert_eq!(
+        Distro::from_id("fedora").unwrap().family,
+        Some(DistroFamily::Fedora)
+    );
+    assert!(Distro::from_id("haiku").is_err());
+}
+
+#[test]
+fn detect_reads_os_release_inside_a_target_root() {
+    let root = std::env::temp_dir().join(format!("glwiz-distro-test-{}", std::process::id()));
+    fs::create_dir_all(root.join("etc")).unwrap();
+    fs::copy(
+        PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/os-release/pop-22.04"),
+        root.join("etc/os-release"),
+    )
+    .unwrap();
+
+    let target = Target::new(root.to_str().unwrap()).unwrap();
+    let distro = Distro::detect(&target).unwrap();
+    assert_eq!(distro.id, "pop");
+    assert_eq!(distro.family, Some(DistroFamily::Debian));
+
+    fs::remove_dir_all(root).unwrap();
+}
@@ -0,0 +1,105 @@
+/*
+ * GLWiz - The ultimate post-installation setup assistant for GNU/Linux popular distros,
+ * streamlining your configuration process with ease and precision.
+ * 
+ * Copyright (C) 2025  Andrew Kushyk
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published
+ * by the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use glwiz::functionality::{
+    bundle::BUNDLES, distro::DistroFamily, flatpak::resolve_apps, package_manager::portage::atom,
+    package_map::PackageMap, prog_fun::default_sw_package,
+};
+use std::{fs, path::PathBuf};
+
+// Parses the bundled package table.
+fn bundled() -> PackageMap {
+    let path = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("configs/packages.toml");
+    PackageMap::parse(&fs::read_to_string(path).unwrap()).unwrap()
+}
+
+#[test]
+fn bundled_table_maps_names_that_differ() {
+    let map = bundled();
+    assert_eq!(map.lookup("fd", "debian").unwrap(), ["fd-find"]);
+    assert_eq!(map.lookup("python", "fedora").unwrap(), ["python3"]);
+    assert_eq!(map.lookup("vim", "fedora").unwrap(), ["vim-enhanced"]);
+    assert_eq!(
+        map.lookup("build-essential", "arch").unwrap(),
+        ["base-devel"]
+    );
+    assert_eq!(
+        map.lookup("base-devel", "debian").unwrap(),
+        ["build-essential"]
+    );
+    assert!(map.lookup("build-essential", "gentoo").unwrap().is_empty());
+}
+
+#[test]
+fn bundled_table_knows_the_default_packages() {
+    let map = bundled();
+    for family in [
+        "arch", "debian", "fedora", "suse", "alpine", "void", "gentoo", "nixos",
+    ] {
+        let resolution = map.resolve(default_sw_package(), family);
+        assert!(
+            resolution.unknown.is_empty(),
+            "{}: {:?}",
+            family,
+            resolution.unknown
+        );
+    }
+    let gentoo = map.resolve(default_sw_package(), "gentoo");
+    assert!(gentoo.packages.iter().all(|atom| atom.contains('/')));
+}
+
+#[test]
+fn bundled_table_gives_atoms_for_the_packages_the_setup_needs() {
+    let map = bundled();
+    let needed = DistroFamily::Gentoo.required_packages().iter().copied();
+    for package in needed.chain(["zram-init", "zram-generator", "flatpak"]) {
+        assert!(
+            atom(package, &map).contains('/'),
+            "{} has no Gentoo atom",
+            package
+        );
+    }
+}
+
+#[test]
+fn bundled_table_maps_desktop_apps_to_flathub() {
+    let (apps, unknown) = resolve_apps(&["firefox", "gimp", "mpv", "zsh"], &bundled());
+    assert_eq!(apps, ["org.mozilla.firefox", "org.gimp.GIMP", "io.mpv.Mpv"]);
+    assert_eq!(unknown, ["zsh"]);
+}
+
+#[test]
+fn bundled_table_knows_the_bundle_packages() {
+    let map = bundled();
+    for bundle in &BUNDLES {
+        for family in [
+            "arch", "debian", "fedora", "suse", "alpine", "void", "gentoo", "nixos",
+        ] {
+            let resolution = map.resolve(bundle.packages, family);
+            assert!(
+                resolution.unknown.is_empty(),
+                "{} on {}: {:?}",
+                bundle.name,
+                family,
+                resolution.unknown
+            );
+        }
+    }
+}